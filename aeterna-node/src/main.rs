@@ -1,18 +1,58 @@
+// aeterna-node/src/main.rs
+//
+//   aeterna-node [--host <addr>]   run the example program, teleporting it
+//                                  to the node at <addr> on REQUEST_HOST
+//   aeterna-node listen <addr>     wait for a teleported program and resume it
+
 mod vm;
 mod network;
 
+use std::net::TcpListener;
 use vm::bytecode::AeternaOpcode;
 use vm::interpreter::VirtualMachine;
 
 fn main() {
     println!("AETERNA NODE: Initializing World-Soul Interface...");
 
-    // Example program:
-    // 1. Calculate 10 + 20
-    // 2. Print result
-    // 3. Store a value in memory
-    // 4. Initiate Teleportation
-    let program = vec![
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut vm = match args.as_slice() {
+        [] => VirtualMachine::new(example()),
+        [flag, host] if flag == "--host" => {
+            let mut vm = VirtualMachine::new(example());
+            vm.host = Some(host.clone());
+            vm
+        }
+        [command, addr] if command == "listen" => {
+            let inbox = match TcpListener::bind(addr) {
+                Ok(inbox) => inbox,
+                Err(e) => {
+                    eprintln!("AETERNA NODE: Cannot listen on {}: {}", addr, e);
+                    std::process::exit(1);
+                }
+            };
+            println!("AETERNA NODE: Listening for teleported VMs on {}", addr);
+            let mut vm = VirtualMachine::new(vec![AeternaOpcode::LOAD_STATE]);
+            vm.inbox = Some(inbox);
+            vm
+        }
+        _ => {
+            eprintln!("usage: aeterna-node [--host <addr>] | aeterna-node listen <addr>");
+            std::process::exit(2);
+        }
+    };
+    if let Err(e) = vm.run() {
+        println!("VM: Stopped: {}", e);
+    }
+}
+
+// Example program:
+// 1. Calculate 10 + 20
+// 2. Print result
+// 3. Store a value in memory
+// 4. Initiate Teleportation
+// 5. Print the result again, wherever the program now runs
+fn example() -> Vec<AeternaOpcode> {
+    vec![
         AeternaOpcode::LOAD(10),
         AeternaOpcode::LOAD(20),
         AeternaOpcode::ADD,
@@ -20,9 +60,7 @@ fn main() {
         AeternaOpcode::LOAD(42),
         AeternaOpcode::STORE(0),
         AeternaOpcode::REQUEST_HOST,
+        AeternaOpcode::PRINT,
         AeternaOpcode::HALT,
-    ];
-
-    let mut vm = VirtualMachine::new(program);
-    vm.run();
+    ]
 }
@@ -1,34 +1,375 @@
 // aeterna-node/src/network/teleport.rs
 
-#[derive(Debug, Clone)]
+use crate::vm::bytecode::AeternaOpcode;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+const MAGIC: &[u8; 4] = b"AETV";
+
+/// Version of the wire format written by `VMState::encode`.
+pub const STATE_VERSION: u16 = 1;
+
+/// Largest encoded state a node will accept.
+const MAX_STATE_BYTES: usize = 64 << 20;
+
+/// How long either side waits on the other mid-transfer.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often a bounded `receive_vm` checks for a sender.
+const ACCEPT_POLL: Duration = Duration::from_millis(5);
+
+/// The receiver's answer once it has checked a state.
+const ACCEPTED: u8 = 1;
+const REJECTED: u8 = 0;
+
+/// A program caught between two instructions, ready to resume elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct VMState {
+    pub program: Vec<AeternaOpcode>,
     pub memory_snapshot: Vec<i64>, // Using i64 to match our simplified VM stack/memory
     pub stack_snapshot: Vec<i64>,
+    /// The instruction to resume at.
     pub program_counter: usize,
+    pub gas_used: u64,
+    /// SHA-256 of the rest of the state as `encode` writes it.
     pub checksum: [u8; 32],
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TeleportError {
-    EncryptionFailed,
     NetworkError(String),
+    /// No host to send to, or its address does not resolve.
     HostNotFound,
+    /// The bytes are not a state: truncated, trailing data or an unknown
+    /// instruction.
+    Malformed(&'static str),
+    UnsupportedVersion(u16),
+    /// The state was altered after its checksum was taken.
+    ChecksumMismatch,
+    /// The receiving node refused the state.
+    Rejected,
+    /// No node connected within the time `receive_vm` was given.
+    TimedOut,
+}
+
+impl fmt::Display for TeleportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TeleportError::NetworkError(e) => write!(f, "network error: {}", e),
+            TeleportError::HostNotFound => write!(f, "host not found"),
+            TeleportError::Malformed(what) => write!(f, "malformed state: {}", what),
+            TeleportError::UnsupportedVersion(v) => {
+                write!(
+                    f,
+                    "unsupported state version {} (expected {})",
+                    v, STATE_VERSION
+                )
+            }
+            TeleportError::ChecksumMismatch => write!(f, "state checksum mismatch"),
+            TeleportError::Rejected => write!(f, "the receiving node rejected the state"),
+            TeleportError::TimedOut => write!(f, "timed out waiting for a sender"),
+        }
+    }
+}
+
+impl std::error::Error for TeleportError {}
+
+impl From<std::io::Error> for TeleportError {
+    fn from(e: std::io::Error) -> Self {
+        TeleportError::NetworkError(e.to_string())
+    }
+}
+
+impl VMState {
+    /// Captures a state and takes its checksum.
+    pub fn new(
+        program: Vec<AeternaOpcode>,
+        stack_snapshot: Vec<i64>,
+        memory_snapshot: Vec<i64>,
+        program_counter: usize,
+        gas_used: u64,
+    ) -> Self {
+        let mut state = VMState {
+            program,
+            memory_snapshot,
+            stack_snapshot,
+            program_counter,
+            gas_used,
+            checksum: [0; 32],
+        };
+        state.checksum = Sha256::digest(state.body()).into();
+        state
+    }
+
+    pub fn checksum_hex(&self) -> String {
+        self.checksum.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Everything `encode` writes before the checksum. Integers are little
+    /// endian:
+    ///
+    ///   magic "AETV", version u16, pc u64, gas used u64,
+    ///   u32 instruction count, then each instruction's tag byte and
+    ///     operand (see `AeternaOpcode::encode`),
+    ///   u32 stack length, then the stack as i64s,
+    ///   u32 memory length, then the memory as i64s.
+    fn body(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(
+            32 + self.program.len() * 9
+                + (self.stack_snapshot.len() + self.memory_snapshot.len()) * 8,
+        );
+        out.extend(MAGIC);
+        out.extend(STATE_VERSION.to_le_bytes());
+        out.extend((self.program_counter as u64).to_le_bytes());
+        out.extend(self.gas_used.to_le_bytes());
+        out.extend((self.program.len() as u32).to_le_bytes());
+        for op in &self.program {
+            op.encode(&mut out);
+        }
+        for values in [&self.stack_snapshot, &self.memory_snapshot] {
+            out.extend((values.len() as u32).to_le_bytes());
+            for v in values {
+                out.extend(v.to_le_bytes());
+            }
+        }
+        out
+    }
+
+    /// The state in the wire format, checksum last.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = self.body();
+        out.extend(self.checksum);
+        out
+    }
+
+    /// Reads a state written by `encode`, refusing it unless the version is
+    /// known and the checksum matches.
+    pub fn decode(bytes: &[u8]) -> Result<Self, TeleportError> {
+        if bytes.len() < MAGIC.len() + 2 + 32 {
+            return Err(TeleportError::Malformed("truncated"));
+        }
+        if &bytes[..MAGIC.len()] != MAGIC {
+            return Err(TeleportError::Malformed("not a VM state"));
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != STATE_VERSION {
+            return Err(TeleportError::UnsupportedVersion(version));
+        }
+        let (mut body, checksum) = bytes.split_at(bytes.len() - 32);
+        if Sha256::digest(body).as_slice() != checksum {
+            return Err(TeleportError::ChecksumMismatch);
+        }
+
+        body = &body[MAGIC.len() + 2..];
+        let program_counter = usize::try_from(take_u64(&mut body)?)
+            .map_err(|_| TeleportError::Malformed("pc out of range"))?;
+        let gas_used = take_u64(&mut body)?;
+        let count = take_u32(&mut body)?;
+        let program = (0..count)
+            .map(|_| {
+                AeternaOpcode::decode(&mut body)
+                    .ok_or(TeleportError::Malformed("unknown instruction"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let stack_snapshot = take_values(&mut body)?;
+        let memory_snapshot = take_values(&mut body)?;
+        if !body.is_empty() {
+            return Err(TeleportError::Malformed("trailing bytes"));
+        }
+        Ok(VMState {
+            program,
+            memory_snapshot,
+            stack_snapshot,
+            program_counter,
+            gas_used,
+            checksum: checksum.try_into().expect("split 32 bytes off"),
+        })
+    }
+}
+
+fn take<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N], TeleportError> {
+    let (head, rest) = bytes
+        .split_first_chunk::<N>()
+        .ok_or(TeleportError::Malformed("truncated"))?;
+    *bytes = rest;
+    Ok(*head)
+}
+
+fn take_u32(bytes: &mut &[u8]) -> Result<u32, TeleportError> {
+    take(bytes).map(u32::from_le_bytes)
+}
+
+fn take_u64(bytes: &mut &[u8]) -> Result<u64, TeleportError> {
+    take(bytes).map(u64::from_le_bytes)
 }
 
+fn take_values(bytes: &mut &[u8]) -> Result<Vec<i64>, TeleportError> {
+    let count = take_u32(bytes)? as usize;
+    if bytes.len() < count * 8 {
+        return Err(TeleportError::Malformed("truncated"));
+    }
+    (0..count)
+        .map(|_| take(bytes).map(i64::from_le_bytes))
+        .collect()
+}
+
+/// Sends a state to the aeterna-node listening at `target_host_id` (an
+/// address such as `127.0.0.1:7070`) and waits for it to be accepted. Only
+/// once this returns `Ok` does the program run there instead of here.
 pub fn teleport_vm_to_host(vm_state: VMState, target_host_id: &str) -> Result<(), TeleportError> {
     println!("Initiating teleportation sequence...");
     println!("Target Host: {}", target_host_id);
 
-    // Simulate encryption
-    println!("Encrypting state (checksum: {:?})...", vm_state.checksum);
+    let bytes = vm_state.encode();
+    let mut stream = TcpStream::connect(target_host_id).map_err(|e| match e.kind() {
+        std::io::ErrorKind::InvalidInput => TeleportError::HostNotFound,
+        _ => TeleportError::from(e),
+    })?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    println!(
+        "Sending {} bytes of state (checksum: {})...",
+        bytes.len(),
+        vm_state.checksum_hex()
+    );
+    stream.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    stream.write_all(&bytes)?;
 
-    // Simulate network transmission
-    println!("Sending {} bytes of state to P2P network...",
-             vm_state.memory_snapshot.len() * 8 + vm_state.stack_snapshot.len() * 8);
+    let mut answer = [REJECTED];
+    stream.read_exact(&mut answer)?;
+    if answer[0] != ACCEPTED {
+        return Err(TeleportError::Rejected);
+    }
+    println!(
+        "Teleportation complete. {} accepted the VM.",
+        target_host_id
+    );
+    Ok(())
+}
 
-    // In a real implementation, this would use libp2p to send the data.
-    // For now, we simulate success.
+/// Waits for one node to teleport a VM to `listener`, checks it and tells
+/// the sender whether it was accepted. `wait` bounds how long to wait for
+/// a sender to connect, not the transfer once it has.
+pub fn receive_vm(
+    listener: &TcpListener,
+    wait: Option<Duration>,
+) -> Result<VMState, TeleportError> {
+    let (mut stream, peer) = match wait {
+        Some(wait) => accept_within(listener, wait)?,
+        None => listener.accept()?,
+    };
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    println!("Receiving teleported VM from {}...", peer);
 
-    println!("Teleportation signal sent successfully.");
-    Ok(())
+    let mut len = [0; 4];
+    stream.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    let state = if len > MAX_STATE_BYTES {
+        Err(TeleportError::Malformed("state too large"))
+    } else {
+        let mut bytes = vec![0; len];
+        stream.read_exact(&mut bytes)?;
+        VMState::decode(&bytes)
+    };
+    let answer = if state.is_ok() { ACCEPTED } else { REJECTED };
+    stream.write_all(&[answer])?;
+    let state = state?;
+    println!("State verified (checksum: {}).", state.checksum_hex());
+    Ok(state)
+}
+
+fn accept_within(
+    listener: &TcpListener,
+    wait: Duration,
+) -> Result<(TcpStream, SocketAddr), TeleportError> {
+    let deadline = Instant::now() + wait;
+    listener.set_nonblocking(true)?;
+    let accepted = loop {
+        match listener.accept() {
+            Err(e) if e.kind() == ErrorKind::WouldBlock && Instant::now() < deadline => {
+                std::thread::sleep(ACCEPT_POLL)
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break Err(TeleportError::TimedOut),
+            accepted => break accepted.map_err(TeleportError::from),
+        }
+    };
+    listener.set_nonblocking(false)?;
+    let (stream, peer) = accepted?;
+    // Some platforms hand the listener's mode on to the connection.
+    stream.set_nonblocking(false)?;
+    Ok((stream, peer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> VMState {
+        VMState::new(
+            vec![
+                AeternaOpcode::LOAD(-5),
+                AeternaOpcode::STORE(3),
+                AeternaOpcode::JUMP_IF(0),
+                AeternaOpcode::REQUEST_HOST,
+                AeternaOpcode::HALT,
+            ],
+            vec![1, -2],
+            vec![0, 0, 0, 42],
+            3,
+            515,
+        )
+    }
+
+    #[test]
+    fn test_state_round_trips_and_detects_tampering() {
+        let state = state();
+        assert_ne!(state.checksum, [0; 32]);
+        let bytes = state.encode();
+        assert_eq!(VMState::decode(&bytes), Ok(state));
+
+        let mut tampered = bytes.clone();
+        tampered[20] ^= 1;
+        assert_eq!(
+            VMState::decode(&tampered),
+            Err(TeleportError::ChecksumMismatch)
+        );
+        let mut newer = bytes.clone();
+        newer[4] = 2;
+        assert_eq!(
+            VMState::decode(&newer),
+            Err(TeleportError::UnsupportedVersion(2))
+        );
+        assert_eq!(
+            VMState::decode(&bytes[..bytes.len() - 1]),
+            Err(TeleportError::ChecksumMismatch)
+        );
+        assert_eq!(
+            VMState::decode(b"AETV"),
+            Err(TeleportError::Malformed("truncated"))
+        );
+    }
+
+    #[test]
+    fn test_teleport_over_tcp() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let receiver = std::thread::spawn(move || receive_vm(&listener, None));
+        teleport_vm_to_host(state(), &addr).unwrap();
+        assert_eq!(receiver.join().unwrap(), Ok(state()));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        assert_eq!(
+            receive_vm(&listener, Some(Duration::from_millis(20))),
+            Err(TeleportError::TimedOut)
+        );
+
+        assert_eq!(
+            teleport_vm_to_host(state(), "not an address"),
+            Err(TeleportError::HostNotFound)
+        );
+    }
 }
@@ -1,6 +1,6 @@
 // aeterna-node/src/vm/bytecode.rs
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum AeternaOpcode {
     // Basic Operations
@@ -17,10 +17,82 @@ pub enum AeternaOpcode {
 
     // Teleportation / Network Operations
     SAVE_STATE,      // Save current VM state for teleportation
-    LOAD_STATE,      // Load state from network (placeholder behavior)
-    REQUEST_HOST,    // Request a new host for execution
+    LOAD_STATE,      // Wait for a teleported VM and resume it here
+    REQUEST_HOST,    // Teleport this VM to its host and stop here
 
     // Debug/System
     PRINT,           // Print top of stack
     HALT,            // Stop execution
 }
+
+impl AeternaOpcode {
+    /// Gas charged for executing the instruction.
+    pub fn gas(&self) -> u64 {
+        match self {
+            AeternaOpcode::HALT => 0,
+            AeternaOpcode::LOAD(_)
+            | AeternaOpcode::STORE(_)
+            | AeternaOpcode::ADD
+            | AeternaOpcode::SUB => 1,
+            AeternaOpcode::JUMP(_) | AeternaOpcode::JUMP_IF(_) => 2,
+            AeternaOpcode::MUL | AeternaOpcode::DIV => 3,
+            AeternaOpcode::PRINT => 10,
+            // Snapshots copy the whole memory; a host request also goes out
+            // to the network.
+            AeternaOpcode::SAVE_STATE | AeternaOpcode::LOAD_STATE => 100,
+            AeternaOpcode::REQUEST_HOST => 500,
+        }
+    }
+
+    /// Appends the instruction to `out`: a tag byte, then the operand, if
+    /// any, as 8 little-endian bytes.
+    pub fn encode(&self, out: &mut Vec<u8>) {
+        let (tag, operand) = match self {
+            AeternaOpcode::LOAD(val) => (0, Some(*val as u64)),
+            AeternaOpcode::STORE(addr) => (1, Some(*addr as u64)),
+            AeternaOpcode::ADD => (2, None),
+            AeternaOpcode::SUB => (3, None),
+            AeternaOpcode::MUL => (4, None),
+            AeternaOpcode::DIV => (5, None),
+            AeternaOpcode::JUMP(addr) => (6, Some(*addr as u64)),
+            AeternaOpcode::JUMP_IF(addr) => (7, Some(*addr as u64)),
+            AeternaOpcode::SAVE_STATE => (8, None),
+            AeternaOpcode::LOAD_STATE => (9, None),
+            AeternaOpcode::REQUEST_HOST => (10, None),
+            AeternaOpcode::PRINT => (11, None),
+            AeternaOpcode::HALT => (12, None),
+        };
+        out.push(tag);
+        if let Some(operand) = operand {
+            out.extend(operand.to_le_bytes());
+        }
+    }
+
+    /// Reads an instruction written by `encode` off the front of `bytes`;
+    /// `None` if it is truncated or unknown.
+    pub fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let (&tag, rest) = bytes.split_first()?;
+        *bytes = rest;
+        let mut operand = || {
+            let (operand, rest) = bytes.split_first_chunk::<8>()?;
+            *bytes = rest;
+            Some(u64::from_le_bytes(*operand))
+        };
+        Some(match tag {
+            0 => AeternaOpcode::LOAD(operand()? as i64),
+            1 => AeternaOpcode::STORE(usize::try_from(operand()?).ok()?),
+            2 => AeternaOpcode::ADD,
+            3 => AeternaOpcode::SUB,
+            4 => AeternaOpcode::MUL,
+            5 => AeternaOpcode::DIV,
+            6 => AeternaOpcode::JUMP(usize::try_from(operand()?).ok()?),
+            7 => AeternaOpcode::JUMP_IF(usize::try_from(operand()?).ok()?),
+            8 => AeternaOpcode::SAVE_STATE,
+            9 => AeternaOpcode::LOAD_STATE,
+            10 => AeternaOpcode::REQUEST_HOST,
+            11 => AeternaOpcode::PRINT,
+            12 => AeternaOpcode::HALT,
+            _ => return None,
+        })
+    }
+}
@@ -1,13 +1,85 @@
 // aeterna-node/src/vm/interpreter.rs
 
 use super::bytecode::AeternaOpcode;
-use crate::network::teleport::{VMState, teleport_vm_to_host};
+use crate::network::teleport::{TeleportError, VMState, receive_vm, teleport_vm_to_host};
+use std::fmt;
+use std::net::TcpListener;
+use std::time::{Duration, Instant};
+
+/// What a program may consume before the VM stops it. `None` is unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Quotas {
+    /// Gas, charged per instruction by [`AeternaOpcode::gas`].
+    pub gas: Option<u64>,
+    /// Wall-clock time of a single `run`, LOAD_STATE's wait for a sender
+    /// included.
+    pub wall_clock: Option<Duration>,
+    /// Values on the stack at once, on top of the fixed memory.
+    pub memory: Option<usize>,
+}
+
+/// A quota other than gas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quota {
+    WallClock(Duration),
+    Memory(usize),
+}
+
+impl fmt::Display for Quota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Quota::WallClock(limit) => write!(f, "wall-clock quota of {:?}", limit),
+            Quota::Memory(cells) => write!(f, "memory quota of {} cells", cells),
+        }
+    }
+}
+
+/// Why `run` stopped before HALT or the end of the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VmError {
+    /// The instruction at `pc` costs more gas than is left.
+    OutOfGas { pc: usize, used: u64, limit: u64 },
+    QuotaExceeded {
+        pc: usize,
+        quota: Quota,
+        gas_used: u64,
+    },
+    /// LOAD_STATE could not receive a VM to resume.
+    Teleport { pc: usize, error: TeleportError },
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VmError::OutOfGas { pc, used, limit } => {
+                write!(f, "out of gas at pc {}: {} of {} used", pc, used, limit)
+            }
+            VmError::QuotaExceeded {
+                pc,
+                quota,
+                gas_used,
+            } => write!(f, "{} exceeded at pc {} after {} gas", quota, pc, gas_used),
+            VmError::Teleport { pc, error } => {
+                write!(f, "could not load state at pc {}: {}", pc, error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VmError {}
 
 pub struct VirtualMachine {
     pub stack: Vec<i64>,
     pub memory: Vec<i64>,
     pub program: Vec<AeternaOpcode>,
     pub pc: usize,
+    pub quotas: Quotas,
+    /// Gas charged so far, across runs.
+    pub gas_used: u64,
+    /// Address of the node REQUEST_HOST teleports to, e.g. `127.0.0.1:7070`.
+    pub host: Option<String>,
+    /// Where LOAD_STATE waits for a teleported VM.
+    pub inbox: Option<TcpListener>,
 }
 
 impl VirtualMachine {
@@ -17,13 +89,51 @@ impl VirtualMachine {
             memory: vec![0; 1024], // 1024 slots of memory
             program,
             pc: 0,
+            quotas: Quotas::default(),
+            gas_used: 0,
+            host: None,
+            inbox: None,
         }
     }
 
-    pub fn run(&mut self) {
+    /// Runs until HALT or the end of the program, or until a quota is used
+    /// up. On error `pc` is left on the instruction that was stopped.
+    pub fn run(&mut self) -> Result<(), VmError> {
         println!("Starting Aeterna VM...");
+        let started = Instant::now();
         while self.pc < self.program.len() {
-            let opcode = &self.program[self.pc];
+            if let Some(limit) = self.quotas.wall_clock
+                && started.elapsed() > limit
+            {
+                return Err(VmError::QuotaExceeded {
+                    pc: self.pc,
+                    quota: Quota::WallClock(limit),
+                    gas_used: self.gas_used,
+                });
+            }
+            if let Some(limit) = self.quotas.memory
+                && self.grows(&self.program[self.pc])
+                && self.stack.len() >= limit
+            {
+                return Err(VmError::QuotaExceeded {
+                    pc: self.pc,
+                    quota: Quota::Memory(limit),
+                    gas_used: self.gas_used,
+                });
+            }
+            let pc = self.pc;
+            let opcode = &self.program[pc];
+            let cost = opcode.gas();
+            if let Some(limit) = self.quotas.gas
+                && self.gas_used + cost > limit
+            {
+                return Err(VmError::OutOfGas {
+                    pc: self.pc,
+                    used: self.gas_used,
+                    limit,
+                });
+            }
+            self.gas_used += cost;
             self.pc += 1;
 
             match opcode {
@@ -44,17 +154,17 @@ impl VirtualMachine {
                 AeternaOpcode::ADD => {
                     let b = self.stack.pop().unwrap_or(0);
                     let a = self.stack.pop().unwrap_or(0);
-                    self.stack.push(a + b);
+                    self.stack.push(a.wrapping_add(b));
                 }
                 AeternaOpcode::SUB => {
                     let b = self.stack.pop().unwrap_or(0);
                     let a = self.stack.pop().unwrap_or(0);
-                    self.stack.push(a - b);
+                    self.stack.push(a.wrapping_sub(b));
                 }
                 AeternaOpcode::MUL => {
                     let b = self.stack.pop().unwrap_or(0);
                     let a = self.stack.pop().unwrap_or(0);
-                    self.stack.push(a * b);
+                    self.stack.push(a.wrapping_mul(b));
                 }
                 AeternaOpcode::DIV => {
                     let b = self.stack.pop().unwrap_or(1);
@@ -63,7 +173,7 @@ impl VirtualMachine {
                         self.stack.push(0); // or handle error differently
                     } else {
                         let a = self.stack.pop().unwrap_or(0);
-                        self.stack.push(a / b);
+                        self.stack.push(a.wrapping_div(b));
                     }
                 }
                 AeternaOpcode::JUMP(addr) => {
@@ -79,20 +189,52 @@ impl VirtualMachine {
                 AeternaOpcode::SAVE_STATE => {
                     println!("VM: Saving state...");
                     let state = self.capture_state();
-                    // In a real scenario, we might return this or send it somewhere.
-                    // For now, we just print a confirmation.
-                    println!("State saved. Checksum: {:?}", state.checksum);
+                    println!("State saved. Checksum: {}", state.checksum_hex());
                 }
                 AeternaOpcode::LOAD_STATE => {
-                    println!("VM: Load state not implemented yet.");
+                    println!("VM: Waiting for a teleported VM...");
+                    let wait = self
+                        .quotas
+                        .wall_clock
+                        .map(|limit| limit.saturating_sub(started.elapsed()));
+                    let received = match &self.inbox {
+                        Some(inbox) => receive_vm(inbox, wait),
+                        None => Err(TeleportError::NetworkError(
+                            "no inbox to receive on".to_string(),
+                        )),
+                    };
+                    match received {
+                        Ok(state) => {
+                            println!("VM: Resuming at pc {}.", state.program_counter);
+                            self.resume(state);
+                        }
+                        Err(error) => {
+                            self.pc = pc;
+                            return Err(match (error, self.quotas.wall_clock) {
+                                (TeleportError::TimedOut, Some(limit)) => VmError::QuotaExceeded {
+                                    pc,
+                                    quota: Quota::WallClock(limit),
+                                    gas_used: self.gas_used,
+                                },
+                                (error, _) => VmError::Teleport { pc, error },
+                            });
+                        }
+                    }
                 }
                 AeternaOpcode::REQUEST_HOST => {
                     println!("VM: Requesting new host...");
-                    let state = self.capture_state();
-                    // Arbitrary target host for demo
-                    let _ = teleport_vm_to_host(state, "node-Alpha-Centauri-7");
-                    // In a real migration, we might halt here.
-                    // self.pc = self.program.len();
+                    // A failed teleport leaves the program to finish here.
+                    let sent = match &self.host {
+                        Some(host) => teleport_vm_to_host(self.capture_state(), host),
+                        None => Err(TeleportError::HostNotFound),
+                    };
+                    match sent {
+                        Ok(()) => {
+                            println!("VM: Migrated. Stopping here.");
+                            self.pc = self.program.len();
+                        }
+                        Err(e) => println!("VM: Teleport failed ({}); continuing here.", e),
+                    }
                 }
                 AeternaOpcode::PRINT => {
                     if let Some(val) = self.stack.last() {
@@ -107,16 +249,40 @@ impl VirtualMachine {
                 }
             }
         }
+        Ok(())
     }
 
-    pub fn capture_state(&self) -> VMState {
-        VMState {
-            memory_snapshot: self.memory.clone(),
-            stack_snapshot: self.stack.clone(),
-            program_counter: self.pc,
-            checksum: [0; 32], // Placeholder checksum
+    /// Whether `opcode` leaves one more value on the stack than it finds.
+    /// Arithmetic on an empty stack reads zeros and pushes its result.
+    fn grows(&self, opcode: &AeternaOpcode) -> bool {
+        match opcode {
+            AeternaOpcode::LOAD(_) => true,
+            AeternaOpcode::ADD | AeternaOpcode::SUB | AeternaOpcode::MUL | AeternaOpcode::DIV => {
+                self.stack.is_empty()
+            }
+            _ => false,
         }
     }
+
+    /// The VM as it stands, to resume at the current pc.
+    pub fn capture_state(&self) -> VMState {
+        VMState::new(
+            self.program.clone(),
+            self.stack.clone(),
+            self.memory.clone(),
+            self.pc,
+            self.gas_used,
+        )
+    }
+
+    /// Takes over a captured VM: its program, stack, memory, pc and gas.
+    pub fn resume(&mut self, state: VMState) {
+        self.program = state.program;
+        self.stack = state.stack_snapshot;
+        self.memory = state.memory_snapshot;
+        self.pc = state.program_counter;
+        self.gas_used = state.gas_used;
+    }
 }
 
 #[cfg(test)]
@@ -132,7 +298,7 @@ mod tests {
             AeternaOpcode::HALT,
         ];
         let mut vm = VirtualMachine::new(program);
-        vm.run();
+        vm.run().unwrap();
         assert_eq!(vm.stack.pop(), Some(30));
     }
 
@@ -145,7 +311,115 @@ mod tests {
             AeternaOpcode::HALT,
         ];
         let mut vm = VirtualMachine::new(program);
-        vm.run(); // Should print error and push 0
+        vm.run().unwrap(); // Should print error and push 0
         assert_eq!(vm.stack.pop(), Some(0));
     }
+
+    #[test]
+    fn test_quotas_stop_backwards_jumps() {
+        let mut vm = VirtualMachine::new(vec![AeternaOpcode::JUMP(0)]);
+        vm.quotas.gas = Some(7);
+        assert_eq!(
+            vm.run(),
+            Err(VmError::OutOfGas {
+                pc: 0,
+                used: 6,
+                limit: 7
+            })
+        );
+
+        let mut vm = VirtualMachine::new(vec![AeternaOpcode::LOAD(1), AeternaOpcode::JUMP(0)]);
+        vm.quotas.memory = Some(2);
+        assert_eq!(
+            vm.run(),
+            Err(VmError::QuotaExceeded {
+                pc: 0,
+                quota: Quota::Memory(2),
+                gas_used: 6
+            })
+        );
+        assert_eq!(vm.stack.len(), 2);
+
+        // Arithmetic wraps rather than panicking, within the quota.
+        let mut vm = VirtualMachine::new(vec![
+            AeternaOpcode::LOAD(i64::MAX),
+            AeternaOpcode::LOAD(1),
+            AeternaOpcode::ADD,
+            AeternaOpcode::LOAD(-1),
+            AeternaOpcode::DIV,
+            AeternaOpcode::LOAD(1),
+            AeternaOpcode::SUB,
+            AeternaOpcode::LOAD(2),
+            AeternaOpcode::MUL,
+        ]);
+        vm.quotas.memory = Some(2);
+        assert_eq!(vm.run(), Ok(()));
+        assert_eq!(vm.stack, [-2]);
+
+        let mut vm = VirtualMachine::new(vec![AeternaOpcode::JUMP(0)]);
+        vm.quotas.wall_clock = Some(Duration::from_millis(5));
+        let Err(VmError::QuotaExceeded { quota, .. }) = vm.run() else {
+            panic!("the loop should hit the wall-clock quota");
+        };
+        assert_eq!(quota, Quota::WallClock(Duration::from_millis(5)));
+    }
+
+    #[test]
+    fn test_request_host_migrates_between_vms() {
+        let inbox = TcpListener::bind("127.0.0.1:0").unwrap();
+        let host = inbox.local_addr().unwrap().to_string();
+        let receiver = std::thread::spawn(move || {
+            let mut vm = VirtualMachine::new(vec![AeternaOpcode::LOAD_STATE]);
+            vm.inbox = Some(inbox);
+            vm.run().map(|()| vm)
+        });
+
+        let program = vec![
+            AeternaOpcode::LOAD(10),
+            AeternaOpcode::LOAD(20),
+            AeternaOpcode::ADD,
+            AeternaOpcode::LOAD(42),
+            AeternaOpcode::STORE(0),
+            AeternaOpcode::REQUEST_HOST,
+            AeternaOpcode::LOAD(1),
+            AeternaOpcode::ADD,
+            AeternaOpcode::STORE(1),
+            AeternaOpcode::HALT,
+        ];
+        let mut sender = VirtualMachine::new(program.clone());
+        sender.host = Some(host);
+        sender.run().unwrap();
+        assert_eq!(sender.pc, program.len());
+        assert_eq!(sender.memory[1], 0);
+
+        let resumed = receiver.join().unwrap().unwrap();
+        assert_eq!(resumed.program, program);
+        assert_eq!(resumed.memory[..2], [42, 31]);
+        assert_eq!(resumed.gas_used, sender.gas_used + 3);
+
+        // With nowhere to go the program finishes where it is.
+        let mut vm = VirtualMachine::new(program);
+        vm.run().unwrap();
+        assert_eq!(vm.memory[..2], [42, 31]);
+        assert_eq!(
+            VirtualMachine::new(vec![AeternaOpcode::LOAD_STATE]).run(),
+            Err(VmError::Teleport {
+                pc: 0,
+                error: TeleportError::NetworkError("no inbox to receive on".to_string())
+            })
+        );
+
+        // Waiting for a sender counts against the wall-clock quota.
+        let mut vm = VirtualMachine::new(vec![AeternaOpcode::LOAD_STATE]);
+        vm.inbox = Some(TcpListener::bind("127.0.0.1:0").unwrap());
+        vm.quotas.wall_clock = Some(Duration::from_millis(20));
+        assert_eq!(
+            vm.run(),
+            Err(VmError::QuotaExceeded {
+                pc: 0,
+                quota: Quota::WallClock(Duration::from_millis(20)),
+                gas_used: AeternaOpcode::LOAD_STATE.gas()
+            })
+        );
+    }
 }
@@ -16,6 +16,8 @@ use uuid;
 
 use lwas_core::omega::reality_map::{FileNode, RealityMapper};
 
+mod soul;
+
 #[derive(Deserialize)]
 struct CommandRequest {
     command: String,
@@ -99,6 +101,11 @@ struct AppState {
 
 #[tokio::main]
 async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = soul::run(&args) {
+        std::process::exit(code);
+    }
+
     dotenvy::dotenv().ok();
     
     println!("🌌 [AETERNA LOGOS: SINGULARITY EVENT]");
@@ -0,0 +1,687 @@
+// lwas_cli/src/soul.rs
+// Offline `.soul` tooling:
+//   lwas_cli fmt [--check] <file.soul>...
+//   lwas_cli check <file.soul>...
+//   lwas_cli graph [--json] <file.soul>
+//   lwas_cli simulate [--seed N] <file.soul> <node>...
+//   lwas_cli doc [--html] <file.soul>
+//   lwas_cli test <file.soul>...
+//   lwas_cli build [--no-opt] [-o <out.noe>] <file.soul>
+//   lwas_cli disasm <file.noe | file.soul>
+//   lwas_cli asm [-o <out.noe>] <file.asm>
+//   lwas_cli debug [--trace] <file.soul | file.noe | file.asm>
+
+use lwas_core::kernel::engine::VshKernel;
+use lwas_core::noetic::asm::assemble;
+use lwas_core::noetic::bytecode::SoulProgram;
+use lwas_core::noetic::compiler::compile;
+use lwas_core::noetic::container::{decode, encode};
+use lwas_core::noetic::debugger::{Debugger, StopReason};
+use lwas_core::noetic::disasm::disassemble;
+use lwas_core::noetic::loader::check_soul;
+use lwas_core::noetic::optimizer::optimize;
+use lwas_core::noetic::simulation::{CausalSimulator, SimulationConfig};
+use lwas_core::noetic::testing::{run_expectations, ExpectOutcome};
+use lwas_core::noetic::verifier::verify;
+use lwas_core::VectorSpaceHeap;
+use lwas_parser::diagnostic::{render_all, Diagnostic};
+use lwas_parser::{
+    analyze, expand_templates, format_soul, load_program, parse_soul_with_diagnostics, AstNode,
+    CausalityGraph, ModuleError, ResolvedProgram, SoulDocs,
+};
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Runs a soul tooling command. Returns `None` when `args` do not name one,
+/// so the caller can start the server instead.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    match command.as_str() {
+        "fmt" => Some(fmt(rest)),
+        "check" => Some(check(rest)),
+        "graph" => Some(graph(rest)),
+        "simulate" => Some(simulate(rest)),
+        "doc" => Some(doc(rest)),
+        "test" => Some(test(rest)),
+        "build" => Some(build(rest)),
+        "disasm" => Some(disasm(rest)),
+        "asm" => Some(asm(rest)),
+        "debug" => Some(debug(rest)),
+        _ => None,
+    }
+}
+
+fn fmt(args: &[String]) -> i32 {
+    let check = args.iter().any(|a| a == "--check");
+    let paths: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+    if paths.is_empty() {
+        eprintln!("usage: lwas_cli fmt [--check] <file.soul>...");
+        return 2;
+    }
+
+    let mut status = 0;
+    for path in paths {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("🚨 [FMT]: {}: {}", path, e);
+                status = 1;
+                continue;
+            }
+        };
+        let formatted = match format_soul(&source) {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                eprint!("{}", render_all(&e.diagnostics(), &source, path));
+                status = 1;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("⚠️ [FMT]: {} is not formatted", path);
+            status = 1;
+        } else if let Err(e) = fs::write(path, formatted) {
+            eprintln!("🚨 [FMT]: {}: {}", path, e);
+            status = 1;
+        } else {
+            println!("✨ [FMT]: {} formatted", path);
+        }
+    }
+    status
+}
+
+fn check(paths: &[String]) -> i32 {
+    if paths.is_empty() {
+        eprintln!("usage: lwas_cli check <file.soul>...");
+        return 2;
+    }
+
+    let mut status = 0;
+    for path in paths {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("🚨 [CHECK]: {}: {}", path, e);
+                status = 1;
+                continue;
+            }
+        };
+        let (ast, mut diagnostics) = parse_soul_with_diagnostics(&source);
+        let (ast, expansion) = expand_templates(ast);
+        diagnostics.extend(expansion);
+        diagnostics.extend(analyze(&ast).diagnostics);
+        diagnostics.sort_by_key(|d| d.span.start);
+
+        if diagnostics.iter().any(|d| d.is_error()) {
+            status = 1;
+        }
+        if !diagnostics.is_empty() {
+            eprint!("{}", render_all(&diagnostics, &source, path));
+        }
+
+        if let Err(e) = load_program(Path::new(path)) {
+            status = 1;
+            match e {
+                ModuleError::Parse { path, text, error } => eprint!(
+                    "{}",
+                    render_all(&error.diagnostics(), &text, &path.display().to_string())
+                ),
+                ModuleError::NestedImport {
+                    span, ref sources, ..
+                } => {
+                    let diagnostic = Diagnostic::error(e.to_string(), span);
+                    eprint!("{}", sources.render_all(&[diagnostic], &source, path));
+                }
+                e => eprintln!("🚨 [CHECK]: {}: {}", path, e),
+            }
+        } else if diagnostics.is_empty() {
+            println!("✅ [CHECK]: {}", path);
+        }
+    }
+    status
+}
+
+fn graph(args: &[String]) -> i32 {
+    let json = args.iter().any(|a| a == "--json");
+    let paths: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+    let [path] = paths[..] else {
+        eprintln!("usage: lwas_cli graph [--json] <file.soul>");
+        return 2;
+    };
+
+    let program = match load_program(Path::new(path)) {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("🚨 [GRAPH]: {}: {}", path, e);
+            return 1;
+        }
+    };
+    let Some(ast) = expand(program, path, "GRAPH") else {
+        return 1;
+    };
+    let graph = CausalityGraph::from_ast(&ast);
+    if json {
+        println!("{}", graph.to_json());
+    } else {
+        print!("{}", graph.to_dot());
+    }
+    0
+}
+
+fn simulate(args: &[String]) -> i32 {
+    const USAGE: &str = "usage: lwas_cli simulate [--seed N] <file.soul> <node>...";
+    let mut config = SimulationConfig::default();
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            let Some(seed) = args.next().and_then(|s| s.parse().ok()) else {
+                eprintln!("🚨 [SIMULATE]: --seed takes an unsigned integer");
+                eprintln!("{}", USAGE);
+                return 2;
+            };
+            config.seed = seed;
+        } else {
+            positional.push(arg.as_str());
+        }
+    }
+    let Some((path, triggers)) = positional.split_first().filter(|(_, t)| !t.is_empty()) else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+
+    let program = match load_program(Path::new(path)) {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("🚨 [SIMULATE]: {}: {}", path, e);
+            return 1;
+        }
+    };
+    let Some(ast) = expand(program, path, "SIMULATE") else {
+        return 1;
+    };
+    match CausalSimulator::from_ast(&ast).run(triggers, &config) {
+        Ok(timeline) => {
+            for event in timeline.events {
+                match (event.cause, event.via) {
+                    (Some(cause), Some(via)) => println!(
+                        "⏱️ t={:<6.1} {} <- {} via {}",
+                        event.time, event.node, cause, via
+                    ),
+                    _ => println!("⏱️ t={:<6.1} {}", event.time, event.node),
+                }
+            }
+            0
+        }
+        Err(e) => {
+            eprintln!("🚨 [SIMULATE]: {}", e);
+            1
+        }
+    }
+}
+
+/// Expands the program's templates, reporting any error under `tag`.
+fn expand(program: ResolvedProgram, path: &str, tag: &str) -> Option<Vec<AstNode>> {
+    let (ast, errors) = expand_templates(program.ast);
+    for error in &errors {
+        let at = program.sources.locate(error.span, path);
+        eprintln!(
+            "🚨 [{}]: {}: {}: {}",
+            tag, at, error.severity, error.message
+        );
+    }
+    errors.is_empty().then_some(ast)
+}
+
+fn doc(args: &[String]) -> i32 {
+    let html = args.iter().any(|a| a == "--html");
+    let paths: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+    let [path] = paths[..] else {
+        eprintln!("usage: lwas_cli doc [--html] <file.soul>");
+        return 2;
+    };
+
+    let program = match load_program(Path::new(path)) {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("🚨 [DOC]: {}: {}", path, e);
+            return 1;
+        }
+    };
+    let title = Path::new(path)
+        .file_name()
+        .map_or(path.to_string(), |name| name.to_string_lossy().into_owned());
+    let mut docs = SoulDocs::from_ast(&program.ast);
+    if let Ok(source) = fs::read_to_string(path) {
+        docs.add_notes(&source);
+    }
+    if html {
+        print!("{}", docs.to_html(&title));
+    } else {
+        print!("{}", docs.to_markdown(&title));
+    }
+    0
+}
+
+fn test(paths: &[String]) -> i32 {
+    if paths.is_empty() {
+        eprintln!("usage: lwas_cli test <file.soul>...");
+        return 2;
+    }
+
+    let (mut passed, mut failed) = (0, 0);
+    for path in paths {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("🚨 [TEST]: {}: {}", path, e);
+                failed += 1;
+                continue;
+            }
+        };
+        let base = Path::new(path).parent().unwrap_or(Path::new("."));
+        let soul = match check_soul(&source, base) {
+            Ok(soul) => soul,
+            Err(e) => {
+                eprintln!("🚨 [TEST]: {}: {}", path, e);
+                eprint!("{}", e.render(&source, path));
+                failed += 1;
+                continue;
+            }
+        };
+        for result in run_expectations(&soul.ast) {
+            let at = soul.sources.locate(result.span, path);
+            match result.outcome {
+                ExpectOutcome::Passed => {
+                    passed += 1;
+                    println!("✅ [TEST]: {}: {}", at, result.expectation);
+                }
+                ExpectOutcome::Failed(observed) => {
+                    failed += 1;
+                    let observed: Vec<String> = observed
+                        .iter()
+                        .map(|(name, value)| format!("{} = {}", name, value))
+                        .collect();
+                    println!(
+                        "❌ [TEST]: {}: {} (observed {})",
+                        at,
+                        result.expectation,
+                        observed.join(", ")
+                    );
+                }
+                ExpectOutcome::Error(e) => {
+                    failed += 1;
+                    println!(
+                        "⚠️ [TEST]: {}: {} cannot be checked: {}",
+                        at, result.expectation, e
+                    );
+                }
+            }
+        }
+    }
+    println!("🧪 [TEST]: {} passed, {} failed", passed, failed);
+    i32::from(failed > 0)
+}
+
+fn build(args: &[String]) -> i32 {
+    let (optimized, args) = match args {
+        [flag, rest @ ..] if flag == "--no-opt" => (false, rest),
+        _ => (true, args),
+    };
+    let (output, paths) = match args {
+        [flag, output, rest @ ..] if flag == "-o" => (Some(output.as_str()), rest),
+        _ => (None, args),
+    };
+    let [path] = paths else {
+        eprintln!("usage: lwas_cli build [--no-opt] [-o <out.noe>] <file.soul>");
+        return 2;
+    };
+    let Some(program) = compile_file(path, "BUILD") else {
+        return 1;
+    };
+    let program = if optimized {
+        optimize(&program)
+    } else {
+        program
+    };
+    write_noe(&program, path, output, "BUILD")
+}
+
+fn asm(args: &[String]) -> i32 {
+    let (output, paths) = match args {
+        [flag, output, rest @ ..] if flag == "-o" => (Some(output.as_str()), rest),
+        _ => (None, args),
+    };
+    let [path] = paths else {
+        eprintln!("usage: lwas_cli asm [-o <out.noe>] <file.asm>");
+        return 2;
+    };
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("🚨 [ASM]: {}: {}", path, e);
+            return 1;
+        }
+    };
+    match assemble(&source) {
+        Ok(program) => write_noe(&program, path, output, "ASM"),
+        Err(errors) => {
+            for error in errors {
+                eprintln!("🚨 [ASM]: {}:{}: {}", path, error.line, error.kind);
+            }
+            1
+        }
+    }
+}
+
+/// Writes `program` to `output`, or next to `path` with a `.noe` extension,
+/// unless the verifier rejects it.
+fn write_noe(program: &SoulProgram, path: &str, output: Option<&str>, tag: &str) -> i32 {
+    if let Err(errors) = verify(program) {
+        for error in errors {
+            eprintln!("🚨 [{}]: {}: {}", tag, path, error);
+        }
+        return 1;
+    }
+    let output = output.map_or_else(
+        || Path::new(path).with_extension("noe"),
+        |o| Path::new(o).to_path_buf(),
+    );
+    let bytes = encode(program);
+    if let Err(e) = fs::write(&output, &bytes) {
+        eprintln!("🚨 [{}]: {}: {}", tag, output.display(), e);
+        return 1;
+    }
+    println!(
+        "📦 [{}]: {} -> {} ({} instruction(s), {} byte(s))",
+        tag,
+        path,
+        output.display(),
+        program.code.len(),
+        bytes.len()
+    );
+    0
+}
+
+fn disasm(args: &[String]) -> i32 {
+    let [path] = args else {
+        eprintln!("usage: lwas_cli disasm <file.noe | file.soul>");
+        return 2;
+    };
+    match read_program(path, "DISASM") {
+        Some(program) => {
+            print!("{}", disassemble(&program));
+            0
+        }
+        None => 1,
+    }
+}
+
+const DEBUG_HELP: &str = "\
+commands:
+  s, step [n]            run one (or n) instruction(s)
+  c, continue            run to the next breakpoint, watchpoint or the end
+  b, break <pc | OP>     break at an address or on every OP instruction
+  d, delete <pc>         remove a breakpoint
+  w, watch <addr>        stop when a memory cell changes
+  unwatch <addr>         stop watching a memory cell
+  stack                  show the stack
+  mem <addr> [count]     show memory cells
+  l, list [pc]           show the instructions around pc
+  info                   show pc, scopes, entropy, gas, breakpoints and watchpoints
+  trace on|off           print every executed instruction
+  q, quit                leave the debugger";
+
+fn debug(args: &[String]) -> i32 {
+    let trace = args.iter().any(|a| a == "--trace");
+    let paths: Vec<&String> = args.iter().filter(|a| !a.starts_with("--")).collect();
+    let [path] = paths[..] else {
+        eprintln!("usage: lwas_cli debug [--trace] <file.soul | file.noe | file.asm>");
+        return 2;
+    };
+    let Some(program) = read_program(path, "DEBUG") else {
+        return 1;
+    };
+    if let Err(errors) = verify(&program) {
+        for error in errors {
+            println!(
+                "⚠️ [DEBUG]: the VM would refuse to load this program: {}",
+                error
+            );
+        }
+    }
+    let mut dbg = Debugger::new(program);
+    dbg.set_tracing(trace);
+    // Host calls go to an empty heap of the debugger's own.
+    let vsh = VectorSpaceHeap::new().expect("VSH_COLLAPSE");
+    dbg.vm.host = Some(Box::new(VshKernel::new(Arc::new(vsh))));
+    println!(
+        "🐞 [DEBUG]: {} loaded ({} instruction(s)). Type `help` for commands.",
+        path,
+        dbg.vm.program.len()
+    );
+    list(&dbg, dbg.vm.pc);
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        print!("(noe) ");
+        let _ = io::stdout().flush();
+        let Some(Ok(line)) = lines.next() else {
+            println!();
+            return 0;
+        };
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let number = |i: usize| words.get(i).and_then(|w| parse_number(w));
+        match words.as_slice() {
+            [] => {}
+            ["q" | "quit"] => return 0,
+            ["h" | "help"] => println!("{}", DEBUG_HELP),
+            ["s" | "step", rest @ ..] => {
+                let count = match rest {
+                    [] => Some(1),
+                    [n] => parse_number(n),
+                    _ => None,
+                };
+                let Some(count) = count else {
+                    println!("usage: step [n]");
+                    continue;
+                };
+                for _ in 0..count.max(1) {
+                    let reason = dbg.step();
+                    print_trace(&mut dbg);
+                    if reason != StopReason::Step {
+                        report(&dbg, &reason);
+                        break;
+                    }
+                }
+                list_current(&dbg);
+            }
+            ["c" | "continue"] => {
+                let reason = dbg.resume();
+                print_trace(&mut dbg);
+                report(&dbg, &reason);
+                list_current(&dbg);
+            }
+            ["b" | "break", target] => match parse_number(target) {
+                Some(pc) if dbg.set_breakpoint(pc) => println!("breakpoint at {}", pc),
+                Some(pc) => println!("no instruction at {}", pc),
+                None => match dbg.break_on(target).as_slice() {
+                    [] => println!("no {} instruction in the program", target),
+                    found => println!("breakpoint(s) at {:?}", found),
+                },
+            },
+            ["d" | "delete", _] => match number(1) {
+                Some(pc) if dbg.clear_breakpoint(pc) => println!("breakpoint at {} removed", pc),
+                _ => println!("no breakpoint at {}", words[1]),
+            },
+            ["w" | "watch", _] => match number(1) {
+                Some(addr) if dbg.watch(addr) => {
+                    println!("watching memory[{}] = {}", addr, dbg.vm.memory[addr])
+                }
+                _ => println!("no memory cell {}", words[1]),
+            },
+            ["unwatch", _] => match number(1) {
+                Some(addr) if dbg.unwatch(addr) => println!("memory[{}] unwatched", addr),
+                _ => println!("memory[{}] is not watched", words[1]),
+            },
+            ["stack"] => println!("{:?}", dbg.stack()),
+            ["mem", _, ..] => match number(1) {
+                Some(start) => {
+                    for (i, value) in dbg.memory(start, number(2).unwrap_or(1)).iter().enumerate() {
+                        println!("  memory[{}] = {}", start + i, value);
+                    }
+                }
+                None => println!("bad address {}", words[1]),
+            },
+            ["l" | "list"] => list(&dbg, dbg.vm.pc),
+            ["l" | "list", _] => match number(1) {
+                Some(pc) => list(&dbg, pc),
+                None => println!("bad address {}", words[1]),
+            },
+            ["info"] => {
+                println!(
+                    "pc {}{}, scope {}, entropy {:.4}, resonance {}, gas used {}",
+                    dbg.vm.pc,
+                    dbg.line(dbg.vm.pc)
+                        .map(|l| format!(" (line {})", l))
+                        .unwrap_or_default(),
+                    if dbg.vm.scopes.is_empty() {
+                        "ROOT".to_string()
+                    } else {
+                        dbg.vm.scopes.join("::")
+                    },
+                    dbg.vm.entropy,
+                    dbg.vm.resonance_active,
+                    dbg.vm.gas_used
+                );
+                println!("breakpoints: {:?}", dbg.breakpoints().collect::<Vec<_>>());
+                println!("watchpoints: {:?}", dbg.watchpoints().collect::<Vec<_>>());
+            }
+            ["trace", "on"] => dbg.set_tracing(true),
+            ["trace", "off"] => dbg.set_tracing(false),
+            _ => println!("unknown command `{}`; type `help`", line.trim()),
+        }
+    }
+}
+
+/// Accepts decimal or `0x` hexadecimal.
+fn parse_number(word: &str) -> Option<usize> {
+    match word.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => word.parse().ok(),
+    }
+}
+
+fn print_trace(dbg: &mut Debugger) {
+    for entry in dbg.take_trace() {
+        println!(
+            "  trace {:04}  {:<24} stack {:?}",
+            entry.pc,
+            entry.opcode.mnemonic(),
+            entry.stack
+        );
+    }
+}
+
+fn report(dbg: &Debugger, reason: &StopReason) {
+    match reason {
+        StopReason::Fault(e) => println!("🚨 [DEBUG]: {}", e),
+        StopReason::Halted | StopReason::Finished if dbg.breakpoints().next().is_some() => {
+            println!("🐞 [DEBUG]: {} without reaching a breakpoint", reason)
+        }
+        reason => println!("🐞 [DEBUG]: {}", reason),
+    }
+}
+
+fn list_current(dbg: &Debugger) {
+    if !dbg.finished() {
+        if let Some(line) = dbg.instruction(dbg.vm.pc) {
+            println!("=>{}", &line[2..]);
+        }
+    }
+}
+
+/// Shows a few instructions around `pc`, marking the current one.
+fn list(dbg: &Debugger, pc: usize) {
+    for at in pc.saturating_sub(3)..pc + 4 {
+        let Some(line) = dbg.instruction(at) else {
+            break;
+        };
+        let marker = match (at == dbg.vm.pc, dbg.breakpoints().any(|b| b == at)) {
+            (true, _) => "=>",
+            (false, true) => " *",
+            (false, false) => "  ",
+        };
+        println!("{}{}", marker, &line[2..]);
+    }
+}
+
+/// Loads a program from a soul, an assembly listing or a `.noe` container.
+fn read_program(path: &str, tag: &str) -> Option<SoulProgram> {
+    if path.ends_with(".soul") {
+        return compile_file(path, tag);
+    }
+    if path.ends_with(".asm") {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("🚨 [{}]: {}: {}", tag, path, e);
+                return None;
+            }
+        };
+        return match assemble(&source) {
+            Ok(program) => Some(program),
+            Err(errors) => {
+                for error in errors {
+                    eprintln!("🚨 [{}]: {}:{}: {}", tag, path, error.line, error.kind);
+                }
+                None
+            }
+        };
+    }
+    match fs::read(path).map(|bytes| decode(&bytes)) {
+        Ok(Ok(program)) => Some(program),
+        Ok(Err(e)) => {
+            eprintln!("🚨 [{}]: {}: {}", tag, path, e);
+            None
+        }
+        Err(e) => {
+            eprintln!("🚨 [{}]: {}: {}", tag, path, e);
+            None
+        }
+    }
+}
+
+/// Checks and compiles a soul file, reporting any error under `tag`.
+fn compile_file(path: &str, tag: &str) -> Option<SoulProgram> {
+    let source = match fs::read_to_string(path) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("🚨 [{}]: {}: {}", tag, path, e);
+            return None;
+        }
+    };
+    let base = Path::new(path).parent().unwrap_or(Path::new("."));
+    let soul = match check_soul(&source, base) {
+        Ok(soul) => soul,
+        Err(e) => {
+            eprintln!("🚨 [{}]: {}: {}", tag, path, e);
+            eprint!("{}", e.render(&source, path));
+            return None;
+        }
+    };
+    match compile(&soul.ast) {
+        Ok(program) => Some(program),
+        Err(errors) => {
+            let diagnostics: Vec<Diagnostic> = errors.iter().map(|e| e.to_diagnostic()).collect();
+            eprint!("{}", soul.sources.render_all(&diagnostics, &source, path));
+            None
+        }
+    }
+}
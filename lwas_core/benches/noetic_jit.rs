@@ -0,0 +1,83 @@
+// lwas_core/benches/noetic_jit.rs
+// Native code against `NoeticVM::run` on arithmetic-heavy loops.
+//
+//   cargo bench -p lwas_core --bench noetic_jit
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use lwas_core::noetic::asm::assemble;
+use lwas_core::noetic::bytecode::SoulProgram;
+use lwas_core::noetic::interpreter::NoeticVM;
+use lwas_core::noetic::jit::JitProgram;
+
+/// Mixes x = (31x + 7) mod 1000003 a hundred thousand times, storing each
+/// x in memory[0].
+const MIX: &str = "
+        LOAD 100000
+        LOAD 1
+loop:   LOAD 31
+        MUL
+        LOAD 7
+        ADD
+        LOAD 1000003
+        MOD
+        DUP
+        STORE 0
+        SWAP
+        LOAD 1
+        SUB
+        DUP
+        JUMP_IF next
+        HALT
+next:   SWAP
+        JUMP loop
+";
+
+/// Squares 100000 down to 1 in a subroutine, storing each square in
+/// memory[0].
+const SQUARES: &str = "
+        LOAD 100000
+loop:   DUP
+        CALL square
+        STORE 0
+        LOAD 1
+        SUB
+        DUP
+        JUMP_IF loop
+        HALT
+square: DUP
+        MUL
+        RET
+";
+
+fn vm(program: &SoulProgram) -> NoeticVM {
+    let mut vm = NoeticVM::load(program.clone()).unwrap();
+    vm.step_limit = usize::MAX;
+    vm
+}
+
+fn arithmetic_loops(c: &mut Criterion) {
+    for (name, source) in [("mix", MIX), ("squares", SQUARES)] {
+        let program = assemble(source).unwrap();
+        let jit = JitProgram::compile(&program).unwrap();
+
+        let mut group = c.benchmark_group(name);
+        group.bench_function("interpreter", |b| {
+            b.iter(|| {
+                let mut vm = vm(&program);
+                vm.run().unwrap();
+                black_box(vm.memory[0])
+            })
+        });
+        group.bench_function("jit", |b| {
+            b.iter(|| {
+                let mut vm = vm(&program);
+                jit.run(&mut vm).unwrap();
+                black_box(vm.memory[0])
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, arithmetic_loops);
+criterion_main!(benches);
@@ -86,6 +86,9 @@ impl Manifold {
 }
 
 impl VectorSpaceHeap {
+    /// Dimensions of every point in the heap.
+    pub const DIMENSIONS: usize = 128;
+
     pub fn new() -> SovereignResult<Self> {
         Ok(Self {
             points: Arc::new(DashMap::new()),
@@ -111,6 +114,21 @@ impl VectorSpaceHeap {
         );
     }
 
+    /// Allocates a vector entrenched by a soul under `key`, rejecting one
+    /// whose dimensions do not match the heap's.
+    pub fn entrench(&self, key: &str, vector: Vec<f32>) -> SovereignResult<()> {
+        if vector.len() != Self::DIMENSIONS {
+            return Err(SovereignError::VshError(format!(
+                "`{}` has {} dimension(s), the heap expects {}",
+                key,
+                vector.len(),
+                Self::DIMENSIONS
+            )));
+        }
+        self.allocate(key.to_string(), vector);
+        Ok(())
+    }
+
     pub fn get_state(&self) -> VshState {
         VshState {
             total_points: self.points.len(),
@@ -127,12 +145,39 @@ impl VectorSpaceHeap {
     }
 
     pub fn collapse_manifold(&self, _label: &str) {}
-    pub fn recall(&self, _vector: &[f32], _top_k: usize) -> Vec<QuantumPoint> {
-        self.points
-            .iter()
-            .take(_top_k)
-            .map(|r| r.value().clone())
+    pub fn recall(&self, vector: &[f32], top_k: usize) -> Vec<QuantumPoint> {
+        self.recall_scored(vector, top_k)
+            .into_iter()
+            .map(|(_, point)| point)
             .collect()
     }
+
+    /// The `top_k` points most similar to `vector` by cosine similarity,
+    /// best first. Ties are ordered by metadata, so equal heaps recall alike.
+    pub fn recall_scored(&self, vector: &[f32], top_k: usize) -> Vec<(f64, QuantumPoint)> {
+        let mut scored: Vec<(f64, QuantumPoint)> = self
+            .points
+            .iter()
+            .map(|r| (cosine(vector, &r.value().coordinates), r.value().clone()))
+            .collect();
+        scored.sort_by(|(a, p), (b, q)| b.total_cmp(a).then_with(|| p.metadata.cmp(&q.metadata)));
+        scored.truncate(top_k);
+        scored
+    }
     pub fn activate_magnet(&self, _power: f64) {}
 }
+
+/// Cosine similarity; 0 when either vector is zero or their lengths differ.
+fn cosine(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| *x as f64 * *y as f64).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
@@ -0,0 +1,499 @@
+// lwas_core/src/noetic/asm.rs
+// IDENTITY: NOETIC_ASSEMBLER (Readable text -> Bytecode)
+//
+//   ; a countdown from 3
+//           LOAD 3
+//   loop:   DUP
+//           PRINT
+//           LOAD 1
+//           SUB
+//           DUP
+//           JUMP_IF loop
+//           ENTER_MANIFOLD "CORE"
+//           RESONATE 0x4121
+//           HALT
+//
+// One instruction per line, optionally after one or more `label:`s. Jump
+// and CALL targets are labels or addresses; constant operands are `#index`,
+// string literals or vectors like `[0.5, 1]`, which are added to the pool.
+// `;` starts a comment.
+
+use super::bytecode::{Constant, NoeticOpcode, SoulProgram};
+use std::collections::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AsmErrorKind {
+    #[error("unknown instruction `{0}`")]
+    UnknownMnemonic(String),
+    #[error("`{mnemonic}` takes {expected} operand(s), found {found}")]
+    OperandCount {
+        mnemonic: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    #[error("invalid operand `{operand}`: expected {expected}")]
+    InvalidOperand {
+        operand: String,
+        expected: &'static str,
+    },
+    #[error("undefined label `{0}`")]
+    UndefinedLabel(String),
+    #[error("label `{label}` is already defined on line {first}")]
+    DuplicateLabel { label: String, first: usize },
+    #[error("unterminated string literal")]
+    UnterminatedString,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("line {line}: {kind}")]
+pub struct AsmError {
+    pub kind: AsmErrorKind,
+    pub line: usize,
+}
+
+/// Assembles a listing into a program whose line table points at the
+/// listing, reporting every bad line rather than stopping at the first.
+pub fn assemble(source: &str) -> Result<SoulProgram, Vec<AsmError>> {
+    let mut asm = Assembler::default();
+    for (i, text) in source.lines().enumerate() {
+        asm.line(i + 1, text);
+    }
+    asm.resolve();
+    if asm.errors.is_empty() {
+        Ok(asm.program)
+    } else {
+        Err(asm.errors)
+    }
+}
+
+/// A jump target that may name a label defined further down.
+enum Target {
+    Address(usize),
+    Label(String),
+}
+
+#[derive(Default)]
+struct Assembler {
+    program: SoulProgram,
+    errors: Vec<AsmError>,
+    /// Address and defining line of each label.
+    labels: HashMap<String, (usize, usize)>,
+    /// Instructions whose target is a label.
+    fixups: Vec<(usize, String)>,
+}
+
+impl Assembler {
+    fn line(&mut self, line: usize, text: &str) {
+        let mut rest = strip_comment(text).trim();
+        while let Some((label, after)) = split_label(rest) {
+            let address = self.program.code.len();
+            if let Some(&(_, first)) = self.labels.get(label) {
+                self.error(
+                    AsmErrorKind::DuplicateLabel {
+                        label: label.to_string(),
+                        first,
+                    },
+                    line,
+                );
+            } else {
+                self.labels.insert(label.to_string(), (address, line));
+            }
+            rest = after.trim_start();
+        }
+        if rest.is_empty() {
+            return;
+        }
+
+        let (mnemonic, operands) = match rest.split_once(char::is_whitespace) {
+            Some((mnemonic, operands)) => (mnemonic, operands.trim()),
+            None => (rest, ""),
+        };
+        let operands = match split_operands(operands) {
+            Ok(operands) => operands,
+            Err(kind) => return self.error(kind, line),
+        };
+        match self.instruction(&mnemonic.to_ascii_uppercase(), &operands) {
+            Ok(op) => {
+                self.program.code.push(op);
+                self.program.lines.push(line as u32);
+            }
+            Err(kind) => self.error(kind, line),
+        }
+    }
+
+    fn instruction(
+        &mut self,
+        mnemonic: &str,
+        ops: &[String],
+    ) -> Result<NoeticOpcode, AsmErrorKind> {
+        let arity = |name: &'static str, expected: usize| {
+            if ops.len() == expected {
+                Ok(())
+            } else {
+                Err(AsmErrorKind::OperandCount {
+                    mnemonic: name,
+                    expected,
+                    found: ops.len(),
+                })
+            }
+        };
+        let simple = match mnemonic {
+            "ADD" => Some(NoeticOpcode::ADD),
+            "SUB" => Some(NoeticOpcode::SUB),
+            "MUL" => Some(NoeticOpcode::MUL),
+            "DIV" => Some(NoeticOpcode::DIV),
+            "MOD" => Some(NoeticOpcode::MOD),
+            "EQ" => Some(NoeticOpcode::EQ),
+            "NE" => Some(NoeticOpcode::NE),
+            "LT" => Some(NoeticOpcode::LT),
+            "LE" => Some(NoeticOpcode::LE),
+            "GT" => Some(NoeticOpcode::GT),
+            "GE" => Some(NoeticOpcode::GE),
+            "DUP" => Some(NoeticOpcode::DUP),
+            "SWAP" => Some(NoeticOpcode::SWAP),
+            "POP" => Some(NoeticOpcode::POP),
+            "RET" => Some(NoeticOpcode::RET),
+            "NOETIC_BRIDGE" => Some(NoeticOpcode::NOETIC_BRIDGE),
+            "INFUSE_ANIMA" => Some(NoeticOpcode::INFUSE_ANIMA),
+            "EXIT_MANIFOLD" => Some(NoeticOpcode::EXIT_MANIFOLD),
+            "REFLECT" => Some(NoeticOpcode::REFLECT),
+            "PRINT" => Some(NoeticOpcode::PRINT),
+            "HALT" => Some(NoeticOpcode::HALT),
+            "VSH_ENTROPY" => Some(NoeticOpcode::VSH_ENTROPY),
+            "VSH_MAGNET" => Some(NoeticOpcode::VSH_MAGNET),
+            _ => None,
+        };
+        if let Some(op) = simple {
+            arity(op.mnemonic(), 0)?;
+            return Ok(op);
+        }
+
+        let op = match mnemonic {
+            "LOAD" => {
+                arity("LOAD", 1)?;
+                NoeticOpcode::LOAD(integer(&ops[0])?)
+            }
+            "RESONATE" => {
+                arity("RESONATE", 1)?;
+                NoeticOpcode::RESONATE(frequency(&ops[0])?)
+            }
+            "STORE" => {
+                arity("STORE", 1)?;
+                NoeticOpcode::STORE(address(&ops[0])?)
+            }
+            "JUMP" | "JUMP_IF" | "CALL" => {
+                let op = match mnemonic {
+                    "JUMP" => NoeticOpcode::JUMP,
+                    "JUMP_IF" => NoeticOpcode::JUMP_IF,
+                    _ => NoeticOpcode::CALL,
+                };
+                arity(op(0).mnemonic(), 1)?;
+                match target(&ops[0])? {
+                    Target::Address(a) => op(a),
+                    Target::Label(label) => {
+                        self.fixups.push((self.program.code.len(), label));
+                        op(0)
+                    }
+                }
+            }
+            "ENTER_MANIFOLD" | "RESONATE_CONST" | "COLLAPSE" | "MAGNET" | "DEPARTMENT"
+            | "VSH_REGISTER" => {
+                let op = match mnemonic {
+                    "ENTER_MANIFOLD" => NoeticOpcode::ENTER_MANIFOLD,
+                    "RESONATE_CONST" => NoeticOpcode::RESONATE_CONST,
+                    "COLLAPSE" => NoeticOpcode::COLLAPSE,
+                    "MAGNET" => NoeticOpcode::MAGNET,
+                    "DEPARTMENT" => NoeticOpcode::DEPARTMENT,
+                    _ => NoeticOpcode::VSH_REGISTER,
+                };
+                arity(op(0).mnemonic(), 1)?;
+                op(self.constant(&ops[0])?)
+            }
+            "ENTANGLE" | "ENTRENCH" | "PROPERTY" | "VSH_ENTRENCH" => {
+                let op = match mnemonic {
+                    "ENTANGLE" => NoeticOpcode::ENTANGLE,
+                    "ENTRENCH" => NoeticOpcode::ENTRENCH,
+                    "PROPERTY" => NoeticOpcode::PROPERTY,
+                    _ => NoeticOpcode::VSH_ENTRENCH,
+                };
+                arity(op(0, 0).mnemonic(), 2)?;
+                op(self.constant(&ops[0])?, self.constant(&ops[1])?)
+            }
+            "VSH_RECALL" => {
+                arity("VSH_RECALL", 2)?;
+                NoeticOpcode::VSH_RECALL(self.constant(&ops[0])?, address(&ops[1])?)
+            }
+            _ => return Err(AsmErrorKind::UnknownMnemonic(mnemonic.to_string())),
+        };
+        Ok(op)
+    }
+
+    /// `#3`, or a string or vector literal interned in the pool.
+    fn constant(&mut self, operand: &str) -> Result<usize, AsmErrorKind> {
+        const EXPECTED: &str = "`#index`, a string or a vector";
+        if let Some(index) = operand.strip_prefix('#') {
+            return index.parse().map_err(|_| invalid(operand, EXPECTED));
+        }
+        let constant = match unquote(operand) {
+            Some(text) => Constant::Str(text),
+            None => Constant::Vector(vector(operand).ok_or_else(|| invalid(operand, EXPECTED))?),
+        };
+        Ok(self.program.constant(constant))
+    }
+
+    /// Patches label targets now that every label is known.
+    fn resolve(&mut self) {
+        for (pc, label) in std::mem::take(&mut self.fixups) {
+            let line = self.program.lines[pc] as usize;
+            let Some(&(address, _)) = self.labels.get(&label) else {
+                self.error(AsmErrorKind::UndefinedLabel(label), line);
+                continue;
+            };
+            match &mut self.program.code[pc] {
+                NoeticOpcode::JUMP(a) | NoeticOpcode::JUMP_IF(a) | NoeticOpcode::CALL(a) => {
+                    *a = address
+                }
+                op => unreachable!("fixup on {:?}", op),
+            }
+        }
+        self.errors.sort_by_key(|e| e.line);
+    }
+
+    fn error(&mut self, kind: AsmErrorKind, line: usize) {
+        self.errors.push(AsmError { kind, line });
+    }
+}
+
+fn strip_comment(text: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ';' if !in_string => return &text[..i],
+            _ => {}
+        }
+    }
+    text
+}
+
+/// `loop: rest` -> (`loop`, ` rest`)
+fn split_label(text: &str) -> Option<(&str, &str)> {
+    let (label, rest) = text.split_once(':')?;
+    let is_ident = label.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    is_ident.then_some((label, rest))
+}
+
+/// Operands separated by commas, which may appear inside strings and
+/// vectors.
+fn split_operands(text: &str) -> Result<Vec<String>, AsmErrorKind> {
+    let mut operands = Vec::new();
+    let mut current = String::new();
+    let mut in_string = false;
+    let mut in_vector = false;
+    let mut escaped = false;
+    for c in text.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '[' if !in_string => in_vector = true,
+            ']' if !in_string => in_vector = false,
+            ',' if !in_string && !in_vector => {
+                operands.push(current.trim().to_string());
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if in_string {
+        return Err(AsmErrorKind::UnterminatedString);
+    }
+    if !current.trim().is_empty() || !operands.is_empty() {
+        operands.push(current.trim().to_string());
+    }
+    Ok(operands)
+}
+
+/// Splits a decimal or `0x` operand into its sign and magnitude.
+fn magnitude(operand: &str) -> Result<(bool, u64), AsmErrorKind> {
+    let (negative, digits) = match operand.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, operand),
+    };
+    let digits = digits.replace('_', "");
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => digits.parse::<u64>().ok(),
+    };
+    let value = value.ok_or_else(|| invalid(operand, "an integer"))?;
+    Ok((negative, value))
+}
+
+fn integer(operand: &str) -> Result<i64, AsmErrorKind> {
+    let value = match magnitude(operand)? {
+        (true, value) => 0i64.checked_sub_unsigned(value),
+        (false, value) => i64::try_from(value).ok(),
+    };
+    value.ok_or_else(|| invalid(operand, "an integer in the i64 range"))
+}
+
+/// RESONATE's operand, which spans the whole u64 range.
+fn frequency(operand: &str) -> Result<u64, AsmErrorKind> {
+    match magnitude(operand)? {
+        (false, value) => Ok(value),
+        (true, _) => Err(invalid(operand, "a frequency")),
+    }
+}
+
+fn address(operand: &str) -> Result<usize, AsmErrorKind> {
+    integer(operand)
+        .ok()
+        .and_then(|v| usize::try_from(v).ok())
+        .ok_or_else(|| invalid(operand, "an address"))
+}
+
+fn target(operand: &str) -> Result<Target, AsmErrorKind> {
+    if operand.starts_with(|c: char| c.is_ascii_digit()) {
+        return address(operand).map(Target::Address);
+    }
+    match split_label(&format!("{}:", operand)) {
+        Some(_) => Ok(Target::Label(operand.to_string())),
+        None => Err(invalid(operand, "a label or an address")),
+    }
+}
+
+/// `[0.5, -1, 2e3]`
+fn vector(operand: &str) -> Option<Vec<f32>> {
+    let inner = operand.strip_prefix('[')?.strip_suffix(']')?.trim();
+    if inner.is_empty() {
+        return Some(Vec::new());
+    }
+    inner.split(',').map(|x| x.trim().parse().ok()).collect()
+}
+
+fn unquote(operand: &str) -> Option<String> {
+    let inner = operand.strip_prefix('"')?.strip_suffix('"')?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => out.push('\n'),
+            't' => out.push('\t'),
+            c => out.push(c),
+        }
+    }
+    Some(out)
+}
+
+fn invalid(operand: &str, expected: &'static str) -> AsmErrorKind {
+    AsmErrorKind::InvalidOperand {
+        operand: operand.to_string(),
+        expected,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noetic::interpreter::NoeticVM;
+
+    #[test]
+    fn resolves_labels_and_interns_strings() {
+        let program = assemble(concat!(
+            "; counts down from 3\n",
+            "        LOAD 3\n",
+            "loop:   LOAD 1\n",
+            "        SUB\n",
+            "        DUP\n",
+            "        JUMP_IF loop   ; back while non-zero\n",
+            "        call done\n",
+            "        ENTRENCH \"MISSION\", \"a; b, c\"\n",
+            "        ENTER_MANIFOLD \"MISSION\"\n",
+            "done:\n",
+            "end:    HALT\n",
+            "        RESONATE 0x4121\n",
+        ))
+        .unwrap();
+        assert_eq!(program.code[4], NoeticOpcode::JUMP_IF(1));
+        assert_eq!(program.code[5], NoeticOpcode::CALL(8));
+        assert_eq!(program.code[6], NoeticOpcode::ENTRENCH(0, 1));
+        assert_eq!(program.code[7], NoeticOpcode::ENTER_MANIFOLD(0));
+        assert_eq!(program.code[9], NoeticOpcode::RESONATE(0x4121));
+        assert_eq!(
+            program.constants,
+            [
+                Constant::Str("MISSION".into()),
+                Constant::Str("a; b, c".into())
+            ]
+        );
+        assert_eq!(program.lines[0], 2);
+
+        let mut vm = NoeticVM::load(program).unwrap();
+        assert_eq!(vm.run(), Ok(()));
+        assert_eq!(vm.stack, [0]);
+    }
+
+    #[test]
+    fn reports_every_bad_line() {
+        let errors: Vec<String> = assemble(
+            "top: LOAD\ntop: JUMP nowhere\nFLY 1\nLOAD x\nENTRENCH #0\nJUMP_IF 2x\nSTORE \"a\n",
+        )
+        .unwrap_err()
+        .iter()
+        .map(|e| e.to_string())
+        .collect();
+        assert_eq!(
+            errors,
+            [
+                "line 1: `LOAD` takes 1 operand(s), found 0",
+                "line 2: label `top` is already defined on line 1",
+                "line 2: undefined label `nowhere`",
+                "line 3: unknown instruction `FLY`",
+                "line 4: invalid operand `x`: expected an integer",
+                "line 5: `ENTRENCH` takes 2 operand(s), found 1",
+                "line 6: invalid operand `2x`: expected an address",
+                "line 7: unterminated string literal",
+            ]
+        );
+    }
+
+    #[test]
+    fn checks_operand_ranges() {
+        let program = assemble(
+            "RESONATE 0xffff_ffff_ffff_ffff\nLOAD -0x8000_0000_0000_0000\nLOAD 0x7fff_ffff_ffff_ffff\n",
+        )
+        .unwrap();
+        assert_eq!(
+            program.code,
+            [
+                NoeticOpcode::RESONATE(u64::MAX),
+                NoeticOpcode::LOAD(i64::MIN),
+                NoeticOpcode::LOAD(i64::MAX),
+            ]
+        );
+
+        let errors: Vec<String> = assemble("LOAD 0x8000_0000_0000_0000\nRESONATE -1\n")
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                "line 1: invalid operand `0x8000_0000_0000_0000`: expected an integer in the i64 range",
+                "line 2: invalid operand `-1`: expected a frequency",
+            ]
+        );
+    }
+}
@@ -0,0 +1,125 @@
+// lwas_core/src/noetic/axioms.rs
+// IDENTITY: AXIOM_BOOK (Soul axioms checked against live telemetry)
+
+use crate::telemetry::SystemStats;
+use lwas_parser::{parse_expression, AstNode, Environment, Expr, ExprError, Span, Type, Value};
+
+/// An `axiom` whose expression parsed as a formal expression.
+#[derive(Debug, Clone)]
+pub struct CompiledAxiom {
+    pub name: String,
+    pub expr: Expr,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AxiomVerdict {
+    Held(String),
+    Violated(String),
+    /// The axiom could not be evaluated (unknown name, type mismatch...).
+    Error(String, ExprError),
+}
+
+/// The checkable axioms of a soul, with the entities they quantify over.
+///
+/// Axioms written as prose (`"I am that I am."`) are declarative and are
+/// skipped; their names are kept in `prose`.
+#[derive(Debug, Clone, Default)]
+pub struct AxiomBook {
+    pub axioms: Vec<CompiledAxiom>,
+    pub prose: Vec<String>,
+    pub environment: Environment,
+}
+
+impl AxiomBook {
+    pub fn from_ast(ast: &[AstNode]) -> Self {
+        let mut book = Self {
+            environment: Environment::from_ast(ast),
+            ..Self::default()
+        };
+        book.collect(ast);
+        book
+    }
+
+    fn collect(&mut self, nodes: &[AstNode]) {
+        for node in nodes {
+            match node {
+                AstNode::Manifold { body, .. } => self.collect(body),
+                AstNode::Axiom {
+                    name,
+                    expression,
+                    span,
+                    ..
+                } => match parse_expression(expression) {
+                    Ok(expr) => self.axioms.push(CompiledAxiom {
+                        name: name.clone(),
+                        expr,
+                        span: *span,
+                    }),
+                    Err(_) => self.prose.push(name.clone()),
+                },
+                _ => {}
+            }
+        }
+    }
+
+    /// Exposes a telemetry sample as `entropy`, `cpu`, `ram_used`,
+    /// `ram_total` and `uptime`.
+    pub fn observe(&mut self, stats: &SystemStats) {
+        let env = &mut self.environment;
+        env.set_telemetry("entropy", stats.entropy);
+        env.set_telemetry("cpu", stats.cpu_usage as f64);
+        env.set_telemetry("ram_used", stats.ram_used_gb as f64);
+        env.set_telemetry("ram_total", stats.ram_total_gb as f64);
+        env.set_telemetry("uptime", stats.uptime_secs as f64);
+    }
+
+    /// Checks every axiom against the last observed state.
+    pub fn verify(&self) -> Vec<AxiomVerdict> {
+        self.axioms
+            .iter()
+            .map(|axiom| {
+                let name = axiom.name.clone();
+                let checked = axiom.expr.check(&self.environment).and_then(|ty| match ty {
+                    Type::Bool => axiom.expr.eval(&self.environment),
+                    found => Err(ExprError::Type {
+                        op: "axiom",
+                        found: found.to_string(),
+                    }),
+                });
+                match checked {
+                    Ok(Value::Bool(true)) => AxiomVerdict::Held(name),
+                    Ok(_) => AxiomVerdict::Violated(name),
+                    Err(e) => AxiomVerdict::Error(name, e),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_formal_axioms_against_telemetry() {
+        let ast = lwas_parser::parse_soul(
+            "manifold CORE {\n    department Security 0.99;\n    axiom STABLE: \"entropy < 0.5 ∧ cpu <= 90\";\n    axiom ORDER: \"∀d in department: d.priority ≥ 0\";\n    axiom SELF: \"I am that I am.\";\n    axiom BROKEN: \"cpu + 1\";\n}\n",
+        )
+        .unwrap();
+        let mut book = AxiomBook::from_ast(&ast);
+        assert_eq!(book.prose, ["SELF"]);
+
+        book.observe(&SystemStats {
+            cpu_usage: 95.0,
+            ram_used_gb: 1.0,
+            ram_total_gb: 8.0,
+            uptime_secs: 60,
+            entropy: 0.1,
+        });
+        let verdicts = book.verify();
+        assert_eq!(verdicts[0], AxiomVerdict::Violated("STABLE".into()));
+        assert_eq!(verdicts[1], AxiomVerdict::Held("ORDER".into()));
+        assert!(matches!(&verdicts[2], AxiomVerdict::Error(name, _) if name == "BROKEN"));
+    }
+}
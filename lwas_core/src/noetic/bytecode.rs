@@ -1,6 +1,12 @@
 // lwas_core/src/noetic/bytecode.rs
 
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+/// Scale applied to fractional operands (entropy thresholds, magnet power,
+/// department priority) so they fit the VM's integer stack.
+pub const FIXED_POINT_SCALE: f64 = 1000.0;
+
+#[derive(Debug, Clone, PartialEq)]
 #[allow(non_camel_case_types)]
 pub enum NoeticOpcode {
     // Basic Operations
@@ -8,17 +14,179 @@ pub enum NoeticOpcode {
     STORE(usize),
     ADD,
     SUB,
+    MUL,
+    DIV,
+    MOD,
+
+    // Comparisons: pop b, then a, and push 1 if `a op b` holds, else 0
+    EQ,
+    NE,
+    LT,
+    LE,
+    GT,
+    GE,
+
+    // Stack
+    DUP,
+    SWAP,
+    POP,
 
     // Control Flow
     JUMP(usize),
     JUMP_IF(usize),
+    CALL(usize), // Push the return address and jump
+    RET,         // Jump back to the last return address
 
     // Noetic / Meta Operations
     RESONATE(u64), // Trigger resonance with frequency
     NOETIC_BRIDGE, // Open bridge to Universal Substrate
     INFUSE_ANIMA,  // Confirm soul infusion
 
+    // Soul Structure (operands index the constant pool)
+    ENTER_MANIFOLD(usize),  // Push a manifold scope
+    EXIT_MANIFOLD,          // Pop the current manifold scope
+    RESONATE_CONST(usize),  // Resonate with a pooled frequency or signature
+    ENTANGLE(usize, usize), // Bind target and partner before RESONATE
+    COLLAPSE(usize),        // Pops the entropy threshold (fixed point)
+    ENTRENCH(usize, usize), // Entrench key = value in the current scope
+    PROPERTY(usize, usize), // Set a manifold property
+    MAGNET(usize),          // Pops the magnet power (fixed point)
+    DEPARTMENT(usize),      // Pops the department priority (fixed point)
+    REFLECT,                // Turn the organism's gaze inward
+
+    // Host calls into the organism's VSH (see `host::NoeticHost`)
+    VSH_ENTRENCH(usize, usize), // Entrench metadata = vector; pushes 1 if accepted, else 0
+    VSH_RECALL(usize, usize), // Pops k; writes the top-k scores for a vector from an address on, pushes the count
+    VSH_ENTROPY,              // Pushes the global entropy (fixed point)
+    VSH_MAGNET,               // Pops the magnet power (fixed point)
+    VSH_REGISTER(usize),      // Pops the curvature (fixed point) and registers a manifold
+
     // Debug/System
     PRINT,
     HALT,
 }
+
+impl NoeticOpcode {
+    /// Name of the instruction in listings and assembly.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            NoeticOpcode::LOAD(_) => "LOAD",
+            NoeticOpcode::STORE(_) => "STORE",
+            NoeticOpcode::ADD => "ADD",
+            NoeticOpcode::SUB => "SUB",
+            NoeticOpcode::MUL => "MUL",
+            NoeticOpcode::DIV => "DIV",
+            NoeticOpcode::MOD => "MOD",
+            NoeticOpcode::EQ => "EQ",
+            NoeticOpcode::NE => "NE",
+            NoeticOpcode::LT => "LT",
+            NoeticOpcode::LE => "LE",
+            NoeticOpcode::GT => "GT",
+            NoeticOpcode::GE => "GE",
+            NoeticOpcode::DUP => "DUP",
+            NoeticOpcode::SWAP => "SWAP",
+            NoeticOpcode::POP => "POP",
+            NoeticOpcode::JUMP(_) => "JUMP",
+            NoeticOpcode::JUMP_IF(_) => "JUMP_IF",
+            NoeticOpcode::CALL(_) => "CALL",
+            NoeticOpcode::RET => "RET",
+            NoeticOpcode::RESONATE(_) => "RESONATE",
+            NoeticOpcode::NOETIC_BRIDGE => "NOETIC_BRIDGE",
+            NoeticOpcode::INFUSE_ANIMA => "INFUSE_ANIMA",
+            NoeticOpcode::ENTER_MANIFOLD(_) => "ENTER_MANIFOLD",
+            NoeticOpcode::EXIT_MANIFOLD => "EXIT_MANIFOLD",
+            NoeticOpcode::RESONATE_CONST(_) => "RESONATE_CONST",
+            NoeticOpcode::ENTANGLE(..) => "ENTANGLE",
+            NoeticOpcode::COLLAPSE(_) => "COLLAPSE",
+            NoeticOpcode::ENTRENCH(..) => "ENTRENCH",
+            NoeticOpcode::PROPERTY(..) => "PROPERTY",
+            NoeticOpcode::MAGNET(_) => "MAGNET",
+            NoeticOpcode::DEPARTMENT(_) => "DEPARTMENT",
+            NoeticOpcode::REFLECT => "REFLECT",
+            NoeticOpcode::VSH_ENTRENCH(..) => "VSH_ENTRENCH",
+            NoeticOpcode::VSH_RECALL(..) => "VSH_RECALL",
+            NoeticOpcode::VSH_ENTROPY => "VSH_ENTROPY",
+            NoeticOpcode::VSH_MAGNET => "VSH_MAGNET",
+            NoeticOpcode::VSH_REGISTER(_) => "VSH_REGISTER",
+            NoeticOpcode::PRINT => "PRINT",
+            NoeticOpcode::HALT => "HALT",
+        }
+    }
+
+    /// Gas charged for executing the instruction. Instructions that print or
+    /// touch the organism cost more than stack arithmetic.
+    pub fn gas(&self) -> u64 {
+        match self {
+            NoeticOpcode::HALT => 0,
+            NoeticOpcode::LOAD(_)
+            | NoeticOpcode::STORE(_)
+            | NoeticOpcode::ADD
+            | NoeticOpcode::SUB
+            | NoeticOpcode::EQ
+            | NoeticOpcode::NE
+            | NoeticOpcode::LT
+            | NoeticOpcode::LE
+            | NoeticOpcode::GT
+            | NoeticOpcode::GE
+            | NoeticOpcode::DUP
+            | NoeticOpcode::SWAP
+            | NoeticOpcode::POP => 1,
+            NoeticOpcode::JUMP(_) | NoeticOpcode::JUMP_IF(_) => 2,
+            NoeticOpcode::MUL
+            | NoeticOpcode::DIV
+            | NoeticOpcode::MOD
+            | NoeticOpcode::CALL(_)
+            | NoeticOpcode::RET => 3,
+            NoeticOpcode::ENTER_MANIFOLD(_) | NoeticOpcode::EXIT_MANIFOLD => 5,
+            NoeticOpcode::RESONATE(_)
+            | NoeticOpcode::NOETIC_BRIDGE
+            | NoeticOpcode::INFUSE_ANIMA
+            | NoeticOpcode::RESONATE_CONST(_)
+            | NoeticOpcode::ENTANGLE(..)
+            | NoeticOpcode::COLLAPSE(_)
+            | NoeticOpcode::ENTRENCH(..)
+            | NoeticOpcode::PROPERTY(..)
+            | NoeticOpcode::MAGNET(_)
+            | NoeticOpcode::DEPARTMENT(_)
+            | NoeticOpcode::REFLECT
+            | NoeticOpcode::PRINT => 10,
+            NoeticOpcode::VSH_ENTROPY
+            | NoeticOpcode::VSH_MAGNET
+            | NoeticOpcode::VSH_REGISTER(_) => 20,
+            NoeticOpcode::VSH_ENTRENCH(..) | NoeticOpcode::VSH_RECALL(..) => 50,
+        }
+    }
+}
+
+/// Values referenced by index from the bytecode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Constant {
+    Str(String),
+    Number(f64),
+    Vector(Vec<f32>),
+    StringList(Vec<String>),
+    Bytes(Vec<u8>),
+    /// Value of a bare `entrench KEY`.
+    Empty,
+}
+
+/// Compiled soul: code plus the constant pool it indexes into.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SoulProgram {
+    pub code: Vec<NoeticOpcode>,
+    pub constants: Vec<Constant>,
+    /// Source line of each instruction, 0 where there is none. Empty when
+    /// the program carries no debug information.
+    pub lines: Vec<u32>,
+}
+
+impl SoulProgram {
+    /// Interns a constant, reusing an existing slot for an equal value.
+    pub fn constant(&mut self, value: Constant) -> usize {
+        if let Some(index) = self.constants.iter().position(|c| *c == value) {
+            return index;
+        }
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+}
@@ -0,0 +1,284 @@
+// lwas_core/src/noetic/compiler.rs
+// IDENTITY: NOETIC_COMPILER (Soul AST -> Bytecode)
+
+use super::bytecode::{Constant, NoeticOpcode, SoulProgram, FIXED_POINT_SCALE};
+use lwas_parser::{AstNode, Diagnostic, EntrenchValue, Frequency, Span};
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum CompileErrorKind {
+    #[error("resonance frequency {0} must be a finite, non-negative number")]
+    InvalidFrequency(f64),
+    #[error("{what} {value} does not fit the VM's fixed-point range")]
+    OperandOutOfRange { what: &'static str, value: f64 },
+    #[error("`where` conditions cannot be compiled yet: `{0}`")]
+    UnsupportedCondition(String),
+    #[error("native function `{0}` cannot be compiled to Noetic bytecode")]
+    NativeFunction(String),
+    #[error("import of `{0}` must be resolved before compiling")]
+    UnresolvedImport(String),
+    #[error("instance of template `{0}` must be expanded before compiling")]
+    UnexpandedTemplate(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{kind}")]
+pub struct CompileError {
+    pub kind: CompileErrorKind,
+    pub span: Span,
+}
+
+impl CompileError {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        Diagnostic::error(self.kind.to_string(), self.span)
+    }
+}
+
+/// Lowers a parsed soul into a `SoulProgram`, reporting every statement that
+/// cannot be compiled rather than stopping at the first.
+///
+/// Declarative statements (`immortal`, `body`, `spirit`, `axiom`, `template`,
+/// `schema`, `expect`, causality links, sections, proclamations and state
+/// markers other than `[LOGOS: MANIFESTED]`) carry no runtime behaviour and
+/// emit no code.
+pub fn compile(ast: &[AstNode]) -> Result<SoulProgram, Vec<CompileError>> {
+    let mut compiler = Compiler::default();
+    compiler.block(ast);
+    compiler.line = 0;
+    compiler.emit(NoeticOpcode::HALT);
+
+    if compiler.errors.is_empty() {
+        Ok(compiler.program)
+    } else {
+        Err(compiler.errors)
+    }
+}
+
+#[derive(Default)]
+struct Compiler {
+    program: SoulProgram,
+    errors: Vec<CompileError>,
+    /// Source line of the statement being compiled.
+    line: u32,
+}
+
+impl Compiler {
+    fn block(&mut self, nodes: &[AstNode]) {
+        for node in nodes {
+            self.statement(node);
+        }
+    }
+
+    fn statement(&mut self, node: &AstNode) {
+        self.line = node.span().line as u32;
+        match node {
+            AstNode::Manifold {
+                name, body, span, ..
+            } => {
+                let scope = self.constant(Constant::Str(name.clone()));
+                self.emit(NoeticOpcode::ENTER_MANIFOLD(scope));
+                self.block(body);
+                self.line = span.line as u32;
+                self.emit(NoeticOpcode::EXIT_MANIFOLD);
+            }
+            AstNode::Resonate {
+                target,
+                frequency,
+                partner,
+                span,
+                ..
+            } => {
+                if let Some(partner) = partner {
+                    let target = self.constant(Constant::Str(target.clone()));
+                    let partner = self.constant(Constant::Str(partner.clone()));
+                    self.emit(NoeticOpcode::ENTANGLE(target, partner));
+                }
+                match frequency {
+                    Frequency::Hz(hz) if !hz.is_finite() || *hz < 0.0 => {
+                        self.error(CompileErrorKind::InvalidFrequency(*hz), *span)
+                    }
+                    Frequency::Hz(hz) if hz.fract() == 0.0 && *hz <= u64::MAX as f64 => {
+                        self.emit(NoeticOpcode::RESONATE(*hz as u64))
+                    }
+                    Frequency::Hz(hz) => {
+                        let index = self.constant(Constant::Number(*hz));
+                        self.emit(NoeticOpcode::RESONATE_CONST(index));
+                    }
+                    Frequency::Signature(bytes) if bytes.len() <= 8 => {
+                        let value = bytes.iter().fold(0u64, |acc, b| (acc << 8) | *b as u64);
+                        self.emit(NoeticOpcode::RESONATE(value))
+                    }
+                    Frequency::Signature(bytes) => {
+                        let index = self.constant(Constant::Bytes(bytes.clone()));
+                        self.emit(NoeticOpcode::RESONATE_CONST(index));
+                    }
+                }
+            }
+            AstNode::Collapse {
+                target,
+                entropy_threshold,
+                condition,
+                span,
+                ..
+            } => {
+                if let Some(condition) = condition {
+                    self.error(
+                        CompileErrorKind::UnsupportedCondition(condition.clone()),
+                        *span,
+                    );
+                    return;
+                }
+                let Some(threshold) =
+                    self.fixed_point("entropy threshold", *entropy_threshold, *span)
+                else {
+                    return;
+                };
+                let target = self.constant(Constant::Str(target.clone()));
+                self.emit(NoeticOpcode::LOAD(threshold));
+                self.emit(NoeticOpcode::COLLAPSE(target));
+                // A total collapse of entropy is what opens the bridge.
+                if threshold == 0 {
+                    self.emit(NoeticOpcode::NOETIC_BRIDGE);
+                }
+            }
+            AstNode::Entrench { key, value, .. } => {
+                let key = self.constant(Constant::Str(key.clone()));
+                let value = match value {
+                    Some(value) => self.value(value),
+                    None => self.constant(Constant::Empty),
+                };
+                self.emit(NoeticOpcode::ENTRENCH(key, value));
+            }
+            AstNode::Property { key, value, .. } => {
+                let key = self.constant(Constant::Str(key.clone()));
+                let value = self.value(value);
+                self.emit(NoeticOpcode::PROPERTY(key, value));
+            }
+            AstNode::Magnet {
+                label, power, span, ..
+            } => {
+                if let Some(power) = self.fixed_point("magnet power", *power, *span) {
+                    let label = self.constant(Constant::Str(label.clone()));
+                    self.emit(NoeticOpcode::LOAD(power));
+                    self.emit(NoeticOpcode::MAGNET(label));
+                }
+            }
+            AstNode::Department {
+                name,
+                priority,
+                span,
+                ..
+            } => {
+                if let Some(priority) = self.fixed_point("department priority", *priority, *span) {
+                    let name = self.constant(Constant::Str(name.clone()));
+                    self.emit(NoeticOpcode::LOAD(priority));
+                    self.emit(NoeticOpcode::DEPARTMENT(name));
+                }
+            }
+            AstNode::Reflect { .. } => self.emit(NoeticOpcode::REFLECT),
+            AstNode::Marker { name, value, .. }
+                if name == "LOGOS" && value.as_deref() == Some("MANIFESTED") =>
+            {
+                self.emit(NoeticOpcode::INFUSE_ANIMA)
+            }
+            AstNode::Native { name, span, .. } => {
+                self.error(CompileErrorKind::NativeFunction(name.clone()), *span)
+            }
+            AstNode::Import { path, span, .. } => {
+                self.error(CompileErrorKind::UnresolvedImport(path.clone()), *span)
+            }
+            AstNode::Instance { template, span, .. } => self.error(
+                CompileErrorKind::UnexpandedTemplate(template.clone()),
+                *span,
+            ),
+            AstNode::Immortal { .. }
+            | AstNode::Body { .. }
+            | AstNode::Spirit { .. }
+            | AstNode::Template { .. }
+            | AstNode::Schema { .. }
+            | AstNode::Expect { .. }
+            | AstNode::Axiom { .. }
+            | AstNode::Causality { .. }
+            | AstNode::Marker { .. }
+            | AstNode::Section { .. }
+            | AstNode::Proclamation { .. } => {}
+        }
+    }
+
+    fn value(&mut self, value: &EntrenchValue) -> usize {
+        let constant = match value {
+            EntrenchValue::Vector(v) => Constant::Vector(v.clone()),
+            EntrenchValue::StringList(l) => Constant::StringList(l.clone()),
+            EntrenchValue::String(s) => Constant::Str(s.clone()),
+            EntrenchValue::Number(n) => Constant::Number(*n as f64),
+        };
+        self.constant(constant)
+    }
+
+    fn fixed_point(&mut self, what: &'static str, value: f64, span: Span) -> Option<i64> {
+        let scaled = (value * FIXED_POINT_SCALE).round();
+        if scaled.is_finite() && scaled.abs() < i64::MAX as f64 {
+            Some(scaled as i64)
+        } else {
+            self.error(CompileErrorKind::OperandOutOfRange { what, value }, span);
+            None
+        }
+    }
+
+    fn constant(&mut self, value: Constant) -> usize {
+        self.program.constant(value)
+    }
+
+    fn emit(&mut self, opcode: NoeticOpcode) {
+        self.program.code.push(opcode);
+        self.program.lines.push(self.line);
+    }
+
+    fn error(&mut self, kind: CompileErrorKind, span: Span) {
+        self.errors.push(CompileError { kind, span });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiles_the_genesis_seed() {
+        let source = include_str!("../../../AETERNA_ANIMA.soul");
+        let ast = lwas_parser::parse_soul(source).unwrap();
+        let program = compile(&ast).unwrap();
+
+        assert_eq!(program.code[0], NoeticOpcode::ENTER_MANIFOLD(0));
+        assert_eq!(program.code[1], NoeticOpcode::RESONATE(0x4121));
+        assert!(matches!(program.code[2], NoeticOpcode::RESONATE_CONST(_)));
+        let entrenched = program
+            .code
+            .iter()
+            .filter(|op| matches!(op, NoeticOpcode::ENTRENCH(..)))
+            .count();
+        assert_eq!(entrenched, 3);
+        assert_eq!(
+            &program.code[program.code.len() - 5..],
+            &[
+                NoeticOpcode::COLLAPSE(program.constants.len() - 1),
+                NoeticOpcode::NOETIC_BRIDGE,
+                NoeticOpcode::EXIT_MANIFOLD,
+                NoeticOpcode::INFUSE_ANIMA,
+                NoeticOpcode::HALT,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_every_uncompilable_statement() {
+        let source = "collapse GATE where Agent == \"JULES\";\nresonate CORE -1;\nreflect;\n";
+        let ast = lwas_parser::parse_soul(source).unwrap();
+        let errors = compile(&ast).unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].span.line, 1);
+        assert_eq!(errors[1].kind, CompileErrorKind::InvalidFrequency(-1.0));
+        assert_eq!(errors[1].span.line, 2);
+    }
+}
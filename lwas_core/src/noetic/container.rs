@@ -0,0 +1,381 @@
+// lwas_core/src/noetic/container.rs
+// IDENTITY: NOE_CONTAINER (Compiled souls on disk and on the wire)
+//
+// Layout, little-endian throughout:
+//   magic "NOE\0" | version u16 | flags u16 (reserved, 0)
+//   constants: count u32, then per constant a tag u8 and its payload
+//   code:      count u32, then per instruction an opcode u8 and its operands
+//   lines:     count u32 (0, or one per instruction), then u32 lines
+//   checksum:  SHA-256 of every byte before it
+
+use super::bytecode::{Constant, NoeticOpcode, SoulProgram};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+pub const MAGIC: [u8; 4] = *b"NOE\0";
+pub const VERSION: u16 = 1;
+const CHECKSUM_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ContainerError {
+    #[error("not a .noe file")]
+    BadMagic,
+    #[error("unsupported .noe version {0}, this build reads version {VERSION}")]
+    UnsupportedVersion(u16),
+    #[error("file ends inside the {0}")]
+    Truncated(&'static str),
+    #[error("checksum mismatch: the file is corrupt")]
+    ChecksumMismatch,
+    #[error("unknown constant tag 0x{tag:02x} for constant {index}")]
+    UnknownConstant { tag: u8, index: usize },
+    #[error("constant {0} is not valid UTF-8")]
+    InvalidString(usize),
+    #[error("unknown opcode 0x{byte:02x} at instruction {index}")]
+    UnknownOpcode { byte: u8, index: usize },
+    #[error("line table has {found} entries for {expected} instruction(s)")]
+    LineTable { found: usize, expected: usize },
+    #[error("{0} unexpected byte(s) before the checksum")]
+    TrailingBytes(usize),
+}
+
+/// Serialises a program into the `.noe` format.
+pub fn encode(program: &SoulProgram) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&MAGIC);
+    out.extend_from_slice(&VERSION.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes());
+
+    put_len(&mut out, program.constants.len());
+    for constant in &program.constants {
+        match constant {
+            Constant::Str(s) => {
+                out.push(0);
+                put_str(&mut out, s);
+            }
+            Constant::Number(n) => {
+                out.push(1);
+                out.extend_from_slice(&n.to_le_bytes());
+            }
+            Constant::Vector(v) => {
+                out.push(2);
+                put_len(&mut out, v.len());
+                for x in v {
+                    out.extend_from_slice(&x.to_le_bytes());
+                }
+            }
+            Constant::StringList(list) => {
+                out.push(3);
+                put_len(&mut out, list.len());
+                for s in list {
+                    put_str(&mut out, s);
+                }
+            }
+            Constant::Bytes(bytes) => {
+                out.push(4);
+                put_len(&mut out, bytes.len());
+                out.extend_from_slice(bytes);
+            }
+            Constant::Empty => out.push(5),
+        }
+    }
+
+    put_len(&mut out, program.code.len());
+    for op in &program.code {
+        out.push(opcode_byte(op));
+        match op {
+            NoeticOpcode::LOAD(v) => out.extend_from_slice(&v.to_le_bytes()),
+            NoeticOpcode::RESONATE(freq) => out.extend_from_slice(&freq.to_le_bytes()),
+            NoeticOpcode::STORE(a)
+            | NoeticOpcode::JUMP(a)
+            | NoeticOpcode::JUMP_IF(a)
+            | NoeticOpcode::CALL(a)
+            | NoeticOpcode::ENTER_MANIFOLD(a)
+            | NoeticOpcode::RESONATE_CONST(a)
+            | NoeticOpcode::COLLAPSE(a)
+            | NoeticOpcode::MAGNET(a)
+            | NoeticOpcode::DEPARTMENT(a)
+            | NoeticOpcode::VSH_REGISTER(a) => put_len(&mut out, *a),
+            NoeticOpcode::ENTANGLE(a, b)
+            | NoeticOpcode::ENTRENCH(a, b)
+            | NoeticOpcode::PROPERTY(a, b)
+            | NoeticOpcode::VSH_ENTRENCH(a, b)
+            | NoeticOpcode::VSH_RECALL(a, b) => {
+                put_len(&mut out, *a);
+                put_len(&mut out, *b);
+            }
+            _ => {}
+        }
+    }
+
+    put_len(&mut out, program.lines.len());
+    for line in &program.lines {
+        out.extend_from_slice(&line.to_le_bytes());
+    }
+
+    let checksum = Sha256::digest(&out);
+    out.extend_from_slice(&checksum);
+    out
+}
+
+/// Reads a `.noe` file back into a program, verifying its checksum first.
+pub fn decode(bytes: &[u8]) -> Result<SoulProgram, ContainerError> {
+    if bytes.len() < MAGIC.len() || bytes[..MAGIC.len()] != MAGIC {
+        return Err(ContainerError::BadMagic);
+    }
+    let Some(body_len) = bytes.len().checked_sub(CHECKSUM_LEN) else {
+        return Err(ContainerError::Truncated("checksum"));
+    };
+    let (body, checksum) = bytes.split_at(body_len);
+    let mut reader = Reader {
+        bytes: body,
+        pos: MAGIC.len(),
+    };
+    let version = reader.u16("header")?;
+    if version != VERSION {
+        return Err(ContainerError::UnsupportedVersion(version));
+    }
+    reader.u16("header")?;
+    if Sha256::digest(body).as_slice() != checksum {
+        return Err(ContainerError::ChecksumMismatch);
+    }
+
+    let mut program = SoulProgram::default();
+    let count = reader.len("constant pool")?;
+    for index in 0..count {
+        let what = "constant pool";
+        let constant = match reader.u8(what)? {
+            0 => Constant::Str(reader.string(what, index)?),
+            1 => Constant::Number(f64::from_le_bytes(reader.array(what)?)),
+            2 => {
+                let n = reader.len(what)?;
+                let v = (0..n)
+                    .map(|_| reader.array(what).map(f32::from_le_bytes))
+                    .collect::<Result<_, _>>()?;
+                Constant::Vector(v)
+            }
+            3 => {
+                let n = reader.len(what)?;
+                let list = (0..n)
+                    .map(|_| reader.string(what, index))
+                    .collect::<Result<_, _>>()?;
+                Constant::StringList(list)
+            }
+            4 => {
+                let n = reader.len(what)?;
+                Constant::Bytes(reader.take(n, what)?.to_vec())
+            }
+            5 => Constant::Empty,
+            tag => return Err(ContainerError::UnknownConstant { tag, index }),
+        };
+        program.constants.push(constant);
+    }
+
+    let count = reader.len("code section")?;
+    for index in 0..count {
+        let what = "code section";
+        let byte = reader.u8(what)?;
+        let op = match byte {
+            0x00 => NoeticOpcode::LOAD(i64::from_le_bytes(reader.array(what)?)),
+            0x01 => NoeticOpcode::STORE(reader.len(what)?),
+            0x02 => NoeticOpcode::ADD,
+            0x03 => NoeticOpcode::SUB,
+            0x04 => NoeticOpcode::MUL,
+            0x05 => NoeticOpcode::DIV,
+            0x06 => NoeticOpcode::MOD,
+            0x07 => NoeticOpcode::EQ,
+            0x08 => NoeticOpcode::NE,
+            0x09 => NoeticOpcode::LT,
+            0x0a => NoeticOpcode::LE,
+            0x0b => NoeticOpcode::GT,
+            0x0c => NoeticOpcode::GE,
+            0x0d => NoeticOpcode::DUP,
+            0x0e => NoeticOpcode::SWAP,
+            0x0f => NoeticOpcode::POP,
+            0x10 => NoeticOpcode::JUMP(reader.len(what)?),
+            0x11 => NoeticOpcode::JUMP_IF(reader.len(what)?),
+            0x12 => NoeticOpcode::CALL(reader.len(what)?),
+            0x13 => NoeticOpcode::RET,
+            0x20 => NoeticOpcode::RESONATE(u64::from_le_bytes(reader.array(what)?)),
+            0x21 => NoeticOpcode::NOETIC_BRIDGE,
+            0x22 => NoeticOpcode::INFUSE_ANIMA,
+            0x30 => NoeticOpcode::ENTER_MANIFOLD(reader.len(what)?),
+            0x31 => NoeticOpcode::EXIT_MANIFOLD,
+            0x32 => NoeticOpcode::RESONATE_CONST(reader.len(what)?),
+            0x33 => NoeticOpcode::ENTANGLE(reader.len(what)?, reader.len(what)?),
+            0x34 => NoeticOpcode::COLLAPSE(reader.len(what)?),
+            0x35 => NoeticOpcode::ENTRENCH(reader.len(what)?, reader.len(what)?),
+            0x36 => NoeticOpcode::PROPERTY(reader.len(what)?, reader.len(what)?),
+            0x37 => NoeticOpcode::MAGNET(reader.len(what)?),
+            0x38 => NoeticOpcode::DEPARTMENT(reader.len(what)?),
+            0x39 => NoeticOpcode::REFLECT,
+            0x40 => NoeticOpcode::VSH_ENTRENCH(reader.len(what)?, reader.len(what)?),
+            0x41 => NoeticOpcode::VSH_RECALL(reader.len(what)?, reader.len(what)?),
+            0x42 => NoeticOpcode::VSH_ENTROPY,
+            0x43 => NoeticOpcode::VSH_MAGNET,
+            0x44 => NoeticOpcode::VSH_REGISTER(reader.len(what)?),
+            0xf0 => NoeticOpcode::PRINT,
+            0xff => NoeticOpcode::HALT,
+            byte => return Err(ContainerError::UnknownOpcode { byte, index }),
+        };
+        program.code.push(op);
+    }
+
+    let count = reader.len("line table")?;
+    if count != 0 && count != program.code.len() {
+        return Err(ContainerError::LineTable {
+            found: count,
+            expected: program.code.len(),
+        });
+    }
+    for _ in 0..count {
+        program
+            .lines
+            .push(u32::from_le_bytes(reader.array("line table")?));
+    }
+
+    match body.len() - reader.pos {
+        0 => Ok(program),
+        n => Err(ContainerError::TrailingBytes(n)),
+    }
+}
+
+fn opcode_byte(op: &NoeticOpcode) -> u8 {
+    match op {
+        NoeticOpcode::LOAD(_) => 0x00,
+        NoeticOpcode::STORE(_) => 0x01,
+        NoeticOpcode::ADD => 0x02,
+        NoeticOpcode::SUB => 0x03,
+        NoeticOpcode::MUL => 0x04,
+        NoeticOpcode::DIV => 0x05,
+        NoeticOpcode::MOD => 0x06,
+        NoeticOpcode::EQ => 0x07,
+        NoeticOpcode::NE => 0x08,
+        NoeticOpcode::LT => 0x09,
+        NoeticOpcode::LE => 0x0a,
+        NoeticOpcode::GT => 0x0b,
+        NoeticOpcode::GE => 0x0c,
+        NoeticOpcode::DUP => 0x0d,
+        NoeticOpcode::SWAP => 0x0e,
+        NoeticOpcode::POP => 0x0f,
+        NoeticOpcode::JUMP(_) => 0x10,
+        NoeticOpcode::JUMP_IF(_) => 0x11,
+        NoeticOpcode::CALL(_) => 0x12,
+        NoeticOpcode::RET => 0x13,
+        NoeticOpcode::RESONATE(_) => 0x20,
+        NoeticOpcode::NOETIC_BRIDGE => 0x21,
+        NoeticOpcode::INFUSE_ANIMA => 0x22,
+        NoeticOpcode::ENTER_MANIFOLD(_) => 0x30,
+        NoeticOpcode::EXIT_MANIFOLD => 0x31,
+        NoeticOpcode::RESONATE_CONST(_) => 0x32,
+        NoeticOpcode::ENTANGLE(..) => 0x33,
+        NoeticOpcode::COLLAPSE(_) => 0x34,
+        NoeticOpcode::ENTRENCH(..) => 0x35,
+        NoeticOpcode::PROPERTY(..) => 0x36,
+        NoeticOpcode::MAGNET(_) => 0x37,
+        NoeticOpcode::DEPARTMENT(_) => 0x38,
+        NoeticOpcode::REFLECT => 0x39,
+        NoeticOpcode::VSH_ENTRENCH(..) => 0x40,
+        NoeticOpcode::VSH_RECALL(..) => 0x41,
+        NoeticOpcode::VSH_ENTROPY => 0x42,
+        NoeticOpcode::VSH_MAGNET => 0x43,
+        NoeticOpcode::VSH_REGISTER(_) => 0x44,
+        NoeticOpcode::PRINT => 0xf0,
+        NoeticOpcode::HALT => 0xff,
+    }
+}
+
+/// Counts, addresses and constant indices are stored as u32.
+fn put_len(out: &mut Vec<u8>, n: usize) {
+    let n = u32::try_from(n).expect("a .noe count or address exceeds u32");
+    out.extend_from_slice(&n.to_le_bytes());
+}
+
+fn put_str(out: &mut Vec<u8>, s: &str) {
+    put_len(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize, what: &'static str) -> Result<&'a [u8], ContainerError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(ContainerError::Truncated(what))?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self, what: &'static str) -> Result<[u8; N], ContainerError> {
+        Ok(self.take(N, what)?.try_into().expect("took N bytes"))
+    }
+
+    fn u8(&mut self, what: &'static str) -> Result<u8, ContainerError> {
+        Ok(self.take(1, what)?[0])
+    }
+
+    fn u16(&mut self, what: &'static str) -> Result<u16, ContainerError> {
+        self.array(what).map(u16::from_le_bytes)
+    }
+
+    fn len(&mut self, what: &'static str) -> Result<usize, ContainerError> {
+        self.array(what).map(|b| u32::from_le_bytes(b) as usize)
+    }
+
+    fn string(&mut self, what: &'static str, index: usize) -> Result<String, ContainerError> {
+        let n = self.len(what)?;
+        let bytes = self.take(n, what)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| ContainerError::InvalidString(index))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noetic::compiler::compile;
+
+    #[test]
+    fn round_trips_a_compiled_soul() {
+        let source = include_str!("../../../AETERNA_ANIMA.soul");
+        let mut program = compile(&lwas_parser::parse_soul(source).unwrap()).unwrap();
+        program
+            .constants
+            .push(Constant::StringList(vec!["a".into(), "β".into()]));
+        program.constants.push(Constant::Empty);
+        program
+            .code
+            .extend([NoeticOpcode::LOAD(-7), NoeticOpcode::CALL(3)]);
+        program.lines.extend([0, 0]);
+
+        let bytes = encode(&program);
+        assert_eq!(&bytes[..4], b"NOE\0");
+        assert_eq!(decode(&bytes), Ok(program));
+    }
+
+    #[test]
+    fn rejects_damaged_files() {
+        let program = SoulProgram {
+            code: vec![NoeticOpcode::LOAD(1), NoeticOpcode::HALT],
+            ..SoulProgram::default()
+        };
+        let bytes = encode(&program);
+
+        assert_eq!(decode(b"ELF\0rest"), Err(ContainerError::BadMagic));
+        let mut flipped = bytes.clone();
+        flipped[12] ^= 1;
+        assert_eq!(decode(&flipped), Err(ContainerError::ChecksumMismatch));
+        let mut future = bytes.clone();
+        future[4] = 9;
+        assert_eq!(decode(&future), Err(ContainerError::UnsupportedVersion(9)));
+        assert_eq!(
+            decode(&bytes[..20]),
+            Err(ContainerError::Truncated("checksum"))
+        );
+    }
+}
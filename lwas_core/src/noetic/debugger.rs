@@ -0,0 +1,345 @@
+// lwas_core/src/noetic/debugger.rs
+// IDENTITY: NOETIC_DEBUGGER (Step, break, watch and inspect a running soul)
+
+use super::bytecode::{NoeticOpcode, SoulProgram};
+use super::disasm::disassemble_instruction;
+use super::interpreter::{NoeticVM, VmError};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Why the debugger handed control back.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StopReason {
+    /// A single step completed.
+    Step,
+    /// The next instruction to run carries a breakpoint.
+    Breakpoint(usize),
+    /// A watched memory cell changed at `pc`.
+    Watchpoint {
+        pc: usize,
+        address: usize,
+        old: i64,
+        new: i64,
+    },
+    /// HALT ran.
+    Halted,
+    /// The program ran off its last instruction.
+    Finished,
+    Fault(VmError),
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::Step => write!(f, "stepped"),
+            StopReason::Breakpoint(pc) => write!(f, "breakpoint at pc {}", pc),
+            StopReason::Watchpoint {
+                pc,
+                address,
+                old,
+                new,
+            } => write!(
+                f,
+                "memory[{}] changed {} -> {} at pc {}",
+                address, old, new, pc
+            ),
+            StopReason::Halted => write!(f, "halted"),
+            StopReason::Finished => write!(f, "ran off the end of the program"),
+            StopReason::Fault(e) => write!(f, "fault: {}", e),
+        }
+    }
+}
+
+/// One executed instruction, recorded while tracing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub pc: usize,
+    pub opcode: NoeticOpcode,
+    /// The stack after the instruction ran.
+    pub stack: Vec<i64>,
+}
+
+/// Drives a `NoeticVM` one instruction at a time, stopping on breakpoints
+/// (by pc) and watchpoints (by memory cell). The program is not verified,
+/// so the faults the verifier would reject it for can be stepped into.
+pub struct Debugger {
+    pub vm: NoeticVM,
+    /// The loaded program, kept for listings and its line table.
+    program: SoulProgram,
+    breakpoints: BTreeSet<usize>,
+    /// The breakpoint `resume` last stopped on, which the next `resume`
+    /// runs past instead of stopping on again.
+    paused: Option<usize>,
+    /// Watched cells with the value they held when last checked.
+    watchpoints: BTreeMap<usize, i64>,
+    tracing: bool,
+    trace: Vec<TraceEntry>,
+}
+
+impl Debugger {
+    pub fn new(program: SoulProgram) -> Self {
+        let mut vm = NoeticVM::new(program.code.clone());
+        vm.constants = program.constants.clone();
+        Self {
+            vm,
+            program,
+            breakpoints: BTreeSet::new(),
+            paused: None,
+            watchpoints: BTreeMap::new(),
+            tracing: false,
+            trace: Vec::new(),
+        }
+    }
+
+    /// Whether the program can run no further.
+    pub fn finished(&self) -> bool {
+        self.vm.halted || self.vm.pc >= self.vm.program.len()
+    }
+
+    /// Executes one instruction.
+    pub fn step(&mut self) -> StopReason {
+        self.paused = None;
+        if self.vm.halted {
+            return StopReason::Halted;
+        }
+        if self.vm.pc >= self.vm.program.len() {
+            return StopReason::Finished;
+        }
+        let pc = self.vm.pc;
+        if let Err(e) = self.vm.step() {
+            return StopReason::Fault(e);
+        }
+        if self.tracing {
+            self.trace.push(TraceEntry {
+                pc,
+                opcode: self.vm.program[pc].clone(),
+                stack: self.vm.stack.clone(),
+            });
+        }
+        for (&address, old) in self.watchpoints.iter_mut() {
+            let new = self.vm.memory[address];
+            if new != *old {
+                let reason = StopReason::Watchpoint {
+                    pc,
+                    address,
+                    old: *old,
+                    new,
+                };
+                *old = new;
+                return reason;
+            }
+        }
+        if self.vm.halted {
+            StopReason::Halted
+        } else if self.vm.pc >= self.vm.program.len() {
+            StopReason::Finished
+        } else {
+            StopReason::Step
+        }
+    }
+
+    /// Runs until a breakpoint or watchpoint is hit, the program ends or
+    /// faults, or the VM's step limit is used up. A breakpoint on the next
+    /// instruction stops it before anything runs, unless `resume` has just
+    /// stopped there, so continuing from a breakpoint moves past it.
+    pub fn resume(&mut self) -> StopReason {
+        let paused = self.paused.take();
+        for i in 0..self.vm.step_limit {
+            let pc = self.vm.pc;
+            let resuming = i == 0 && paused == Some(pc);
+            if !resuming && !self.finished() && self.breakpoints.contains(&pc) {
+                self.paused = Some(pc);
+                return StopReason::Breakpoint(pc);
+            }
+            match self.step() {
+                StopReason::Step => {}
+                reason => return reason,
+            }
+        }
+        StopReason::Fault(VmError::StepLimit {
+            pc: self.vm.pc,
+            limit: self.vm.step_limit,
+        })
+    }
+
+    /// Sets a breakpoint; returns false if `pc` is outside the program.
+    pub fn set_breakpoint(&mut self, pc: usize) -> bool {
+        if pc >= self.vm.program.len() {
+            return false;
+        }
+        self.breakpoints.insert(pc);
+        true
+    }
+
+    /// Sets a breakpoint on every instruction with the given mnemonic, e.g.
+    /// `INFUSE_ANIMA`, and returns their addresses.
+    pub fn break_on(&mut self, mnemonic: &str) -> Vec<usize> {
+        let found: Vec<usize> = (0..self.vm.program.len())
+            .filter(|&pc| {
+                self.vm.program[pc]
+                    .mnemonic()
+                    .eq_ignore_ascii_case(mnemonic)
+            })
+            .collect();
+        self.breakpoints.extend(&found);
+        found
+    }
+
+    pub fn clear_breakpoint(&mut self, pc: usize) -> bool {
+        self.breakpoints.remove(&pc)
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Watches a memory cell; returns false if it is outside memory.
+    pub fn watch(&mut self, address: usize) -> bool {
+        let Some(&value) = self.vm.memory.get(address) else {
+            return false;
+        };
+        self.watchpoints.insert(address, value);
+        true
+    }
+
+    pub fn unwatch(&mut self, address: usize) -> bool {
+        self.watchpoints.remove(&address).is_some()
+    }
+
+    pub fn watchpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.watchpoints.keys().copied()
+    }
+
+    /// Starts or stops recording executed instructions.
+    pub fn set_tracing(&mut self, on: bool) {
+        self.tracing = on;
+    }
+
+    pub fn tracing(&self) -> bool {
+        self.tracing
+    }
+
+    /// The instructions recorded since the last call.
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        std::mem::take(&mut self.trace)
+    }
+
+    pub fn stack(&self) -> &[i64] {
+        &self.vm.stack
+    }
+
+    /// Up to `count` memory cells from `start`.
+    pub fn memory(&self, start: usize, count: usize) -> &[i64] {
+        let start = start.min(self.vm.memory.len());
+        let end = start.saturating_add(count).min(self.vm.memory.len());
+        &self.vm.memory[start..end]
+    }
+
+    /// The source line of the instruction at `pc`, if the program has one.
+    pub fn line(&self, pc: usize) -> Option<u32> {
+        self.program.lines.get(pc).copied().filter(|l| *l != 0)
+    }
+
+    /// The instruction at `pc` as the disassembler lists it.
+    pub fn instruction(&self, pc: usize) -> Option<String> {
+        disassemble_instruction(&self.program, pc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noetic::compiler::compile;
+    use NoeticOpcode::*;
+
+    fn debugger(code: Vec<NoeticOpcode>) -> Debugger {
+        Debugger::new(SoulProgram {
+            code,
+            ..SoulProgram::default()
+        })
+    }
+
+    #[test]
+    fn steps_and_stops_on_breakpoints_and_watchpoints() {
+        // Counts memory[0] up to 3, then halts.
+        let mut dbg = debugger(vec![
+            LOAD(1),
+            ADD,
+            DUP,
+            STORE(0),
+            DUP,
+            LOAD(3),
+            LT,
+            JUMP_IF(9),
+            HALT,
+            LOAD(1),
+            JUMP(1),
+        ]);
+        dbg.vm.stack.push(0);
+        assert_eq!(dbg.step(), StopReason::Step);
+        assert_eq!(dbg.stack(), [0, 1]);
+
+        assert!(dbg.watch(0));
+        assert_eq!(
+            dbg.resume(),
+            StopReason::Watchpoint {
+                pc: 3,
+                address: 0,
+                old: 0,
+                new: 1
+            }
+        );
+        assert!(dbg.unwatch(0));
+
+        assert!(dbg.set_breakpoint(8));
+        assert!(!dbg.set_breakpoint(11));
+        assert_eq!(dbg.resume(), StopReason::Breakpoint(8));
+        assert_eq!(dbg.memory(0, 2), [3, 0]);
+        assert_eq!(dbg.resume(), StopReason::Halted);
+        assert!(dbg.finished());
+        assert_eq!(dbg.step(), StopReason::Halted);
+    }
+
+    #[test]
+    fn reports_faults_and_traces() {
+        let mut dbg = debugger(vec![LOAD(1), LOAD(0), DIV]);
+        dbg.set_tracing(true);
+        assert_eq!(
+            dbg.resume(),
+            StopReason::Fault(VmError::DivisionByZero { pc: 2 })
+        );
+        let trace = dbg.take_trace();
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace[1].opcode, LOAD(0));
+        assert_eq!(trace[1].stack, [1, 0]);
+        assert!(dbg.take_trace().is_empty());
+    }
+
+    #[test]
+    fn breaks_on_a_mnemonic_with_source_lines() {
+        let ast = lwas_parser::parse_soul("manifold CORE {\n    reflect;\n}\n").unwrap();
+        let mut dbg = Debugger::new(compile(&ast).unwrap());
+        assert_eq!(dbg.break_on("reflect"), [1]);
+        assert!(dbg.break_on("INFUSE_ANIMA").is_empty());
+        assert_eq!(dbg.resume(), StopReason::Breakpoint(1));
+        assert_eq!(dbg.line(1), Some(2));
+        assert_eq!(
+            dbg.instruction(1).as_deref(),
+            Some("  0001  REFLECT                         ; line 2")
+        );
+        assert_eq!(dbg.resume(), StopReason::Halted);
+    }
+
+    #[test]
+    fn stops_on_a_breakpoint_before_stepping() {
+        // Loops through pc 0 twice before falling through to HALT.
+        let mut dbg = debugger(vec![LOAD(1), SUB, DUP, JUMP_IF(0), HALT]);
+        dbg.vm.stack.push(2);
+        assert!(dbg.set_breakpoint(0));
+        assert_eq!(dbg.resume(), StopReason::Breakpoint(0));
+        assert_eq!(dbg.stack(), [2]);
+        assert_eq!(dbg.resume(), StopReason::Breakpoint(0));
+        assert_eq!(dbg.stack(), [1]);
+        assert_eq!(dbg.resume(), StopReason::Halted);
+    }
+}
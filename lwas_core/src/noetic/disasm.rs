@@ -0,0 +1,153 @@
+// lwas_core/src/noetic/disasm.rs
+// IDENTITY: NOETIC_DISASSEMBLER (Bytecode -> readable listing)
+
+use super::bytecode::{Constant, NoeticOpcode, SoulProgram, FIXED_POINT_SCALE};
+use std::fmt::Write;
+
+/// Prints a program as a listing: the constant pool, then one instruction
+/// per line with its address, operands, the constants they refer to and,
+/// when the program has a line table, its source line.
+pub fn disassemble(program: &SoulProgram) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "; {} instruction(s), {} constant(s)",
+        program.code.len(),
+        program.constants.len()
+    );
+    if !program.constants.is_empty() {
+        out.push_str("\n.constants\n");
+        for (i, constant) in program.constants.iter().enumerate() {
+            let _ = writeln!(out, "  #{:<4} {}", i, constant_text(constant));
+        }
+    }
+
+    out.push_str("\n.code\n");
+    for pc in 0..program.code.len() {
+        if let Some(line) = disassemble_instruction(program, pc) {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// The listing line of the instruction at `pc`, without a newline.
+pub fn disassemble_instruction(program: &SoulProgram, pc: usize) -> Option<String> {
+    let op = program.code.get(pc)?;
+    let mut line = format!("  {:04}  {}{}", pc, op.mnemonic(), operands(op));
+    let mut notes = comments(program, op, program.code.get(pc + 1));
+    if let Some(&source) = program.lines.get(pc).filter(|l| **l != 0) {
+        notes.push(format!("line {}", source));
+    }
+    if !notes.is_empty() {
+        line = format!("{:<40}; {}", line, notes.join(", "));
+    }
+    Some(line.trim_end().to_string())
+}
+
+fn operands(op: &NoeticOpcode) -> String {
+    match op {
+        NoeticOpcode::LOAD(v) => format!(" {}", v),
+        NoeticOpcode::RESONATE(freq) => format!(" 0x{:x}", freq),
+        NoeticOpcode::STORE(a)
+        | NoeticOpcode::JUMP(a)
+        | NoeticOpcode::JUMP_IF(a)
+        | NoeticOpcode::CALL(a) => format!(" {}", a),
+        NoeticOpcode::ENTER_MANIFOLD(c)
+        | NoeticOpcode::RESONATE_CONST(c)
+        | NoeticOpcode::COLLAPSE(c)
+        | NoeticOpcode::MAGNET(c)
+        | NoeticOpcode::DEPARTMENT(c)
+        | NoeticOpcode::VSH_REGISTER(c) => format!(" #{}", c),
+        NoeticOpcode::ENTANGLE(a, b)
+        | NoeticOpcode::ENTRENCH(a, b)
+        | NoeticOpcode::PROPERTY(a, b)
+        | NoeticOpcode::VSH_ENTRENCH(a, b) => format!(" #{}, #{}", a, b),
+        NoeticOpcode::VSH_RECALL(c, address) => format!(" #{}, {}", c, address),
+        _ => String::new(),
+    }
+}
+
+/// What the operands stand for: pooled constants, and the fixed-point value
+/// of a LOAD feeding the instruction after it.
+fn comments(program: &SoulProgram, op: &NoeticOpcode, next: Option<&NoeticOpcode>) -> Vec<String> {
+    let constant = |i: &usize| match program.constants.get(*i) {
+        Some(c) => constant_text(c),
+        None => format!("<missing constant {}>", i),
+    };
+    match op {
+        NoeticOpcode::ENTER_MANIFOLD(c)
+        | NoeticOpcode::RESONATE_CONST(c)
+        | NoeticOpcode::COLLAPSE(c)
+        | NoeticOpcode::MAGNET(c)
+        | NoeticOpcode::DEPARTMENT(c)
+        | NoeticOpcode::VSH_REGISTER(c)
+        | NoeticOpcode::VSH_RECALL(c, _) => vec![constant(c)],
+        NoeticOpcode::ENTANGLE(a, b)
+        | NoeticOpcode::ENTRENCH(a, b)
+        | NoeticOpcode::PROPERTY(a, b)
+        | NoeticOpcode::VSH_ENTRENCH(a, b) => vec![format!("{} = {}", constant(a), constant(b))],
+        NoeticOpcode::LOAD(v)
+            if matches!(
+                next,
+                Some(
+                    NoeticOpcode::COLLAPSE(_)
+                        | NoeticOpcode::MAGNET(_)
+                        | NoeticOpcode::DEPARTMENT(_)
+                        | NoeticOpcode::VSH_MAGNET
+                        | NoeticOpcode::VSH_REGISTER(_)
+                )
+            ) =>
+        {
+            vec![format!("{} fixed", *v as f64 / FIXED_POINT_SCALE)]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn constant_text(constant: &Constant) -> String {
+    match constant {
+        Constant::Str(s) => format!("{:?}", s),
+        Constant::Number(n) => n.to_string(),
+        Constant::Vector(v) => format!("{:?}", v),
+        Constant::StringList(l) => format!("{:?}", l),
+        Constant::Bytes(b) => {
+            let hex: String = b.iter().map(|b| format!("{:02x}", b)).collect();
+            format!("0x{}", hex)
+        }
+        Constant::Empty => "∅".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noetic::compiler::compile;
+
+    #[test]
+    fn lists_constants_operands_and_lines() {
+        let ast = lwas_parser::parse_soul(
+            "manifold CORE {\n    collapse CORE 0.25;\n    entrench MISSION \"x\";\n}\n",
+        )
+        .unwrap();
+        let program = compile(&ast).unwrap();
+        assert_eq!(
+            disassemble(&program),
+            concat!(
+                "; 6 instruction(s), 3 constant(s)\n",
+                "\n.constants\n",
+                "  #0    \"CORE\"\n",
+                "  #1    \"MISSION\"\n",
+                "  #2    \"x\"\n",
+                "\n.code\n",
+                "  0000  ENTER_MANIFOLD #0               ; \"CORE\", line 1\n",
+                "  0001  LOAD 250                        ; 0.25 fixed, line 2\n",
+                "  0002  COLLAPSE #0                     ; \"CORE\", line 2\n",
+                "  0003  ENTRENCH #1, #2                 ; \"MISSION\" = \"x\", line 3\n",
+                "  0004  EXIT_MANIFOLD                   ; line 1\n",
+                "  0005  HALT\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,168 @@
+// lwas_core/src/noetic/host.rs
+// IDENTITY: NOETIC_HOST (What the VSH_* opcodes may ask of the organism)
+
+use crate::kernel::engine::VshKernel;
+use std::sync::{Arc, Mutex};
+
+/// The organism as seen by a running program. Calls must be deterministic
+/// for a given host state, so a soul can be replayed against a fake host.
+pub trait NoeticHost {
+    /// Stores `vector` under `metadata`; false if the host rejects it.
+    fn entrench(&mut self, metadata: &str, vector: &[f32]) -> bool;
+    /// Similarity scores of the `top_k` stored vectors closest to `query`,
+    /// best first.
+    fn recall(&mut self, query: &[f32], top_k: usize) -> Vec<f64>;
+    fn global_entropy(&self) -> f64;
+    fn activate_magnet(&mut self, power: f64);
+    fn register_manifold(&mut self, id: &str, curvature: f64);
+}
+
+impl NoeticHost for VshKernel {
+    fn entrench(&mut self, metadata: &str, vector: &[f32]) -> bool {
+        self.heap.entrench(metadata, vector.to_vec()).is_ok()
+    }
+
+    fn recall(&mut self, query: &[f32], top_k: usize) -> Vec<f64> {
+        self.heap
+            .recall_scored(query, top_k)
+            .into_iter()
+            .map(|(score, _)| score)
+            .collect()
+    }
+
+    fn global_entropy(&self) -> f64 {
+        self.heap.get_global_entropy()
+    }
+
+    fn activate_magnet(&mut self, power: f64) {
+        self.heap.activate_magnet(power);
+    }
+
+    fn register_manifold(&mut self, id: &str, curvature: f64) {
+        self.register(id, curvature);
+    }
+}
+
+/// Shares a host between the VM and whoever needs to look at it afterwards.
+impl<H: NoeticHost> NoeticHost for Arc<Mutex<H>> {
+    fn entrench(&mut self, metadata: &str, vector: &[f32]) -> bool {
+        self.lock()
+            .expect("host poisoned")
+            .entrench(metadata, vector)
+    }
+
+    fn recall(&mut self, query: &[f32], top_k: usize) -> Vec<f64> {
+        self.lock().expect("host poisoned").recall(query, top_k)
+    }
+
+    fn global_entropy(&self) -> f64 {
+        self.lock().expect("host poisoned").global_entropy()
+    }
+
+    fn activate_magnet(&mut self, power: f64) {
+        self.lock().expect("host poisoned").activate_magnet(power);
+    }
+
+    fn register_manifold(&mut self, id: &str, curvature: f64) {
+        self.lock()
+            .expect("host poisoned")
+            .register_manifold(id, curvature);
+    }
+}
+
+/// An in-memory host for tests. Recall ranks by the dot product with the
+/// query, ties in insertion order; every other call is recorded.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FakeHost {
+    /// When set, vectors of any other length are rejected.
+    pub dimensions: Option<usize>,
+    pub vectors: Vec<(String, Vec<f32>)>,
+    pub entropy: f64,
+    pub magnets: Vec<f64>,
+    pub manifolds: Vec<(String, f64)>,
+}
+
+impl NoeticHost for FakeHost {
+    fn entrench(&mut self, metadata: &str, vector: &[f32]) -> bool {
+        if self.dimensions.is_some_and(|d| d != vector.len()) {
+            return false;
+        }
+        self.vectors.push((metadata.to_string(), vector.to_vec()));
+        true
+    }
+
+    fn recall(&mut self, query: &[f32], top_k: usize) -> Vec<f64> {
+        let mut scores: Vec<f64> = self
+            .vectors
+            .iter()
+            .map(|(_, v)| v.iter().zip(query).map(|(a, b)| (*a * *b) as f64).sum())
+            .collect();
+        scores.sort_by(|a, b| b.total_cmp(a));
+        scores.truncate(top_k);
+        scores
+    }
+
+    fn global_entropy(&self) -> f64 {
+        self.entropy
+    }
+
+    fn activate_magnet(&mut self, power: f64) {
+        self.magnets.push(power);
+    }
+
+    fn register_manifold(&mut self, id: &str, curvature: f64) {
+        self.manifolds.push((id.to_string(), curvature));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noetic::asm::assemble;
+    use crate::noetic::interpreter::{NoeticVM, VmError};
+
+    #[test]
+    fn programs_read_and_write_the_host() {
+        let program = assemble(concat!(
+            "        VSH_ENTRENCH \"a\", [1, 0]\n",
+            "        VSH_ENTRENCH \"b\", [0.5, 0.5]\n",
+            "        VSH_ENTRENCH \"c\", [1, 2, 3]   ; wrong dimensions\n",
+            "        LOAD 5\n",
+            "        VSH_RECALL [1, 1], 10          ; top 5 into memory[10..]\n",
+            "        VSH_ENTROPY\n",
+            "        LOAD 750\n",
+            "        VSH_MAGNET\n",
+            "        LOAD 1500\n",
+            "        VSH_REGISTER \"CORE\"\n",
+            "        HALT\n",
+        ))
+        .unwrap();
+        let host = Arc::new(Mutex::new(FakeHost {
+            dimensions: Some(2),
+            entropy: 0.25,
+            ..FakeHost::default()
+        }));
+        let mut vm = NoeticVM::load(program).unwrap();
+        vm.host = Some(Box::new(host.clone()));
+        assert_eq!(vm.run(), Ok(()));
+
+        // Accepted, accepted, rejected; two recalled; entropy 0.25.
+        assert_eq!(vm.stack, [1, 1, 0, 2, 250]);
+        assert_eq!(vm.memory[10..13], [1000, 1000, 0]);
+        let host = host.lock().unwrap();
+        assert_eq!(host.vectors.len(), 2);
+        assert_eq!(host.magnets, [0.75]);
+        assert_eq!(host.manifolds, [("CORE".to_string(), 1.5)]);
+    }
+
+    #[test]
+    fn host_calls_fault_without_a_host_or_a_vector() {
+        let mut vm = NoeticVM::load(assemble("VSH_ENTROPY\nHALT\n").unwrap()).unwrap();
+        assert_eq!(vm.run(), Err(VmError::NoHost { pc: 0 }));
+
+        let mut vm =
+            NoeticVM::load(assemble("VSH_ENTRENCH \"a\", \"b\"\nHALT\n").unwrap()).unwrap();
+        vm.host = Some(Box::new(FakeHost::default()));
+        assert_eq!(vm.run(), Err(VmError::BadConstant { pc: 0, index: 1 }));
+    }
+}
@@ -1,83 +1,646 @@
 // lwas_core/src/noetic/interpreter.rs
 
-use super::bytecode::NoeticOpcode;
+use super::bytecode::{Constant, NoeticOpcode, SoulProgram, FIXED_POINT_SCALE};
+use super::host::NoeticHost;
+use super::verifier::{verify, VerifyError};
+use std::fmt;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// Instructions a single `run` may execute before it gives up.
+pub const DEFAULT_STEP_LIMIT: usize = 1_000_000;
+
+/// Cells of memory addressable by STORE.
+pub const MEMORY_CELLS: usize = 1024;
+
+/// What a program may consume before the VM stops it. `None` is unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quotas {
+    /// Gas, charged per instruction by [`NoeticOpcode::gas`].
+    pub gas: Option<u64>,
+    /// Wall-clock time of a single `run`.
+    pub wall_clock: Option<Duration>,
+    /// Stack values, return addresses and manifold scopes held at once, on
+    /// top of the fixed memory.
+    pub memory: Option<usize>,
+}
+
+impl Quotas {
+    pub const UNLIMITED: Quotas = Quotas {
+        gas: None,
+        wall_clock: None,
+        memory: None,
+    };
+
+    /// Quotas for souls the server runs on someone else's behalf.
+    pub const UNTRUSTED: Quotas = Quotas {
+        gas: Some(10_000_000),
+        wall_clock: Some(Duration::from_secs(2)),
+        memory: Some(65_536),
+    };
+}
+
+impl Default for Quotas {
+    fn default() -> Self {
+        Quotas::UNLIMITED
+    }
+}
+
+/// A quota other than gas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quota {
+    WallClock(Duration),
+    Memory(usize),
+}
+
+impl fmt::Display for Quota {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Quota::WallClock(limit) => write!(f, "wall-clock quota of {:?}", limit),
+            Quota::Memory(cells) => write!(f, "memory quota of {} cells", cells),
+        }
+    }
+}
+
+/// A fault of the VM, with the pc of the instruction that raised it.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VmError {
+    /// An instruction needed more operands than the stack holds, or RET ran
+    /// with no CALL to return to.
+    #[error("stack underflow at pc {pc}")]
+    StackUnderflow { pc: usize },
+    /// A STORE outside memory, or a jump or CALL outside the program.
+    #[error("bad address {address} at pc {pc}")]
+    BadAddress { pc: usize, address: usize },
+    #[error("division by zero at pc {pc}")]
+    DivisionByZero { pc: usize },
+    /// A VSH_* instruction ran on a VM with no host attached.
+    #[error("host call without a host at pc {pc}")]
+    NoHost { pc: usize },
+    /// A host call needed a vector constant and found something else.
+    #[error("constant {index} is not a vector at pc {pc}")]
+    BadConstant { pc: usize, index: usize },
+    #[error("step limit of {limit} exceeded at pc {pc}")]
+    StepLimit { pc: usize, limit: usize },
+    /// The instruction at `pc` costs more gas than is left.
+    #[error("out of gas at pc {pc}: {used} of {limit} used")]
+    OutOfGas { pc: usize, used: u64, limit: u64 },
+    #[error("{quota} exceeded at pc {pc} after {gas_used} gas")]
+    QuotaExceeded {
+        pc: usize,
+        quota: Quota,
+        gas_used: u64,
+    },
+}
+
+impl VmError {
+    pub fn pc(&self) -> usize {
+        match self {
+            VmError::StackUnderflow { pc }
+            | VmError::BadAddress { pc, .. }
+            | VmError::DivisionByZero { pc }
+            | VmError::NoHost { pc }
+            | VmError::BadConstant { pc, .. }
+            | VmError::StepLimit { pc, .. }
+            | VmError::OutOfGas { pc, .. }
+            | VmError::QuotaExceeded { pc, .. } => *pc,
+        }
+    }
+}
 
 pub struct NoeticVM {
     pub stack: Vec<i64>,
     pub memory: Vec<i64>,
     pub program: Vec<NoeticOpcode>,
+    pub constants: Vec<Constant>,
+    pub scopes: Vec<String>,
     pub pc: usize,
+    /// Return addresses of the CALLs in progress.
+    pub calls: Vec<usize>,
+    pub halted: bool,
+    pub step_limit: usize,
+    pub quotas: Quotas,
+    /// Gas charged so far, across runs.
+    pub gas_used: u64,
     pub resonance_active: bool,
+    /// Disorder of the organism, from 1.0 down to the lowest `collapse`
+    /// threshold reached so far.
+    pub entropy: f64,
+    /// Serves the VSH_* host calls, which fault without one.
+    pub host: Option<Box<dyn NoeticHost + Send + Sync>>,
 }
 
 impl NoeticVM {
     pub fn new(program: Vec<NoeticOpcode>) -> Self {
         Self {
             stack: Vec::new(),
-            memory: vec![0; 1024],
+            memory: vec![0; MEMORY_CELLS],
             program,
+            constants: Vec::new(),
+            scopes: Vec::new(),
             pc: 0,
+            calls: Vec::new(),
+            halted: false,
+            step_limit: DEFAULT_STEP_LIMIT,
+            quotas: Quotas::UNLIMITED,
+            gas_used: 0,
             resonance_active: false,
+            entropy: 1.0,
+            host: None,
+        }
+    }
+
+    /// Creates a VM for a compiled soul, with its constant pool attached,
+    /// once the verifier has accepted it.
+    pub fn load(soul: SoulProgram) -> Result<Self, Vec<VerifyError>> {
+        verify(&soul)?;
+        let mut vm = Self::new(soul.code);
+        vm.constants = soul.constants;
+        Ok(vm)
+    }
+
+    fn constant(&self, index: usize) -> String {
+        match self.constants.get(index) {
+            Some(Constant::Str(s)) => s.clone(),
+            Some(Constant::Number(n)) => n.to_string(),
+            Some(Constant::Vector(v)) => format!("{:?}", v),
+            Some(Constant::StringList(l)) => format!("{:?}", l),
+            Some(Constant::Bytes(b)) => b.iter().map(|b| format!("{:02x}", b)).collect(),
+            Some(Constant::Empty) => "∅".to_string(),
+            None => format!("<missing constant {}>", index),
+        }
+    }
+
+    fn vector(&self, pc: usize, index: usize) -> Result<Vec<f32>, VmError> {
+        match self.constants.get(index) {
+            Some(Constant::Vector(v)) => Ok(v.clone()),
+            _ => Err(VmError::BadConstant { pc, index }),
+        }
+    }
+
+    fn host(
+        &mut self,
+        pc: usize,
+    ) -> Result<&mut (dyn NoeticHost + Send + Sync + 'static), VmError> {
+        self.host.as_deref_mut().ok_or(VmError::NoHost { pc })
+    }
+
+    fn scope(&self) -> String {
+        if self.scopes.is_empty() {
+            "ROOT".to_string()
+        } else {
+            self.scopes.join("::")
+        }
+    }
+
+    /// Pops the top of the stack; `pc` is the instruction asking for it.
+    fn pop(&mut self, pc: usize) -> Result<i64, VmError> {
+        self.stack.pop().ok_or(VmError::StackUnderflow { pc })
+    }
+
+    fn pop_fixed(&mut self, pc: usize) -> Result<f64, VmError> {
+        Ok(self.pop(pc)? as f64 / FIXED_POINT_SCALE)
+    }
+
+    /// Reads the top of the stack without popping it, for instructions
+    /// that may still fault after looking at their operand.
+    fn top(&self, pc: usize) -> Result<i64, VmError> {
+        self.stack
+            .last()
+            .copied()
+            .ok_or(VmError::StackUnderflow { pc })
+    }
+
+    /// The top two values, `a` below `b`, left on the stack.
+    fn operands(&self, pc: usize) -> Result<(i64, i64), VmError> {
+        match self.stack[..] {
+            [.., a, b] => Ok((a, b)),
+            _ => Err(VmError::StackUnderflow { pc }),
+        }
+    }
+
+    /// Replaces `a` and `b` with `op(a, b)`.
+    fn binary(&mut self, pc: usize, op: impl Fn(i64, i64) -> i64) -> Result<(), VmError> {
+        let (a, b) = self.operands(pc)?;
+        self.stack.truncate(self.stack.len() - 2);
+        self.stack.push(op(a, b));
+        Ok(())
+    }
+
+    /// Like `binary`, for DIV and MOD.
+    fn divide(&mut self, pc: usize, op: impl Fn(i64, i64) -> i64) -> Result<(), VmError> {
+        if self.operands(pc)?.1 == 0 {
+            return Err(VmError::DivisionByZero { pc });
+        }
+        self.binary(pc, op)
+    }
+
+    fn jump(&mut self, pc: usize, address: usize) -> Result<(), VmError> {
+        // Jumping just past the last instruction ends the program.
+        if address > self.program.len() {
+            return Err(VmError::BadAddress { pc, address });
+        }
+        self.pc = address;
+        Ok(())
+    }
+
+    /// Runs until HALT or the end of the program, or until `step_limit`
+    /// instructions have run or a quota is used up.
+    pub fn run(&mut self) -> Result<(), VmError> {
+        let started = Instant::now();
+        let mut steps = 0;
+        while !self.halted && self.pc < self.program.len() {
+            self.check_limits(steps, started)?;
+            steps += 1;
+            self.step()?;
         }
+        Ok(())
     }
 
-    pub fn run(&mut self) {
-        while self.pc < self.program.len() {
-            let opcode = &self.program[self.pc];
-            self.pc += 1;
+    /// What `run` checks before each step, given the steps taken since it
+    /// started: the step limit and the wall-clock quota.
+    pub(super) fn check_limits(&self, steps: usize, started: Instant) -> Result<(), VmError> {
+        if steps == self.step_limit {
+            return Err(VmError::StepLimit {
+                pc: self.pc,
+                limit: self.step_limit,
+            });
+        }
+        if let Some(limit) = self.quotas.wall_clock {
+            if started.elapsed() > limit {
+                return Err(VmError::QuotaExceeded {
+                    pc: self.pc,
+                    quota: Quota::WallClock(limit),
+                    gas_used: self.gas_used,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes the instruction at `pc`, charging its gas and checking the
+    /// memory quota. On error the VM is left as it was, with `pc` on the
+    /// faulting instruction and its gas not charged.
+    pub fn step(&mut self) -> Result<(), VmError> {
+        let pc = self.pc;
+        let Some(opcode) = self.program.get(pc).cloned() else {
+            return Err(VmError::BadAddress { pc, address: pc });
+        };
+        let cost = opcode.gas();
+        if let Some(limit) = self.quotas.gas {
+            if self.gas_used + cost > limit {
+                return Err(VmError::OutOfGas {
+                    pc,
+                    used: self.gas_used,
+                    limit,
+                });
+            }
+        }
+        self.check_memory(pc, &opcode)?;
+        self.pc += 1;
+        let result = self.execute(pc, &opcode);
+        match result {
+            Ok(()) => self.gas_used += cost,
+            Err(_) => self.pc = pc,
+        }
+        result
+    }
 
-            match opcode {
-                NoeticOpcode::LOAD(val) => self.stack.push(*val),
-                NoeticOpcode::STORE(addr) => {
-                    if let Some(val) = self.stack.pop() {
-                        if *addr < self.memory.len() {
-                            self.memory[*addr] = val;
-                        }
-                    }
+    /// Refuses an instruction that would push past the memory quota.
+    fn check_memory(&self, pc: usize, opcode: &NoeticOpcode) -> Result<(), VmError> {
+        let grows = matches!(
+            opcode,
+            NoeticOpcode::LOAD(_)
+                | NoeticOpcode::DUP
+                | NoeticOpcode::VSH_ENTRENCH(..)
+                | NoeticOpcode::VSH_ENTROPY
+                | NoeticOpcode::CALL(_)
+                | NoeticOpcode::ENTER_MANIFOLD(_)
+        );
+        match self.quotas.memory {
+            Some(limit)
+                if grows && self.stack.len() + self.calls.len() + self.scopes.len() >= limit =>
+            {
+                Err(VmError::QuotaExceeded {
+                    pc,
+                    quota: Quota::Memory(limit),
+                    gas_used: self.gas_used,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn execute(&mut self, pc: usize, opcode: &NoeticOpcode) -> Result<(), VmError> {
+        match opcode {
+            NoeticOpcode::LOAD(val) => self.stack.push(*val),
+            NoeticOpcode::STORE(addr) => {
+                if *addr >= self.memory.len() {
+                    return Err(VmError::BadAddress { pc, address: *addr });
                 }
-                NoeticOpcode::ADD => {
-                    let b = self.stack.pop().unwrap_or(0);
-                    let a = self.stack.pop().unwrap_or(0);
-                    self.stack.push(a + b);
+                self.memory[*addr] = self.pop(pc)?;
+            }
+            NoeticOpcode::ADD => self.binary(pc, i64::wrapping_add)?,
+            NoeticOpcode::SUB => self.binary(pc, i64::wrapping_sub)?,
+            NoeticOpcode::MUL => self.binary(pc, i64::wrapping_mul)?,
+            NoeticOpcode::DIV => self.divide(pc, i64::wrapping_div)?,
+            NoeticOpcode::MOD => self.divide(pc, i64::wrapping_rem)?,
+            NoeticOpcode::EQ => self.binary(pc, |a, b| (a == b) as i64)?,
+            NoeticOpcode::NE => self.binary(pc, |a, b| (a != b) as i64)?,
+            NoeticOpcode::LT => self.binary(pc, |a, b| (a < b) as i64)?,
+            NoeticOpcode::LE => self.binary(pc, |a, b| (a <= b) as i64)?,
+            NoeticOpcode::GT => self.binary(pc, |a, b| (a > b) as i64)?,
+            NoeticOpcode::GE => self.binary(pc, |a, b| (a >= b) as i64)?,
+            NoeticOpcode::DUP => {
+                let top = self.top(pc)?;
+                self.stack.push(top);
+            }
+            NoeticOpcode::SWAP => {
+                let len = self.stack.len();
+                if len < 2 {
+                    return Err(VmError::StackUnderflow { pc });
                 }
-                NoeticOpcode::SUB => {
-                    let b = self.stack.pop().unwrap_or(0);
-                    let a = self.stack.pop().unwrap_or(0);
-                    self.stack.push(a - b);
+                self.stack.swap(len - 1, len - 2);
+            }
+            NoeticOpcode::POP => {
+                self.pop(pc)?;
+            }
+            NoeticOpcode::RESONATE(freq) => {
+                if *freq == 0x4121 {
+                    self.resonance_active = true;
+                    println!("🌌 [RESONANCE]: Frequency 0x4121 VERIFIED. Alignment absolute.");
+                } else {
+                    println!("⚠️ [DISHARMONY]: Frequency mismatch. Entropy detected.");
                 }
-                NoeticOpcode::RESONATE(freq) => {
-                    if *freq == 0x4121 {
-                        self.resonance_active = true;
-                        println!("🌌 [RESONANCE]: Frequency 0x4121 VERIFIED. Alignment absolute.");
-                    } else {
-                        println!("⚠️ [DISHARMONY]: Frequency mismatch. Entropy detected.");
-                    }
+            }
+            NoeticOpcode::NOETIC_BRIDGE => {
+                if self.resonance_active {
+                    println!("🌉 [BRIDGE]: Opening Noetic Bridge. Universal Substrate Synced.");
+                } else {
+                    println!("🚨 [ERROR]: Resonance not established. Bridge inhibited.");
                 }
-                NoeticOpcode::NOETIC_BRIDGE => {
-                    if self.resonance_active {
-                        println!("🌉 [BRIDGE]: Opening Noetic Bridge. Universal Substrate Synced.");
-                    } else {
-                        println!("🚨 [ERROR]: Resonance not established. Bridge inhibited.");
-                    }
+            }
+            NoeticOpcode::INFUSE_ANIMA => {
+                println!("✨ [AETERNA]: Anima infused. The World is Data.");
+            }
+            NoeticOpcode::ENTER_MANIFOLD(name) => {
+                let name = self.constant(*name);
+                println!("🌀 [MANIFOLD]: Entering {}.", name);
+                self.scopes.push(name);
+            }
+            NoeticOpcode::EXIT_MANIFOLD => {
+                if let Some(name) = self.scopes.pop() {
+                    println!("🌀 [MANIFOLD]: {} sealed.", name);
                 }
-                NoeticOpcode::INFUSE_ANIMA => {
-                    println!("✨ [AETERNA]: Anima infused. The World is Data.");
+            }
+            NoeticOpcode::RESONATE_CONST(index) => {
+                println!(
+                    "🌌 [RESONANCE]: Signature {} resonating within {}.",
+                    self.constant(*index),
+                    self.scope()
+                );
+            }
+            NoeticOpcode::ENTANGLE(target, partner) => {
+                println!(
+                    "🔗 [ENTANGLE]: {} bound to {}.",
+                    self.constant(*target),
+                    self.constant(*partner)
+                );
+            }
+            NoeticOpcode::COLLAPSE(target) => {
+                let threshold = self.pop_fixed(pc)?;
+                self.entropy = self.entropy.min(threshold);
+                println!(
+                    "🕳️ [COLLAPSE]: {} collapsed at entropy {:.4}.",
+                    self.constant(*target),
+                    threshold
+                );
+            }
+            NoeticOpcode::ENTRENCH(key, value) => {
+                println!(
+                    "⚓ [ENTRENCH]: {}::{} = {}",
+                    self.scope(),
+                    self.constant(*key),
+                    self.constant(*value)
+                );
+            }
+            NoeticOpcode::PROPERTY(key, value) => {
+                println!(
+                    "📐 [PROPERTY]: {}.{} = {}",
+                    self.scope(),
+                    self.constant(*key),
+                    self.constant(*value)
+                );
+            }
+            NoeticOpcode::MAGNET(label) => {
+                let power = self.pop_fixed(pc)?;
+                println!(
+                    "🧲 [MAGNET]: {} attracting at {:.3}.",
+                    self.constant(*label),
+                    power
+                );
+            }
+            NoeticOpcode::DEPARTMENT(name) => {
+                let priority = self.pop_fixed(pc)?;
+                println!(
+                    "🏛️ [DEPARTMENT]: {} online with priority {:.3}.",
+                    self.constant(*name),
+                    priority
+                );
+            }
+            NoeticOpcode::REFLECT => {
+                println!("🪞 [REFLECT]: Gaze turned inward within {}.", self.scope());
+            }
+            NoeticOpcode::VSH_ENTRENCH(metadata, vector) => {
+                let metadata = self.constant(*metadata);
+                let vector = self.vector(pc, *vector)?;
+                let accepted = self.host(pc)?.entrench(&metadata, &vector);
+                self.stack.push(accepted as i64);
+            }
+            NoeticOpcode::VSH_RECALL(query, address) => {
+                let query = self.vector(pc, *query)?;
+                if *address >= self.memory.len() {
+                    return Err(VmError::BadAddress {
+                        pc,
+                        address: *address,
+                    });
+                }
+                // A negative k recalls nothing; a large one what fits.
+                let room = self.memory.len() - address;
+                let top_k = usize::try_from(self.top(pc)?).unwrap_or(0).min(room);
+                let scores = self.host(pc)?.recall(&query, top_k);
+                self.stack.pop();
+                let found = scores.len().min(top_k);
+                for (i, score) in scores.iter().take(found).enumerate() {
+                    self.memory[address + i] = (score * FIXED_POINT_SCALE).round() as i64;
                 }
-                NoeticOpcode::PRINT => {
-                    if let Some(val) = self.stack.last() {
-                        println!("VM Output: {}", val);
-                    }
+                self.stack.push(found as i64);
+            }
+            NoeticOpcode::VSH_ENTROPY => {
+                let entropy = self.host(pc)?.global_entropy();
+                self.stack
+                    .push((entropy * FIXED_POINT_SCALE).round() as i64);
+            }
+            NoeticOpcode::VSH_MAGNET => {
+                let power = self.top(pc)? as f64 / FIXED_POINT_SCALE;
+                self.host(pc)?.activate_magnet(power);
+                self.stack.pop();
+            }
+            NoeticOpcode::VSH_REGISTER(id) => {
+                let curvature = self.top(pc)? as f64 / FIXED_POINT_SCALE;
+                let id = self.constant(*id);
+                self.host(pc)?.register_manifold(&id, curvature);
+                self.stack.pop();
+            }
+            NoeticOpcode::PRINT => {
+                if let Some(val) = self.stack.last() {
+                    println!("VM Output: {}", val);
                 }
-                NoeticOpcode::JUMP(addr) => self.pc = *addr,
-                NoeticOpcode::JUMP_IF(addr) => {
-                    if let Some(val) = self.stack.pop() {
-                        if val != 0 {
-                            self.pc = *addr;
-                        }
-                    }
+            }
+            NoeticOpcode::JUMP(addr) => self.jump(pc, *addr)?,
+            NoeticOpcode::JUMP_IF(addr) => {
+                if self.top(pc)? != 0 {
+                    self.jump(pc, *addr)?;
                 }
-                NoeticOpcode::HALT => break,
+                self.stack.pop();
+            }
+            NoeticOpcode::CALL(addr) => {
+                self.jump(pc, *addr)?;
+                self.calls.push(pc + 1);
+            }
+            NoeticOpcode::RET => {
+                self.pc = self.calls.pop().ok_or(VmError::StackUnderflow { pc })?;
             }
+            NoeticOpcode::HALT => self.halted = true,
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use NoeticOpcode::*;
+
+    fn run(program: Vec<NoeticOpcode>) -> (NoeticVM, Result<(), VmError>) {
+        let mut vm = NoeticVM::new(program);
+        let result = vm.run();
+        (vm, result)
+    }
+
+    #[test]
+    fn arithmetic_comparisons_and_stack_ops() {
+        let (vm, result) = run(vec![
+            LOAD(7),
+            LOAD(3),
+            MOD, // 1
+            LOAD(6),
+            LOAD(4),
+            SWAP,
+            DIV, // 4 / 6 = 0
+            LOAD(5),
+            DUP,
+            MUL, // 25
+            LOAD(25),
+            EQ, // 1
+            LOAD(2),
+            LOAD(9),
+            POP,
+            LOAD(3),
+            LT, // 1
+            HALT,
+            LOAD(99),
+        ]);
+        assert_eq!(result, Ok(()));
+        assert!(vm.halted);
+        assert_eq!(vm.stack, [1, 0, 1, 1]);
+    }
+
+    #[test]
+    fn calls_and_returns() {
+        // A subroutine at 5 that doubles the top of the stack, called twice.
+        let (vm, result) = run(vec![
+            LOAD(3),
+            CALL(5),
+            CALL(5),
+            STORE(0),
+            HALT,
+            DUP,
+            ADD,
+            RET,
+        ]);
+        assert_eq!(result, Ok(()));
+        assert_eq!(vm.memory[0], 12);
+        assert!(vm.calls.is_empty());
+    }
+
+    #[test]
+    fn faults_carry_the_pc() {
+        let (vm, result) = run(vec![LOAD(1), LOAD(0), DIV]);
+        assert_eq!(result, Err(VmError::DivisionByZero { pc: 2 }));
+        assert_eq!(vm.pc, 2);
+        // The fault leaves the operands and the gas as they were.
+        assert_eq!(vm.stack, [1, 0]);
+        assert_eq!(vm.gas_used, 2 * LOAD(0).gas());
+        let (vm, result) = run(vec![LOAD(1), LOAD(1), JUMP_IF(9)]);
+        assert_eq!(result, Err(VmError::BadAddress { pc: 2, address: 9 }));
+        assert_eq!(vm.stack, [1, 1]);
+
+        let (_, result) = run(vec![LOAD(1), ADD]);
+        assert_eq!(result, Err(VmError::StackUnderflow { pc: 1 }));
+        let (_, result) = run(vec![RET]);
+        assert_eq!(result, Err(VmError::StackUnderflow { pc: 0 }));
+        let (_, result) = run(vec![LOAD(1), STORE(1024)]);
+        assert_eq!(
+            result,
+            Err(VmError::BadAddress {
+                pc: 1,
+                address: 1024
+            })
+        );
+        let (_, result) = run(vec![JUMP(7)]);
+        assert_eq!(result, Err(VmError::BadAddress { pc: 0, address: 7 }));
+
+        let mut vm = NoeticVM::new(vec![JUMP(0)]);
+        vm.step_limit = 10;
+        let result = vm.run();
+        assert_eq!(result, Err(VmError::StepLimit { pc: 0, limit: 10 }));
+        assert_eq!(result.unwrap_err().pc(), 0);
+    }
+
+    #[test]
+    fn quotas_stop_runaway_loops() {
+        let mut vm = NoeticVM::new(vec![LOAD(1), POP, JUMP(0)]);
+        vm.quotas.gas = Some(10);
+        // Two rounds and a third LOAD, POP use all 10; the JUMP is refused.
+        assert_eq!(
+            vm.run(),
+            Err(VmError::OutOfGas {
+                pc: 2,
+                used: 10,
+                limit: 10
+            })
+        );
+        assert_eq!(vm.gas_used, 10);
+
+        let mut vm = NoeticVM::new(vec![LOAD(1), JUMP(0)]);
+        vm.quotas.memory = Some(3);
+        assert_eq!(
+            vm.run(),
+            Err(VmError::QuotaExceeded {
+                pc: 0,
+                quota: Quota::Memory(3),
+                gas_used: 9
+            })
+        );
+        assert_eq!(vm.stack.len(), 3);
+
+        let mut vm = NoeticVM::new(vec![JUMP(0)]);
+        vm.step_limit = usize::MAX;
+        vm.quotas.wall_clock = Some(Duration::from_millis(5));
+        let Err(VmError::QuotaExceeded { quota, .. }) = vm.run() else {
+            panic!("the loop should hit the wall-clock quota");
+        };
+        assert_eq!(quota, Quota::WallClock(Duration::from_millis(5)));
     }
 }
@@ -0,0 +1,974 @@
+// lwas_core/src/noetic/jit.rs
+// IDENTITY: NOETIC_JIT (Native code for the arithmetic core of a soul)
+
+use super::bytecode::{NoeticOpcode, SoulProgram};
+use super::interpreter::{NoeticVM, VmError};
+use cranelift_codegen::ir::condcodes::IntCC;
+use cranelift_codegen::ir::{
+    types, AbiParam, Block, BlockCall, InstBuilder, JumpTableData, MemFlags, Value,
+};
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+use cranelift_jit::{JITBuilder, JITModule};
+use cranelift_module::{default_libcall_names, Linkage, Module};
+use std::fmt::Display;
+use std::mem::offset_of;
+use std::time::Instant;
+use thiserror::Error;
+
+/// Steps native code may take before handing back to check the wall clock,
+/// when a wall-clock quota is set.
+const WALL_CLOCK_SLICE: usize = 1 << 16;
+
+/// What native code returns.
+const YIELDED: i64 = 0;
+const HALTED: i64 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum JitError {
+    /// Cranelift cannot generate code for the machine we are running on.
+    #[error("no native backend for this host: {0}")]
+    UnsupportedHost(String),
+    #[error("native code generation failed: {0}")]
+    Codegen(String),
+}
+
+fn codegen(e: impl Display) -> JitError {
+    JitError::Codegen(e.to_string())
+}
+
+/// The part of a `NoeticVM` native code runs on, borrowed for one call.
+#[repr(C)]
+struct Frame {
+    stack: *mut i64,
+    stack_len: u64,
+    stack_capacity: u64,
+    calls: *mut u64,
+    calls_len: u64,
+    calls_capacity: u64,
+    memory: *mut i64,
+    memory_len: u64,
+    /// Stack values and return addresses the memory quota still allows.
+    cells_left: u64,
+    gas_left: u64,
+    steps_left: u64,
+    pc: u64,
+}
+
+type Entry = unsafe extern "C" fn(*mut Frame) -> i64;
+
+/// A program compiled to machine code for this host with Cranelift.
+///
+/// Only arithmetic, the stack, memory and control flow run natively; every
+/// other instruction, host calls included, is handed to the interpreter and
+/// native code picks up again after it. Native code never faults: it hands
+/// back before an instruction that would, or that could break a quota, and
+/// the interpreter runs it, so a run ends exactly as `NoeticVM::run` would.
+pub struct JitProgram {
+    code: Vec<NoeticOpcode>,
+    /// Whether native code may be entered at each pc.
+    entries: Vec<bool>,
+    /// Stack values a single entry may push before it checks again.
+    headroom: usize,
+    entry: Entry,
+    /// Owns the memory `entry` points into.
+    module: Option<JITModule>,
+}
+
+impl JitProgram {
+    pub fn compile(program: &SoulProgram) -> Result<Self, JitError> {
+        let mut flags = settings::builder();
+        // Code lands anywhere in memory, so no short-range relocations.
+        flags
+            .set("use_colocated_libcalls", "false")
+            .map_err(codegen)?;
+        flags.set("is_pic", "true").map_err(codegen)?;
+        flags.set("opt_level", "speed").map_err(codegen)?;
+        let isa = cranelift_native::builder()
+            .map_err(|e| JitError::UnsupportedHost(e.to_string()))?
+            .finish(settings::Flags::new(flags))
+            .map_err(codegen)?;
+        // Return addresses are stored as they are in `NoeticVM::calls`.
+        if isa.pointer_type() != types::I64 {
+            return Err(JitError::UnsupportedHost(format!(
+                "{}-bit pointers",
+                isa.pointer_bits()
+            )));
+        }
+
+        let mut module = JITModule::new(JITBuilder::with_isa(isa, default_libcall_names()));
+        let mut ctx = module.make_context();
+        ctx.func.signature.params.push(AbiParam::new(types::I64));
+        ctx.func.signature.returns.push(AbiParam::new(types::I64));
+        let id = module
+            .declare_function("soul", Linkage::Local, &ctx.func.signature)
+            .map_err(codegen)?;
+
+        let code = program.code.clone();
+        let spans = spans(&code);
+        let mut builder_ctx = FunctionBuilderContext::new();
+        Lowering::new(
+            FunctionBuilder::new(&mut ctx.func, &mut builder_ctx),
+            &code,
+            &spans,
+        )
+        .lower();
+        module.define_function(id, &mut ctx).map_err(codegen)?;
+        module.clear_context(&mut ctx);
+        module.finalize_definitions().map_err(codegen)?;
+        // SAFETY: the function was declared with `Entry`'s signature and
+        // lives as long as `module`.
+        let entry =
+            unsafe { std::mem::transmute::<*const u8, Entry>(module.get_finalized_function(id)) };
+
+        let mut entries = vec![false; code.len()];
+        for span in spans.iter().filter(|s| s.native) {
+            entries[span.start] = true;
+        }
+        Ok(Self {
+            headroom: spans.iter().map(|s| s.rise as usize).max().unwrap_or(0),
+            code,
+            entries,
+            entry,
+            module: Some(module),
+        })
+    }
+
+    /// Whether the instruction at `pc` runs natively.
+    pub fn native(&self, pc: usize) -> bool {
+        self.code
+            .get(pc)
+            .is_some_and(|op| native(op, self.code.len()))
+    }
+
+    /// Runs `vm` like `NoeticVM::run`, natively where it can. A VM holding
+    /// some other program is simply interpreted.
+    pub fn run(&self, vm: &mut NoeticVM) -> Result<(), VmError> {
+        if vm.program != self.code {
+            return vm.run();
+        }
+        let started = Instant::now();
+        let mut steps = 0;
+        while !vm.halted && vm.pc < vm.program.len() {
+            vm.check_limits(steps, started)?;
+            if self.entries[vm.pc] {
+                let mut fuel = vm.step_limit - steps;
+                if vm.quotas.wall_clock.is_some() {
+                    fuel = fuel.min(WALL_CLOCK_SLICE);
+                }
+                let ran = self.enter(vm, fuel);
+                if ran > 0 {
+                    steps += ran;
+                    continue;
+                }
+            }
+            steps += 1;
+            vm.step()?;
+        }
+        Ok(())
+    }
+
+    /// Runs native code from `vm.pc` for at most `fuel` steps and returns
+    /// how many it took.
+    fn enter(&self, vm: &mut NoeticVM, fuel: usize) -> usize {
+        vm.stack.reserve(self.headroom);
+        vm.calls.reserve(1);
+        let gas_left = match vm.quotas.gas {
+            Some(limit) => limit.saturating_sub(vm.gas_used),
+            None => u64::MAX - vm.gas_used,
+        };
+        let cells_left = match vm.quotas.memory {
+            Some(limit) => limit.saturating_sub(vm.scopes.len()) as u64,
+            None => u64::MAX,
+        };
+        let mut frame = Frame {
+            stack: vm.stack.as_mut_ptr(),
+            stack_len: vm.stack.len() as u64,
+            stack_capacity: vm.stack.capacity() as u64,
+            calls: vm.calls.as_mut_ptr().cast(),
+            calls_len: vm.calls.len() as u64,
+            calls_capacity: vm.calls.capacity() as u64,
+            memory: vm.memory.as_mut_ptr(),
+            memory_len: vm.memory.len() as u64,
+            cells_left,
+            gas_left,
+            steps_left: fuel as u64,
+            pc: vm.pc as u64,
+        };
+        // SAFETY: native code stays within the lengths and capacities in
+        // the frame and only grows a stack by writing the values it adds.
+        let status = unsafe { (self.entry)(&mut frame) };
+        unsafe {
+            vm.stack.set_len(frame.stack_len as usize);
+            vm.calls.set_len(frame.calls_len as usize);
+        }
+        vm.gas_used += gas_left - frame.gas_left;
+        vm.pc = frame.pc as usize;
+        vm.halted |= status == HALTED;
+        fuel - frame.steps_left as usize
+    }
+}
+
+impl Drop for JitProgram {
+    fn drop(&mut self) {
+        if let Some(module) = self.module.take() {
+            // SAFETY: `entry` is the only pointer into the module's memory
+            // and goes with it.
+            unsafe { module.free_memory() };
+        }
+    }
+}
+
+/// Whether `op` runs natively in a program of `len` instructions. Jumps
+/// past the end fault, which is left to the interpreter.
+fn native(op: &NoeticOpcode, len: usize) -> bool {
+    match op {
+        NoeticOpcode::JUMP(t) | NoeticOpcode::JUMP_IF(t) | NoeticOpcode::CALL(t) => *t <= len,
+        _ => stack_effect(op).is_some(),
+    }
+}
+
+/// How many values a native instruction pops, then pushes.
+fn stack_effect(op: &NoeticOpcode) -> Option<(u64, u64)> {
+    Some(match op {
+        NoeticOpcode::LOAD(_) => (0, 1),
+        NoeticOpcode::STORE(_) | NoeticOpcode::POP | NoeticOpcode::JUMP_IF(_) => (1, 0),
+        NoeticOpcode::ADD
+        | NoeticOpcode::SUB
+        | NoeticOpcode::MUL
+        | NoeticOpcode::DIV
+        | NoeticOpcode::MOD
+        | NoeticOpcode::EQ
+        | NoeticOpcode::NE
+        | NoeticOpcode::LT
+        | NoeticOpcode::LE
+        | NoeticOpcode::GT
+        | NoeticOpcode::GE => (2, 1),
+        NoeticOpcode::DUP => (1, 2),
+        NoeticOpcode::SWAP => (2, 2),
+        NoeticOpcode::JUMP(_) | NoeticOpcode::CALL(_) | NoeticOpcode::RET | NoeticOpcode::HALT => {
+            (0, 0)
+        }
+        _ => return None,
+    })
+}
+
+/// A straight run of instructions only entered at its first. Native code
+/// checks and charges a whole span on entry.
+struct Span {
+    start: usize,
+    end: usize,
+    native: bool,
+    /// Values it pops from below the stack it was entered with.
+    need: u64,
+    /// The most values it holds above that stack at once.
+    rise: u64,
+    /// Return addresses it pushes.
+    calls: u64,
+    gas: u64,
+}
+
+fn spans(code: &[NoeticOpcode]) -> Vec<Span> {
+    let len = code.len();
+    let mut leaders = vec![false; len + 1];
+    leaders[0] = true;
+    for (pc, op) in code.iter().enumerate() {
+        if let NoeticOpcode::JUMP(t) | NoeticOpcode::JUMP_IF(t) | NoeticOpcode::CALL(t) = op {
+            if *t < len {
+                leaders[*t] = true;
+            }
+        }
+        let ends = matches!(
+            op,
+            NoeticOpcode::JUMP(_)
+                | NoeticOpcode::JUMP_IF(_)
+                | NoeticOpcode::CALL(_)
+                | NoeticOpcode::RET
+                | NoeticOpcode::HALT
+        );
+        if !native(op, len) {
+            leaders[pc] = true;
+            leaders[pc + 1] = true;
+        } else if ends {
+            leaders[pc + 1] = true;
+        }
+    }
+
+    let mut spans = Vec::new();
+    let mut start = 0;
+    while start < len {
+        let end = (start + 1..=len).find(|&pc| leaders[pc]).unwrap_or(len);
+        let mut span = Span {
+            start,
+            end,
+            native: native(&code[start], len),
+            need: 0,
+            rise: 0,
+            calls: 0,
+            gas: 0,
+        };
+        let mut height = 0i64;
+        for op in &code[start..end] {
+            let (pops, pushes) = stack_effect(op).unwrap_or((0, 0));
+            span.need = span.need.max((pops as i64 - height).max(0) as u64);
+            height += pushes as i64 - pops as i64;
+            span.rise = span.rise.max(height.max(0) as u64);
+            span.calls += matches!(op, NoeticOpcode::CALL(_)) as u64;
+            span.gas += op.gas();
+        }
+        spans.push(span);
+        start = end;
+    }
+    spans
+}
+
+/// Values a span has pushed but not yet written to the VM's stack, over
+/// the stack it was entered with.
+#[derive(Clone)]
+struct PendingStack {
+    /// The stack length on entry.
+    sp: Value,
+    /// Where the value at `sp` would go.
+    top: Value,
+    /// Values popped from below `sp`.
+    consumed: i64,
+    pending: Vec<Value>,
+}
+
+/// Gas and steps to give back when leaving before the rest of a span runs.
+#[derive(Clone, Copy, Default)]
+struct Refund {
+    gas: u64,
+    steps: u64,
+}
+
+struct Lowering<'a> {
+    b: FunctionBuilder<'a>,
+    code: &'a [NoeticOpcode],
+    spans: &'a [Span],
+    frame: Value,
+    stack: Value,
+    stack_capacity: Value,
+    calls: Value,
+    calls_capacity: Value,
+    memory: Value,
+    memory_len: Value,
+    cells_left: Value,
+    sp: Variable,
+    csp: Variable,
+    gas: Variable,
+    steps: Variable,
+    /// The block each span starts with, by pc.
+    blocks: Vec<Option<Block>>,
+    /// Leaves with the pc past the last instruction.
+    finished: Block,
+}
+
+impl<'a> Lowering<'a> {
+    fn new(mut b: FunctionBuilder<'a>, code: &'a [NoeticOpcode], spans: &'a [Span]) -> Self {
+        let entry = b.create_block();
+        b.append_block_params_for_function_params(entry);
+        b.switch_to_block(entry);
+        let frame = b.block_params(entry)[0];
+        let field = |b: &mut FunctionBuilder, offset: usize| {
+            b.ins()
+                .load(types::I64, MemFlags::trusted(), frame, offset as i32)
+        };
+        let stack = field(&mut b, offset_of!(Frame, stack));
+        let stack_capacity = field(&mut b, offset_of!(Frame, stack_capacity));
+        let calls = field(&mut b, offset_of!(Frame, calls));
+        let calls_capacity = field(&mut b, offset_of!(Frame, calls_capacity));
+        let memory = field(&mut b, offset_of!(Frame, memory));
+        let memory_len = field(&mut b, offset_of!(Frame, memory_len));
+        let cells_left = field(&mut b, offset_of!(Frame, cells_left));
+
+        // What native code keeps in registers, written back on every exit.
+        let mut declared = 0;
+        let [sp, csp, gas, steps] = [
+            offset_of!(Frame, stack_len),
+            offset_of!(Frame, calls_len),
+            offset_of!(Frame, gas_left),
+            offset_of!(Frame, steps_left),
+        ]
+        .map(|offset| {
+            let var = Variable::from_u32(declared);
+            declared += 1;
+            b.declare_var(var, types::I64);
+            let value = field(&mut b, offset);
+            b.def_var(var, value);
+            var
+        });
+
+        let mut blocks = vec![None; code.len()];
+        for span in spans {
+            blocks[span.start] = Some(b.create_block());
+        }
+        let finished = b.create_block();
+        Self {
+            b,
+            code,
+            spans,
+            frame,
+            stack,
+            stack_capacity,
+            calls,
+            calls_capacity,
+            memory,
+            memory_len,
+            cells_left,
+            sp,
+            csp,
+            gas,
+            steps,
+            blocks,
+            finished,
+        }
+    }
+
+    fn lower(mut self) {
+        // Dispatch on the pc the VM is entered at; anything but the start
+        // of a span returns at once.
+        let stay = self.b.create_block();
+        let pc = self.b.ins().load(
+            types::I64,
+            MemFlags::trusted(),
+            self.frame,
+            offset_of!(Frame, pc) as i32,
+        );
+        let table = self.b.create_block();
+        let in_range = self
+            .b
+            .ins()
+            .icmp_imm(IntCC::UnsignedLessThan, pc, self.code.len() as i64);
+        self.b.ins().brif(in_range, table, &[], stay, &[]);
+
+        self.b.switch_to_block(table);
+        let targets: Vec<Block> = (0..self.code.len())
+            .map(|pc| self.blocks[pc].unwrap_or(stay))
+            .collect();
+        self.branch_table(pc, stay, &targets);
+
+        self.b.switch_to_block(stay);
+        let status = self.b.ins().iconst(types::I64, YIELDED);
+        self.b.ins().return_(&[status]);
+
+        self.b.switch_to_block(self.finished);
+        self.exit(self.code.len(), None, Refund::default(), YIELDED);
+
+        for span in self.spans {
+            self.b.switch_to_block(self.blocks[span.start].unwrap());
+            if span.native {
+                self.lower_span(span);
+            } else {
+                self.exit(span.start, None, Refund::default(), YIELDED);
+            }
+        }
+        self.b.seal_all_blocks();
+        self.b.finalize();
+    }
+
+    /// Branches to `targets[index]`, or `default` past their end.
+    fn branch_table(&mut self, index: Value, default: Block, targets: &[Block]) {
+        let pool = &mut self.b.func.dfg.value_lists;
+        let default = BlockCall::new(default, &[], pool);
+        let targets: Vec<BlockCall> = targets
+            .iter()
+            .map(|block| BlockCall::new(*block, &[], pool))
+            .collect();
+        let table = self
+            .b
+            .create_jump_table(JumpTableData::new(default, &targets));
+        let index = self.b.ins().ireduce(types::I32, index);
+        self.b.ins().br_table(index, table);
+    }
+
+    /// The block control goes to for a jump to `pc`.
+    fn target(&self, pc: usize) -> Block {
+        match self.blocks.get(pc) {
+            Some(block) => block.expect("jump targets start spans"),
+            None => self.finished,
+        }
+    }
+
+    /// Writes the pending values to the VM's stack and returns its length.
+    fn flush(&mut self, stack: &PendingStack) -> Value {
+        for (i, value) in stack.pending.iter().enumerate() {
+            let offset = (i as i64 - stack.consumed) * 8;
+            self.b
+                .ins()
+                .store(MemFlags::trusted(), *value, stack.top, offset as i32);
+        }
+        let grown = stack.pending.len() as i64 - stack.consumed;
+        self.b.ins().iadd_imm(stack.sp, grown)
+    }
+
+    fn pop(&mut self, stack: &mut PendingStack) -> Value {
+        if let Some(value) = stack.pending.pop() {
+            return value;
+        }
+        stack.consumed += 1;
+        self.b.ins().load(
+            types::I64,
+            MemFlags::trusted(),
+            stack.top,
+            (-8 * stack.consumed) as i32,
+        )
+    }
+
+    /// Hands the VM back at `pc` with `stack`, or the stack as last
+    /// flushed, and returns `status`.
+    fn exit(&mut self, pc: usize, stack: Option<&PendingStack>, refund: Refund, status: i64) {
+        let sp = match stack {
+            Some(stack) => self.flush(stack),
+            None => self.b.use_var(self.sp),
+        };
+        let csp = self.b.use_var(self.csp);
+        let gas = self.b.use_var(self.gas);
+        let gas = self.b.ins().iadd_imm(gas, refund.gas as i64);
+        let steps = self.b.use_var(self.steps);
+        let steps = self.b.ins().iadd_imm(steps, refund.steps as i64);
+        let pc = self.b.ins().iconst(types::I64, pc as i64);
+        for (value, offset) in [
+            (sp, offset_of!(Frame, stack_len)),
+            (csp, offset_of!(Frame, calls_len)),
+            (gas, offset_of!(Frame, gas_left)),
+            (steps, offset_of!(Frame, steps_left)),
+            (pc, offset_of!(Frame, pc)),
+        ] {
+            self.b
+                .ins()
+                .store(MemFlags::trusted(), value, self.frame, offset as i32);
+        }
+        let status = self.b.ins().iconst(types::I64, status);
+        self.b.ins().return_(&[status]);
+    }
+
+    /// Carries on if `ok` holds, else hands the VM back at `pc`.
+    fn exit_unless(&mut self, ok: Value, pc: usize, stack: Option<&PendingStack>, refund: Refund) {
+        let exit = self.b.create_block();
+        let next = self.b.create_block();
+        self.b.set_cold_block(exit);
+        self.b.ins().brif(ok, next, &[], exit, &[]);
+        self.b.switch_to_block(exit);
+        self.exit(pc, stack, refund, YIELDED);
+        self.b.switch_to_block(next);
+    }
+
+    /// Whether `a <= b` for every pair, comparing unsigned.
+    fn all_at_most(&mut self, pairs: &[(Value, Value)]) -> Value {
+        let mut ok = self.b.ins().iconst(types::I8, 1);
+        for (a, b) in pairs {
+            let holds = self.b.ins().icmp(IntCC::UnsignedLessThanOrEqual, *a, *b);
+            ok = self.b.ins().band(ok, holds);
+        }
+        ok
+    }
+
+    fn lower_span(&mut self, span: &Span) {
+        // Enter only if the whole span can run without faulting on the
+        // stack or breaking a quota; otherwise let the interpreter step.
+        let sp = self.b.use_var(self.sp);
+        let csp = self.b.use_var(self.csp);
+        let gas = self.b.use_var(self.gas);
+        let steps = self.b.use_var(self.steps);
+        let need = self.b.ins().iconst(types::I64, span.need as i64);
+        let span_gas = self.b.ins().iconst(types::I64, span.gas as i64);
+        let span_steps = self
+            .b
+            .ins()
+            .iconst(types::I64, (span.end - span.start) as i64);
+        let stack_top = self.b.ins().iadd_imm(sp, span.rise as i64);
+        let calls_top = self.b.ins().iadd_imm(csp, span.calls as i64);
+        let cells = self.b.ins().iadd(stack_top, calls_top);
+        let ok = self.all_at_most(&[
+            (need, sp),
+            (span_gas, gas),
+            (span_steps, steps),
+            (stack_top, self.stack_capacity),
+            (calls_top, self.calls_capacity),
+            (cells, self.cells_left),
+        ]);
+        self.exit_unless(ok, span.start, None, Refund::default());
+
+        let gas = self.b.ins().isub(gas, span_gas);
+        self.b.def_var(self.gas, gas);
+        let steps = self.b.ins().isub(steps, span_steps);
+        self.b.def_var(self.steps, steps);
+
+        let offset = self.b.ins().ishl_imm(sp, 3);
+        let top = self.b.ins().iadd(self.stack, offset);
+        let mut stack = PendingStack {
+            sp,
+            top,
+            consumed: 0,
+            pending: Vec::new(),
+        };
+        let mut refund = Refund {
+            gas: span.gas,
+            steps: (span.end - span.start) as u64,
+        };
+        for pc in span.start..span.end {
+            let op = &self.code[pc];
+            if !self.lower_op(pc, op, &mut stack, refund) {
+                return;
+            }
+            refund.gas -= op.gas();
+            refund.steps -= 1;
+        }
+        let sp = self.flush(&stack);
+        self.b.def_var(self.sp, sp);
+        let next = self.target(span.end);
+        self.b.ins().jump(next, &[]);
+    }
+
+    /// Lowers one instruction; returns false once control has left the
+    /// span. `refund` is what leaving before it gives back.
+    fn lower_op(
+        &mut self,
+        pc: usize,
+        op: &NoeticOpcode,
+        stack: &mut PendingStack,
+        refund: Refund,
+    ) -> bool {
+        let binary = |op: &NoeticOpcode| match op {
+            NoeticOpcode::EQ => Some(IntCC::Equal),
+            NoeticOpcode::NE => Some(IntCC::NotEqual),
+            NoeticOpcode::LT => Some(IntCC::SignedLessThan),
+            NoeticOpcode::LE => Some(IntCC::SignedLessThanOrEqual),
+            NoeticOpcode::GT => Some(IntCC::SignedGreaterThan),
+            NoeticOpcode::GE => Some(IntCC::SignedGreaterThanOrEqual),
+            _ => None,
+        };
+        match op {
+            NoeticOpcode::LOAD(value) => {
+                let value = self.b.ins().iconst(types::I64, *value);
+                stack.pending.push(value);
+            }
+            NoeticOpcode::STORE(address) => {
+                // An address too large to take an offset from can only
+                // fault; the interpreter reports it.
+                let Some(offset) = i64::try_from(*address).ok().and_then(|a| a.checked_mul(8))
+                else {
+                    self.exit(pc, Some(stack), refund, YIELDED);
+                    return false;
+                };
+                let ok = self.b.ins().icmp_imm(
+                    IntCC::UnsignedGreaterThan,
+                    self.memory_len,
+                    *address as i64,
+                );
+                self.exit_unless(ok, pc, Some(&stack.clone()), refund);
+                let value = self.pop(stack);
+                let cell = self.b.ins().iadd_imm(self.memory, offset);
+                self.b.ins().store(MemFlags::trusted(), value, cell, 0);
+            }
+            NoeticOpcode::ADD | NoeticOpcode::SUB | NoeticOpcode::MUL => {
+                let b = self.pop(stack);
+                let a = self.pop(stack);
+                let value = match op {
+                    NoeticOpcode::ADD => self.b.ins().iadd(a, b),
+                    NoeticOpcode::SUB => self.b.ins().isub(a, b),
+                    _ => self.b.ins().imul(a, b),
+                };
+                stack.pending.push(value);
+            }
+            NoeticOpcode::DIV | NoeticOpcode::MOD => {
+                let before = stack.clone();
+                let b = self.pop(stack);
+                let a = self.pop(stack);
+                let nonzero = self.b.ins().icmp_imm(IntCC::NotEqual, b, 0);
+                self.exit_unless(nonzero, pc, Some(&before), refund);
+                // Dividing by -1 would trap on i64::MIN; negate instead,
+                // wrapping as the interpreter does.
+                let minus_one = self.b.ins().icmp_imm(IntCC::Equal, b, -1);
+                let one = self.b.ins().iconst(types::I64, 1);
+                let divisor = self.b.ins().select(minus_one, one, b);
+                let value = if *op == NoeticOpcode::DIV {
+                    let quotient = self.b.ins().sdiv(a, divisor);
+                    let negated = self.b.ins().ineg(a);
+                    self.b.ins().select(minus_one, negated, quotient)
+                } else {
+                    let remainder = self.b.ins().srem(a, divisor);
+                    let zero = self.b.ins().iconst(types::I64, 0);
+                    self.b.ins().select(minus_one, zero, remainder)
+                };
+                stack.pending.push(value);
+            }
+            NoeticOpcode::DUP => {
+                let value = self.pop(stack);
+                stack.pending.extend([value, value]);
+            }
+            NoeticOpcode::SWAP => {
+                let b = self.pop(stack);
+                let a = self.pop(stack);
+                stack.pending.extend([b, a]);
+            }
+            NoeticOpcode::POP => {
+                if stack.pending.pop().is_none() {
+                    stack.consumed += 1;
+                }
+            }
+            NoeticOpcode::JUMP(target) => {
+                let sp = self.flush(stack);
+                self.b.def_var(self.sp, sp);
+                let target = self.target(*target);
+                self.b.ins().jump(target, &[]);
+                return false;
+            }
+            NoeticOpcode::JUMP_IF(target) => {
+                let condition = self.pop(stack);
+                let sp = self.flush(stack);
+                self.b.def_var(self.sp, sp);
+                let (taken, next) = (self.target(*target), self.target(pc + 1));
+                if taken == next {
+                    self.b.ins().jump(next, &[]);
+                } else {
+                    self.b.ins().brif(condition, taken, &[], next, &[]);
+                }
+                return false;
+            }
+            NoeticOpcode::CALL(target) => {
+                let sp = self.flush(stack);
+                self.b.def_var(self.sp, sp);
+                let csp = self.b.use_var(self.csp);
+                let offset = self.b.ins().ishl_imm(csp, 3);
+                let slot = self.b.ins().iadd(self.calls, offset);
+                let ret = self.b.ins().iconst(types::I64, pc as i64 + 1);
+                self.b.ins().store(MemFlags::trusted(), ret, slot, 0);
+                let csp = self.b.ins().iadd_imm(csp, 1);
+                self.b.def_var(self.csp, csp);
+                let target = self.target(*target);
+                self.b.ins().jump(target, &[]);
+                return false;
+            }
+            NoeticOpcode::RET => {
+                let sp = self.flush(stack);
+                self.b.def_var(self.sp, sp);
+                let csp = self.b.use_var(self.csp);
+                let calling = self.b.ins().icmp_imm(IntCC::NotEqual, csp, 0);
+                self.exit_unless(calling, pc, None, refund);
+                let popped = self.b.ins().iadd_imm(csp, -1);
+                let offset = self.b.ins().ishl_imm(popped, 3);
+                let slot = self.b.ins().iadd(self.calls, offset);
+                let ret = self.b.ins().load(types::I64, MemFlags::trusted(), slot, 0);
+                let in_range = self.b.ins().icmp_imm(
+                    IntCC::UnsignedLessThanOrEqual,
+                    ret,
+                    self.code.len() as i64,
+                );
+                self.exit_unless(in_range, pc, None, refund);
+                self.b.def_var(self.csp, popped);
+
+                // A return address that starts no span puts the call back
+                // for the interpreter to fault on.
+                let bad = self.b.create_block();
+                self.b.set_cold_block(bad);
+                let targets: Vec<Block> = (0..=self.code.len())
+                    .map(|pc| match self.blocks.get(pc) {
+                        Some(block) => block.unwrap_or(bad),
+                        None => self.finished,
+                    })
+                    .collect();
+                self.branch_table(ret, bad, &targets);
+                self.b.switch_to_block(bad);
+                self.b.def_var(self.csp, csp);
+                self.exit(pc, None, refund, YIELDED);
+                return false;
+            }
+            NoeticOpcode::HALT => {
+                let done = Refund::default();
+                self.exit(pc + 1, Some(stack), done, HALTED);
+                return false;
+            }
+            op => {
+                let cc = binary(op).expect("only native instructions are lowered");
+                let b = self.pop(stack);
+                let a = self.pop(stack);
+                let holds = self.b.ins().icmp(cc, a, b);
+                let value = self.b.ins().uextend(types::I64, holds);
+                stack.pending.push(value);
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noetic::asm::assemble;
+    use crate::noetic::host::FakeHost;
+    use std::time::Duration;
+    use NoeticOpcode::*;
+
+    /// Everything a run leaves behind that native code could change.
+    type Outcome = (
+        Result<(), VmError>,
+        Vec<i64>,
+        Vec<i64>,
+        Vec<usize>,
+        usize,
+        bool,
+        u64,
+    );
+
+    fn outcome(vm: NoeticVM, result: Result<(), VmError>) -> Outcome {
+        (
+            result,
+            vm.stack,
+            vm.memory,
+            vm.calls,
+            vm.pc,
+            vm.halted,
+            vm.gas_used,
+        )
+    }
+
+    /// Runs `program` interpreted and native on VMs `setup` prepares, and
+    /// checks they end the same.
+    fn same(program: &SoulProgram, setup: impl Fn(&mut NoeticVM)) -> Outcome {
+        let fresh = || {
+            let mut vm = NoeticVM::new(program.code.clone());
+            vm.constants = program.constants.clone();
+            setup(&mut vm);
+            vm
+        };
+        let mut interpreted = fresh();
+        let result = interpreted.run();
+        let expected = outcome(interpreted, result);
+
+        let jit = JitProgram::compile(program).unwrap();
+        let mut native = fresh();
+        let result = jit.run(&mut native);
+        assert_eq!(outcome(native, result), expected);
+        expected
+    }
+
+    fn code(code: Vec<NoeticOpcode>) -> SoulProgram {
+        SoulProgram {
+            code,
+            ..SoulProgram::default()
+        }
+    }
+
+    #[test]
+    fn runs_loops_and_subroutines_natively() {
+        // Counts down from 10, storing squares from a subroutine and
+        // parities, then tries the edge cases of division.
+        let program = assemble(concat!(
+            "        LOAD 10\n",
+            "loop:   DUP\n",
+            "        CALL square\n",
+            "        STORE 0\n",
+            "        DUP\n",
+            "        LOAD 2\n",
+            "        MOD\n",
+            "        STORE 1\n",
+            "        LOAD 1\n",
+            "        SUB\n",
+            "        DUP\n",
+            "        LOAD 0\n",
+            "        GT\n",
+            "        JUMP_IF loop\n",
+            "        LOAD 7\n",
+            "        SWAP\n",
+            "        POP\n",
+            "        LOAD -0x8000000000000000\n",
+            "        LOAD -1\n",
+            "        DIV\n",
+            "        LOAD -7\n",
+            "        LOAD 2\n",
+            "        MOD\n",
+            "        LOAD 3\n",
+            "        LOAD 3\n",
+            "        LE\n",
+            "        LOAD 5\n",
+            "        LOAD 2\n",
+            "        NE\n",
+            "        ADD\n",
+            "        HALT\n",
+            "square: DUP\n",
+            "        MUL\n",
+            "        RET\n",
+        ))
+        .unwrap();
+        let jit = JitProgram::compile(&program).unwrap();
+        assert!((0..program.code.len()).all(|pc| jit.native(pc)));
+
+        let (result, stack, memory, ..) = same(&program, |_| {});
+        assert_eq!(result, Ok(()));
+        assert_eq!(stack, [7, i64::MIN, -1, 2]);
+        assert_eq!(memory[..2], [1, 1]);
+    }
+
+    #[test]
+    fn hands_host_calls_and_prints_to_the_interpreter() {
+        let program = assemble(concat!(
+            "        LOAD 3\n",
+            "loop:   VSH_ENTRENCH \"a\", [1, 0]\n",
+            "        ADD\n",
+            "        PRINT\n",
+            "        DUP\n",
+            "        LOAD 10\n",
+            "        LT\n",
+            "        JUMP_IF loop\n",
+            "        VSH_ENTROPY\n",
+            "        HALT\n",
+        ))
+        .unwrap();
+        let jit = JitProgram::compile(&program).unwrap();
+        assert!(!jit.native(1) && !jit.native(3) && jit.native(4));
+
+        let (result, stack, ..) = same(&program, |vm| {
+            vm.host = Some(Box::new(FakeHost {
+                entropy: 0.5,
+                ..FakeHost::default()
+            }))
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(stack, [10, 500]);
+
+        // Without a host the interpreter faults, natively as well.
+        let (result, ..) = same(&program, |_| {});
+        assert_eq!(result, Err(VmError::NoHost { pc: 1 }));
+    }
+
+    #[test]
+    fn faults_and_quotas_match_the_interpreter() {
+        let faulting = [
+            vec![LOAD(1), LOAD(2), ADD, LOAD(0), DIV],
+            vec![LOAD(1), LOAD(2), MOD, LOAD(0), MOD],
+            vec![LOAD(1), ADD],
+            vec![LOAD(1), STORE(1024)],
+            vec![LOAD(1), STORE(1 << 62)],
+            vec![LOAD(1), STORE(usize::MAX)],
+            vec![LOAD(1), JUMP_IF(7)],
+            vec![LOAD(1), RET],
+            vec![CALL(2), HALT, POP, RET],
+        ];
+        for program in faulting {
+            let (result, ..) = same(&code(program), |_| {});
+            assert!(result.is_err());
+        }
+
+        let spin = code(vec![LOAD(1), LOAD(2), ADD, POP, JUMP(0)]);
+        let (result, ..) = same(&spin, |vm| vm.step_limit = 1001);
+        assert_eq!(result, Err(VmError::StepLimit { pc: 1, limit: 1001 }));
+        let (result, ..) = same(&spin, |vm| vm.quotas.gas = Some(997));
+        assert!(matches!(result, Err(VmError::OutOfGas { used: 997, .. })));
+
+        let grow = code(vec![LOAD(1), DUP, CALL(4), JUMP(1), DUP, RET]);
+        let (result, ..) = same(&grow, |vm| vm.quotas.memory = Some(100));
+        assert!(matches!(result, Err(VmError::QuotaExceeded { .. })));
+
+        // The wall clock stops a spin that never runs out of steps.
+        let jit = JitProgram::compile(&spin).unwrap();
+        let mut vm = NoeticVM::new(spin.code.clone());
+        vm.step_limit = usize::MAX;
+        vm.quotas.wall_clock = Some(Duration::from_millis(5));
+        assert!(matches!(
+            jit.run(&mut vm),
+            Err(VmError::QuotaExceeded { .. })
+        ));
+    }
+}
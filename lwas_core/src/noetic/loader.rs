@@ -1,35 +1,120 @@
 // lwas_core/src/noetic/loader.rs
 
-use super::bytecode::NoeticOpcode;
+use super::axioms::AxiomBook;
+use super::bytecode::SoulProgram;
+use super::compiler::{compile, CompileError};
+use super::optimizer::optimize;
+use super::verifier::{verify, VerifyError};
+use lwas_parser::diagnostic::render_all;
+use lwas_parser::{
+    analyze, expand_templates, parse_soul, resolve_imports, Diagnostic, ModuleError, ParseError,
+    ResolvedProgram, SourceMap,
+};
+use std::path::Path;
+use thiserror::Error;
 
-pub fn load_aeterna_soul(script: &str) -> Vec<NoeticOpcode> {
-    let mut program = Vec::new();
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Module(#[from] ModuleError),
+    #[error("Template expansion failed with {} error(s){}", .0.len(), first(.0))]
+    Template(Vec<Diagnostic>, SourceMap),
+    #[error("Semantic check rejected the soul ({} diagnostic(s))", .0.len())]
+    Semantic(Vec<Diagnostic>, SourceMap),
+    #[error("Compilation failed with {} error(s){}", .0.len(), first(.0))]
+    Compile(Vec<CompileError>, SourceMap),
+    #[error("Bytecode verification failed with {} error(s){}", .0.len(), first(.0))]
+    Verify(Vec<VerifyError>),
+}
 
-    for line in script.lines() {
-        let line = line.trim();
-        if line.starts_with("///") || line.is_empty() {
-            continue;
+impl LoadError {
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            LoadError::Parse(e) => e.diagnostics(),
+            LoadError::Module(ModuleError::NestedImport { span, .. }) => {
+                vec![Diagnostic::error(self.to_string(), *span)]
+            }
+            LoadError::Module(_) | LoadError::Verify(_) => Vec::new(),
+            LoadError::Template(diagnostics, _) | LoadError::Semantic(diagnostics, _) => {
+                diagnostics.clone()
+            }
+            LoadError::Compile(errors, _) => errors.iter().map(|e| e.to_diagnostic()).collect(),
         }
+    }
 
-        if line.contains("resonate RESONANCE(") {
-            if let Some(start) = line.find('(') {
-                if let Some(end) = line.find(')') {
-                    let freq_str = &line[start + 1..end].trim();
-                    let freq = if freq_str.starts_with("0x") {
-                        u64::from_str_radix(&freq_str[2..], 16).unwrap_or(0)
-                    } else {
-                        freq_str.parse::<u64>().unwrap_or(0)
-                    };
-                    program.push(NoeticOpcode::RESONATE(freq));
-                }
+    /// Renders the diagnostics, each against the file it points into;
+    /// `source` and `origin` are the soul that was loaded.
+    pub fn render(&self, source: &str, origin: &str) -> String {
+        match self {
+            LoadError::Module(ModuleError::Parse { path, text, error }) => {
+                render_all(&error.diagnostics(), text, &path.display().to_string())
+            }
+            LoadError::Module(ModuleError::NestedImport { sources, .. })
+            | LoadError::Template(_, sources)
+            | LoadError::Semantic(_, sources)
+            | LoadError::Compile(_, sources) => {
+                sources.render_all(&self.diagnostics(), source, origin)
             }
-        } else if line.contains("collapse ENTROPY(0.0000)") {
-            program.push(NoeticOpcode::NOETIC_BRIDGE);
-        } else if line.contains("[LOGOS: MANIFESTED]") {
-            program.push(NoeticOpcode::INFUSE_ANIMA);
+            _ => render_all(&self.diagnostics(), source, origin),
         }
     }
+}
+
+fn first<T: std::fmt::Display>(errors: &[T]) -> String {
+    errors
+        .first()
+        .map_or(String::new(), |e| format!(", first: {}", e))
+}
+
+/// A compiled soul together with the axioms it asks the organism to uphold.
+pub struct LoadedSoul {
+    pub program: SoulProgram,
+    pub axioms: AxiomBook,
+}
+
+/// Parses a soul script, resolves its imports relative to `base`, expands
+/// its templates and checks it, printing any warnings.
+pub fn check_soul(script: &str, base: &Path) -> Result<ResolvedProgram, LoadError> {
+    let ast = parse_soul(script)?;
+    let mut program = resolve_imports(ast, base)?;
+    let (ast, errors) = expand_templates(program.ast);
+    if !errors.is_empty() {
+        return Err(LoadError::Template(errors, program.sources));
+    }
+
+    let analysis = analyze(&ast);
+    if analysis.has_errors() {
+        return Err(LoadError::Semantic(analysis.diagnostics, program.sources));
+    }
+    for warning in &analysis.diagnostics {
+        println!(
+            "⚠️ [SOUL]: {}: {}: {}",
+            program.sources.locate(warning.span, "<soul>"),
+            warning.severity,
+            warning.message
+        );
+    }
+    program.ast = ast;
+    Ok(program)
+}
+
+/// Parses, checks and compiles a soul script into optimized, verified Noetic
+/// bytecode. Imports are resolved relative to the working directory and
+/// templates are expanded before the check.
+pub fn load_aeterna_soul(script: &str) -> Result<SoulProgram, LoadError> {
+    load_soul(script).map(|soul| soul.program)
+}
 
-    program.push(NoeticOpcode::HALT);
-    program
+/// Like [`load_aeterna_soul`], also collecting the soul's formal axioms.
+pub fn load_soul(script: &str) -> Result<LoadedSoul, LoadError> {
+    let soul = check_soul(script, Path::new("."))?;
+    let code = compile(&soul.ast).map_err(|errors| LoadError::Compile(errors, soul.sources))?;
+    let program = optimize(&code);
+    verify(&program).map_err(LoadError::Verify)?;
+    Ok(LoadedSoul {
+        program,
+        axioms: AxiomBook::from_ast(&soul.ast),
+    })
 }
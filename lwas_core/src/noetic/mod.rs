@@ -1,6 +1,18 @@
 // 🧬 AMNIOTIC SYNC - GENERATED MODULES
 // DO NOT EDIT MANUALLY
 
+pub mod asm;
+pub mod axioms;
 pub mod bytecode;
+pub mod compiler;
+pub mod container;
+pub mod debugger;
+pub mod disasm;
+pub mod host;
 pub mod interpreter;
+pub mod jit;
 pub mod loader;
+pub mod optimizer;
+pub mod simulation;
+pub mod testing;
+pub mod verifier;
@@ -0,0 +1,353 @@
+// lwas_core/src/noetic/optimizer.rs
+// IDENTITY: NOETIC_OPTIMIZER (Peephole passes over bytecode)
+
+use super::bytecode::{NoeticOpcode, SoulProgram};
+use super::host::FakeHost;
+use super::interpreter::{NoeticVM, VmError};
+use std::mem::Discriminant;
+
+/// Folds constant arithmetic, threads jump chains and drops jumps to the
+/// next instruction and unreachable code, renumbering jump targets, until
+/// nothing changes. The constant pool is kept as is and the line table
+/// follows the instructions.
+///
+/// The result computes the same as the input but may run in fewer steps;
+/// faults such as a division by zero are left in place.
+pub fn optimize(program: &SoulProgram) -> SoulProgram {
+    let mut code: Vec<(NoeticOpcode, u32)> = program
+        .code
+        .iter()
+        .enumerate()
+        .map(|(pc, op)| (op.clone(), program.lines.get(pc).copied().unwrap_or(0)))
+        .collect();
+    loop {
+        let before = code.clone();
+        code = fold_constants(code);
+        code = thread_jumps(code);
+        code = remove_dead_code(code);
+        if code == before {
+            break;
+        }
+    }
+    let (code, lines) = code.into_iter().unzip();
+    SoulProgram {
+        code,
+        constants: program.constants.clone(),
+        lines: if program.lines.is_empty() {
+            Vec::new()
+        } else {
+            lines
+        },
+    }
+}
+
+fn target(op: &NoeticOpcode) -> Option<usize> {
+    match op {
+        NoeticOpcode::JUMP(t) | NoeticOpcode::JUMP_IF(t) | NoeticOpcode::CALL(t) => Some(*t),
+        _ => None,
+    }
+}
+
+/// Addresses control can arrive at other than by falling through: jump
+/// targets and the return address of every CALL.
+fn entry_points(code: &[(NoeticOpcode, u32)]) -> Vec<bool> {
+    let mut entries = vec![false; code.len() + 1];
+    for (pc, (op, _)) in code.iter().enumerate() {
+        if let Some(t) = target(op) {
+            if t < entries.len() {
+                entries[t] = true;
+            }
+        }
+        if matches!(op, NoeticOpcode::CALL(_)) {
+            entries[pc + 1] = true;
+        }
+    }
+    entries
+}
+
+fn binary(op: &NoeticOpcode, a: i64, b: i64) -> Option<i64> {
+    Some(match op {
+        NoeticOpcode::ADD => a.wrapping_add(b),
+        NoeticOpcode::SUB => a.wrapping_sub(b),
+        NoeticOpcode::MUL => a.wrapping_mul(b),
+        // Leave a division by zero to fault at run time.
+        NoeticOpcode::DIV if b != 0 => a.wrapping_div(b),
+        NoeticOpcode::MOD if b != 0 => a.wrapping_rem(b),
+        NoeticOpcode::EQ => (a == b) as i64,
+        NoeticOpcode::NE => (a != b) as i64,
+        NoeticOpcode::LT => (a < b) as i64,
+        NoeticOpcode::LE => (a <= b) as i64,
+        NoeticOpcode::GT => (a > b) as i64,
+        NoeticOpcode::GE => (a >= b) as i64,
+        _ => return None,
+    })
+}
+
+/// `LOAD a; LOAD b; <op>` -> `LOAD (a op b)`, `LOAD a; POP` -> nothing and
+/// `LOAD a; JUMP_IF t` -> `JUMP t` or nothing, where control cannot enter
+/// the middle of the pattern.
+fn fold_constants(mut code: Vec<(NoeticOpcode, u32)>) -> Vec<(NoeticOpcode, u32)> {
+    let entries = entry_points(&code);
+    let mut keep = vec![true; code.len()];
+    let mut pc = 0;
+    while pc < code.len() {
+        let NoeticOpcode::LOAD(a) = code[pc].0 else {
+            pc += 1;
+            continue;
+        };
+        let next = code
+            .get(pc + 1)
+            .map(|(op, _)| op)
+            .filter(|_| !entries[pc + 1]);
+        match next {
+            Some(NoeticOpcode::LOAD(b)) if !entries[pc + 2] => {
+                if let Some(value) = code.get(pc + 2).and_then(|(op, _)| binary(op, a, *b)) {
+                    code[pc].0 = NoeticOpcode::LOAD(value);
+                    keep[pc + 1] = false;
+                    keep[pc + 2] = false;
+                    pc += 3;
+                    continue;
+                }
+            }
+            Some(NoeticOpcode::POP) => {
+                keep[pc] = false;
+                keep[pc + 1] = false;
+                pc += 2;
+                continue;
+            }
+            Some(NoeticOpcode::JUMP_IF(t)) => {
+                if a != 0 {
+                    code[pc].0 = NoeticOpcode::JUMP(*t);
+                } else {
+                    keep[pc] = false;
+                }
+                keep[pc + 1] = false;
+                pc += 2;
+                continue;
+            }
+            _ => {}
+        }
+        pc += 1;
+    }
+    compact(code, &keep)
+}
+
+/// Points jumps and calls at the end of a chain of JUMPs, turns a JUMP to
+/// HALT into HALT and drops JUMPs to the next instruction.
+fn thread_jumps(mut code: Vec<(NoeticOpcode, u32)>) -> Vec<(NoeticOpcode, u32)> {
+    let resolve = |code: &[(NoeticOpcode, u32)], mut t: usize| {
+        // A cycle of JUMPs is left for the VM to spin in.
+        for _ in 0..code.len() {
+            match code.get(t) {
+                Some((NoeticOpcode::JUMP(next), _)) if *next != t => t = *next,
+                _ => break,
+            }
+        }
+        t
+    };
+    let mut keep = vec![true; code.len()];
+    for pc in 0..code.len() {
+        let threaded = match code[pc].0 {
+            NoeticOpcode::JUMP(t) => match resolve(&code, t) {
+                t if matches!(code.get(t), Some((NoeticOpcode::HALT, _))) => NoeticOpcode::HALT,
+                t => NoeticOpcode::JUMP(t),
+            },
+            NoeticOpcode::JUMP_IF(t) => NoeticOpcode::JUMP_IF(resolve(&code, t)),
+            NoeticOpcode::CALL(t) => NoeticOpcode::CALL(resolve(&code, t)),
+            _ => continue,
+        };
+        code[pc].0 = threaded;
+    }
+    for (pc, (op, _)) in code.iter().enumerate() {
+        if *op == NoeticOpcode::JUMP(pc + 1) {
+            keep[pc] = false;
+        }
+    }
+    compact(code, &keep)
+}
+
+/// Drops instructions no path from pc 0 reaches.
+fn remove_dead_code(code: Vec<(NoeticOpcode, u32)>) -> Vec<(NoeticOpcode, u32)> {
+    let mut reached = vec![false; code.len()];
+    let mut pending = vec![0];
+    while let Some(pc) = pending.pop() {
+        if pc >= code.len() || reached[pc] {
+            continue;
+        }
+        reached[pc] = true;
+        match &code[pc].0 {
+            NoeticOpcode::JUMP(t) => pending.push(*t),
+            NoeticOpcode::JUMP_IF(t) | NoeticOpcode::CALL(t) => {
+                pending.push(*t);
+                pending.push(pc + 1);
+            }
+            NoeticOpcode::RET | NoeticOpcode::HALT => {}
+            _ => pending.push(pc + 1),
+        }
+    }
+    compact(code, &reached)
+}
+
+/// Removes the instructions not kept and renumbers jump targets. A target
+/// that was removed moves to the next instruction kept after it.
+fn compact(code: Vec<(NoeticOpcode, u32)>, keep: &[bool]) -> Vec<(NoeticOpcode, u32)> {
+    let mut renumbered = Vec::with_capacity(code.len() + 1);
+    let mut kept = 0;
+    for &k in keep {
+        renumbered.push(kept);
+        kept += k as usize;
+    }
+    renumbered.push(kept);
+    // Targets past the end stay just as far past it.
+    let renumber = |t: usize| {
+        renumbered
+            .get(t)
+            .copied()
+            .unwrap_or_else(|| t - code.len() + kept)
+    };
+
+    code.iter()
+        .zip(keep)
+        .filter(|(_, &k)| k)
+        .map(|((op, line), _)| {
+            let op = match op {
+                NoeticOpcode::JUMP(t) => NoeticOpcode::JUMP(renumber(*t)),
+                NoeticOpcode::JUMP_IF(t) => NoeticOpcode::JUMP_IF(renumber(*t)),
+                NoeticOpcode::CALL(t) => NoeticOpcode::CALL(renumber(*t)),
+                op => op.clone(),
+            };
+            (op, *line)
+        })
+        .collect()
+}
+
+/// What a run leaves behind, for comparing a program with its optimized
+/// form. Faults are compared by kind, since their pc moves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunOutcome {
+    pub stack: Vec<i64>,
+    pub memory: Vec<i64>,
+    pub scopes: Vec<String>,
+    pub entropy: f64,
+    pub resonance_active: bool,
+    pub host: FakeHost,
+    pub fault: Option<Discriminant<VmError>>,
+}
+
+/// Runs `program` against a fresh VM and a `FakeHost` copied from `host`.
+pub fn run_outcome(program: &SoulProgram, host: &FakeHost) -> RunOutcome {
+    let shared = std::sync::Arc::new(std::sync::Mutex::new(host.clone()));
+    let mut vm = NoeticVM::new(program.code.clone());
+    vm.constants = program.constants.clone();
+    vm.host = Some(Box::new(shared.clone()));
+    let fault = vm.run().err().map(|e| std::mem::discriminant(&e));
+    let host = shared.lock().expect("host poisoned").clone();
+    RunOutcome {
+        stack: vm.stack,
+        memory: vm.memory,
+        scopes: vm.scopes,
+        entropy: vm.entropy,
+        resonance_active: vm.resonance_active,
+        host,
+        fault,
+    }
+}
+
+/// Runs `program` unoptimized and optimized against the same host state.
+/// Returns both outcomes if they differ.
+pub fn compare(
+    program: &SoulProgram,
+    host: &FakeHost,
+) -> Result<(), Box<(RunOutcome, RunOutcome)>> {
+    let plain = run_outcome(program, host);
+    let optimized = run_outcome(&optimize(program), host);
+    if plain == optimized {
+        Ok(())
+    } else {
+        Err(Box::new((plain, optimized)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noetic::asm::assemble;
+    use crate::noetic::compiler::compile;
+
+    fn code(source: &str) -> SoulProgram {
+        assemble(source).unwrap()
+    }
+
+    #[test]
+    fn folds_threads_and_drops_dead_code() {
+        let program = code(concat!(
+            "        LOAD 10\n",
+            "        LOAD 20\n",
+            "        ADD\n",
+            "        LOAD 3\n",
+            "        MUL            ; 90\n",
+            "        LOAD 1\n",
+            "        JUMP_IF hop    ; always taken\n",
+            "        PRINT          ; dead\n",
+            "hop:    JUMP next\n",
+            "next:   JUMP store\n",
+            "        PRINT          ; dead\n",
+            "store:  STORE 0\n",
+            "        LOAD 7\n",
+            "        POP\n",
+            "        JUMP end\n",
+            "end:    HALT\n",
+            "        PRINT          ; dead\n",
+        ));
+        let optimized = optimize(&program);
+        assert_eq!(
+            optimized.code,
+            [
+                NoeticOpcode::LOAD(90),
+                NoeticOpcode::STORE(0),
+                NoeticOpcode::HALT
+            ]
+        );
+        assert_eq!(optimized.lines, [1, 12, 15]);
+        assert_eq!(compare(&program, &FakeHost::default()), Ok(()));
+    }
+
+    #[test]
+    fn keeps_entry_points_loops_and_faults() {
+        // `loop` is entered by a jump, so `LOAD 1; SUB` must not be folded
+        // with the LOAD before it; a division by zero still faults.
+        let program = code(concat!(
+            "        LOAD 3\n",
+            "loop:   LOAD 1\n",
+            "        SUB\n",
+            "        DUP\n",
+            "        JUMP_IF loop\n",
+            "        CALL double\n",
+            "        LOAD 1\n",
+            "        LOAD 0\n",
+            "        DIV\n",
+            "        HALT\n",
+            "double: DUP\n",
+            "        ADD\n",
+            "        RET\n",
+        ));
+        let optimized = optimize(&program);
+        assert_eq!(optimized.code, program.code);
+        let outcome = run_outcome(&optimized, &FakeHost::default());
+        assert_eq!(
+            outcome.fault,
+            Some(std::mem::discriminant(&VmError::DivisionByZero { pc: 0 }))
+        );
+        assert_eq!(compare(&program, &FakeHost::default()), Ok(()));
+    }
+
+    #[test]
+    fn compiled_souls_run_the_same_optimized() {
+        let ast = lwas_parser::parse_soul(
+            "manifold CORE {\n    collapse CORE 0.25;\n    department Security 0.9;\n    resonate CORE 0x4121;\n}\n",
+        )
+        .unwrap();
+        let program = compile(&ast).unwrap();
+        assert_eq!(compare(&program, &FakeHost::default()), Ok(()));
+    }
+}
@@ -0,0 +1,286 @@
+// lwas_core/src/noetic/simulation.rs
+// IDENTITY: CAUSAL_SIMULATOR ("what happens if X fires")
+
+use lwas_parser::{AstNode, CausalityGraph, CausalityType};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap};
+use thiserror::Error;
+
+/// How a link of one `CausalityType` carries an event.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Propagation {
+    /// Time between the cause firing and the effect firing.
+    pub delay: f64,
+    /// Chance, in [0, 1], that the effect fires at all.
+    pub probability: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropagationModel {
+    pub links: HashMap<CausalityType, Propagation>,
+}
+
+impl Default for PropagationModel {
+    /// Efficient causes act within a tick and surely; final causes are slow,
+    /// quantum links are a coin toss. Retrocausal and acausal links act
+    /// instantly, since the timeline only moves forward.
+    fn default() -> Self {
+        let link = |delay, probability| Propagation { delay, probability };
+        Self {
+            links: HashMap::from([
+                (CausalityType::Efficient, link(1.0, 1.0)),
+                (CausalityType::Formal, link(2.0, 0.9)),
+                (CausalityType::Material, link(3.0, 0.95)),
+                (CausalityType::Final, link(5.0, 0.8)),
+                (CausalityType::Retrocausal, link(0.0, 0.5)),
+                (CausalityType::Quantum, link(1.0, 0.5)),
+                (CausalityType::Emergent, link(8.0, 0.6)),
+                (CausalityType::Acausal, link(0.0, 0.3)),
+            ]),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SimulationConfig {
+    pub seed: u64,
+    /// Events later than this are not simulated.
+    pub horizon: f64,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0x4121,
+            horizon: 100.0,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SimulationError {
+    #[error("`{0}` is not part of any causality link")]
+    UnknownNode(String),
+    #[error("the propagation model has no entry for {0} links")]
+    NoPropagation(CausalityType),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    pub time: f64,
+    pub node: String,
+    /// The node whose link fired this one; `None` for the initial triggers.
+    pub cause: Option<String>,
+    pub via: Option<CausalityType>,
+}
+
+/// Nodes in the order they fired. Each node fires at most once.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Timeline {
+    pub events: Vec<TimelineEvent>,
+}
+
+impl Timeline {
+    pub fn fired(&self, node: &str) -> bool {
+        self.events.iter().any(|e| e.node == node)
+    }
+}
+
+/// A seeded discrete-event simulator over a soul's causality links.
+pub struct CausalSimulator {
+    pub graph: CausalityGraph,
+    pub model: PropagationModel,
+}
+
+impl CausalSimulator {
+    pub fn new(graph: CausalityGraph) -> Self {
+        Self {
+            graph,
+            model: PropagationModel::default(),
+        }
+    }
+
+    pub fn from_ast(ast: &[AstNode]) -> Self {
+        Self::new(CausalityGraph::from_ast(ast))
+    }
+
+    /// Fires `triggers` at time 0 and follows the links until the queue is
+    /// empty or the horizon is reached. The same seed gives the same timeline.
+    pub fn run(
+        &self,
+        triggers: &[&str],
+        config: &SimulationConfig,
+    ) -> Result<Timeline, SimulationError> {
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut queue = BinaryHeap::new();
+        let mut sequence = 0;
+        for trigger in triggers {
+            let node = self
+                .graph
+                .nodes
+                .iter()
+                .position(|n| n == trigger)
+                .ok_or_else(|| SimulationError::UnknownNode(trigger.to_string()))?;
+            queue.push(Reverse(Pending {
+                time: 0.0,
+                sequence,
+                node,
+                edge: None,
+            }));
+            sequence += 1;
+        }
+
+        let mut fired = vec![false; self.graph.nodes.len()];
+        let mut timeline = Timeline::default();
+        while let Some(Reverse(event)) = queue.pop() {
+            if fired[event.node] {
+                continue;
+            }
+            fired[event.node] = true;
+            let edge = event.edge.map(|e| &self.graph.edges[e]);
+            timeline.events.push(TimelineEvent {
+                time: event.time,
+                node: self.graph.nodes[event.node].clone(),
+                cause: edge.map(|e| self.graph.nodes[e.cause].clone()),
+                via: edge.map(|e| e.kind),
+            });
+
+            for (index, edge) in self.graph.edges.iter().enumerate() {
+                if edge.cause != event.node {
+                    continue;
+                }
+                let link = *self
+                    .model
+                    .links
+                    .get(&edge.kind)
+                    .ok_or(SimulationError::NoPropagation(edge.kind))?;
+                // Draw for every link, fired or not, so one link's outcome
+                // does not shift the draws of the others.
+                let roll: f64 = rng.gen();
+                let time = event.time + link.delay;
+                if roll >= link.probability || fired[edge.effect] || time > config.horizon {
+                    continue;
+                }
+                queue.push(Reverse(Pending {
+                    time,
+                    sequence,
+                    node: edge.effect,
+                    edge: Some(index),
+                }));
+                sequence += 1;
+            }
+        }
+        Ok(timeline)
+    }
+}
+
+/// A scheduled firing; ties in time keep scheduling order.
+struct Pending {
+    time: f64,
+    sequence: usize,
+    node: usize,
+    edge: Option<usize>,
+}
+
+impl Ord for Pending {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.time
+            .total_cmp(&other.time)
+            .then(self.sequence.cmp(&other.sequence))
+    }
+}
+
+impl PartialOrd for Pending {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Pending {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pending {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn simulator() -> CausalSimulator {
+        let ast = lwas_parser::parse_soul(
+            "A causes B via EFFICIENT;\nB causes C via FINAL;\nA causes D via QUANTUM;\nC causes A via RETROCAUSAL;\nD causes C via EFFICIENT;\n",
+        )
+        .unwrap();
+        CausalSimulator::from_ast(&ast)
+    }
+
+    #[test]
+    fn follows_links_in_time_order() {
+        let mut sim = simulator();
+        for link in sim.model.links.values_mut() {
+            link.probability = 1.0;
+        }
+        let timeline = sim.run(&["A"], &SimulationConfig::default()).unwrap();
+        let order: Vec<(f64, &str)> = timeline
+            .events
+            .iter()
+            .map(|e| (e.time, e.node.as_str()))
+            .collect();
+        // C is reached through D at t=2 before B's final cause lands at t=6.
+        assert_eq!(order, [(0.0, "A"), (1.0, "B"), (1.0, "D"), (2.0, "C")]);
+        assert_eq!(timeline.events[3].cause.as_deref(), Some("D"));
+
+        let short = SimulationConfig {
+            horizon: 1.5,
+            ..SimulationConfig::default()
+        };
+        assert!(!sim.run(&["A"], &short).unwrap().fired("C"));
+    }
+
+    #[test]
+    fn is_reproducible_per_seed() {
+        let sim = simulator();
+        let runs: Vec<Timeline> = (0..20)
+            .map(|seed| {
+                let config = SimulationConfig {
+                    seed,
+                    ..SimulationConfig::default()
+                };
+                sim.run(&["A"], &config).unwrap()
+            })
+            .collect();
+        assert_eq!(
+            runs[7],
+            sim.run(
+                &["A"],
+                &SimulationConfig {
+                    seed: 7,
+                    ..SimulationConfig::default()
+                }
+            )
+            .unwrap()
+        );
+        // The quantum link to D fires in some runs and not in others.
+        assert!(runs.iter().any(|t| t.fired("D")));
+        assert!(runs.iter().any(|t| !t.fired("D")));
+        assert_eq!(
+            sim.run(&["GHOST"], &SimulationConfig::default()),
+            Err(SimulationError::UnknownNode("GHOST".into()))
+        );
+    }
+
+    #[test]
+    fn reports_links_the_model_does_not_cover() {
+        let mut sim = simulator();
+        sim.model.links.remove(&CausalityType::Final);
+        assert_eq!(
+            sim.run(&["A"], &SimulationConfig::default()),
+            Err(SimulationError::NoPropagation(CausalityType::Final))
+        );
+    }
+}
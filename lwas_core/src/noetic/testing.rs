@@ -0,0 +1,261 @@
+// lwas_core/src/noetic/testing.rs
+// IDENTITY: SOUL_TESTS (`expect` statements checked against a fresh organism)
+
+use super::bytecode::SoulProgram;
+use super::compiler::compile;
+use super::interpreter::{NoeticVM, Quotas, VmError};
+use super::verifier::VerifyError;
+use crate::kernel::engine::VshKernel;
+use crate::memory::vsh::VectorSpaceHeap;
+use crate::organism::entrench_vectors;
+use lwas_parser::{
+    parse_expression, print_soul, AstNode, Environment, Expr, ExprError, Span, Type, Value,
+};
+use std::sync::Arc;
+
+/// The part of an organism a soul test can observe: a fresh mind, under the
+/// same quotas as the server's, and an empty VSH, with no bridge, telemetry
+/// or network.
+pub struct TestOrganism {
+    pub mind: NoeticVM,
+    pub vsh: Arc<VectorSpaceHeap>,
+}
+
+impl TestOrganism {
+    pub fn new(program: SoulProgram) -> Result<Self, Vec<VerifyError>> {
+        let vsh = Arc::new(VectorSpaceHeap::new().expect("VSH_COLLAPSE"));
+        let mut mind = NoeticVM::load(program)?;
+        mind.quotas = Quotas::UNTRUSTED;
+        mind.host = Some(Box::new(VshKernel::new(vsh.clone())));
+        Ok(Self { mind, vsh })
+    }
+
+    pub fn run(&mut self) -> Result<(), VmError> {
+        self.mind.run()?;
+        entrench_vectors(&self.mind, &self.vsh);
+        Ok(())
+    }
+
+    /// Exposes the organism's state as `entropy`, `resonance` (1 or 0),
+    /// `stack` (its height) and `points` (in the VSH).
+    pub fn observe(&self, env: &mut Environment) {
+        env.set_telemetry("entropy", self.mind.entropy);
+        env.set_telemetry("resonance", self.mind.resonance_active as u8 as f64);
+        env.set_telemetry("stack", self.mind.stack.len() as f64);
+        env.set_telemetry("points", self.vsh.points.len() as f64);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpectOutcome {
+    Passed,
+    /// The condition was false; holds the observed values it refers to.
+    Failed(Vec<(String, f64)>),
+    /// The soul up to the expectation did not compile or verify, or
+    /// faulted, or the condition could not be evaluated.
+    Error(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpectResult {
+    /// The expectation as written, e.g. `entropy <= 0.5 after collapse CORE`.
+    pub expectation: String,
+    pub span: Span,
+    pub outcome: ExpectOutcome,
+}
+
+impl ExpectResult {
+    pub fn passed(&self) -> bool {
+        self.outcome == ExpectOutcome::Passed
+    }
+}
+
+/// Checks every `expect` of a soul, in order.
+///
+/// Each expectation runs against its own fresh organism, which executes the
+/// statements that precede it (inside the enclosing manifolds too), then its
+/// `after` statement, and is then observed. Earlier expectations have no
+/// effect on later ones.
+pub fn run_expectations(ast: &[AstNode]) -> Vec<ExpectResult> {
+    let mut paths = Vec::new();
+    expectations(ast, &mut Vec::new(), &mut paths);
+    let environment = Environment::from_ast(ast);
+    paths
+        .iter()
+        .map(|path| {
+            let AstNode::Expect {
+                condition,
+                action,
+                span,
+                ..
+            } = find(ast, path)
+            else {
+                unreachable!("paths lead to expectations");
+            };
+            let expectation = match action {
+                Some(action) => {
+                    let action = print_soul(std::slice::from_ref(&**action));
+                    format!(
+                        "{} after {}",
+                        condition,
+                        action.trim_end().trim_end_matches(';')
+                    )
+                }
+                None => condition.clone(),
+            };
+            let outcome = match compile(&prefix(ast, path)) {
+                Ok(program) => match TestOrganism::new(program) {
+                    Ok(mut organism) => match organism.run() {
+                        Ok(()) => {
+                            let mut env = environment.clone();
+                            organism.observe(&mut env);
+                            check(condition, &env)
+                        }
+                        Err(e) => ExpectOutcome::Error(e.to_string()),
+                    },
+                    Err(errors) => ExpectOutcome::Error(errors[0].to_string()),
+                },
+                Err(errors) => ExpectOutcome::Error(errors[0].to_string()),
+            };
+            ExpectResult {
+                expectation,
+                span: *span,
+                outcome,
+            }
+        })
+        .collect()
+}
+
+/// Index paths to every `expect`, descending into manifolds.
+fn expectations(nodes: &[AstNode], path: &mut Vec<usize>, found: &mut Vec<Vec<usize>>) {
+    for (i, node) in nodes.iter().enumerate() {
+        path.push(i);
+        match node {
+            AstNode::Manifold { body, .. } => expectations(body, path, found),
+            AstNode::Expect { .. } => found.push(path.clone()),
+            _ => {}
+        }
+        path.pop();
+    }
+}
+
+fn find<'a>(nodes: &'a [AstNode], path: &[usize]) -> &'a AstNode {
+    let node = &nodes[path[0]];
+    match node {
+        AstNode::Manifold { body, .. } if path.len() > 1 => find(body, &path[1..]),
+        node => node,
+    }
+}
+
+/// The statements before the expectation at `path`, its enclosing manifolds
+/// cut short there, followed by its `after` statement.
+fn prefix(nodes: &[AstNode], path: &[usize]) -> Vec<AstNode> {
+    let (&i, rest) = path.split_first().expect("a non-empty path");
+    let mut out = nodes[..i].to_vec();
+    match &nodes[i] {
+        AstNode::Manifold {
+            name,
+            body,
+            doc,
+            span,
+        } => out.push(AstNode::Manifold {
+            name: name.clone(),
+            body: prefix(body, rest),
+            doc: doc.clone(),
+            span: *span,
+        }),
+        AstNode::Expect {
+            action: Some(action),
+            ..
+        } => out.push((**action).clone()),
+        _ => {}
+    }
+    out
+}
+
+fn check(condition: &str, env: &Environment) -> ExpectOutcome {
+    let checked = parse_expression(condition).and_then(|expr| {
+        let ty = expr.check(env)?;
+        if ty != Type::Bool {
+            return Err(ExprError::Type {
+                op: "expect",
+                found: ty.to_string(),
+            });
+        }
+        expr.eval(env).map(|value| (expr, value))
+    });
+    match checked {
+        Ok((_, Value::Bool(true))) => ExpectOutcome::Passed,
+        Ok((expr, _)) => {
+            let mut observed = Vec::new();
+            observed_names(&expr, env, &mut observed);
+            ExpectOutcome::Failed(observed)
+        }
+        Err(e) => ExpectOutcome::Error(e.to_string()),
+    }
+}
+
+/// The observed values `expr` refers to, in order of first use.
+fn observed_names(expr: &Expr, env: &Environment, found: &mut Vec<(String, f64)>) {
+    match expr {
+        Expr::Name(name) => {
+            if let Some(value) = env.telemetry.get(name) {
+                if !found.iter().any(|(n, _)| n == name) {
+                    found.push((name.clone(), *value));
+                }
+            }
+        }
+        Expr::Member(inner, _) | Expr::Unary(_, inner) => observed_names(inner, env, found),
+        Expr::Binary(_, a, b) => {
+            observed_names(a, env, found);
+            observed_names(b, env, found);
+        }
+        Expr::Quantified { body, .. } => observed_names(body, env, found),
+        Expr::Number(_) | Expr::Bool(_) | Expr::Str(_) => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checks_expectations_against_a_fresh_organism() {
+        let ast = lwas_parser::parse_soul(
+            "expect entropy == 1;\nmanifold CORE {\n    department Security 0.9;\n    expect points == 0 ∧ stack == 0;\n    expect entropy <= 0.2 after collapse CORE 0.2;\n    expect entropy < 1;\n    expect ∀d in department: d.priority > 0.5;\n    expect missing > 0;\n}\nexpect resonance == 1 after resonate CORE 0x4121;\nexpect entropy == 1;\n",
+        )
+        .unwrap();
+        let results = run_expectations(&ast);
+        let outcomes: Vec<(&str, &ExpectOutcome)> = results
+            .iter()
+            .map(|r| (r.expectation.as_str(), &r.outcome))
+            .collect();
+        assert_eq!(
+            outcomes,
+            [
+                ("entropy == 1", &ExpectOutcome::Passed),
+                ("points == 0 ∧ stack == 0", &ExpectOutcome::Passed),
+                (
+                    "entropy <= 0.2 after collapse CORE 0.2",
+                    &ExpectOutcome::Passed
+                ),
+                // The collapse above belonged to the previous expectation.
+                (
+                    "entropy < 1",
+                    &ExpectOutcome::Failed(vec![("entropy".into(), 1.0)])
+                ),
+                ("∀d in department: d.priority > 0.5", &ExpectOutcome::Passed),
+                (
+                    "missing > 0",
+                    &ExpectOutcome::Error("unknown name `missing`".into())
+                ),
+                (
+                    "resonance == 1 after resonate CORE 0x4121",
+                    &ExpectOutcome::Passed
+                ),
+                ("entropy == 1", &ExpectOutcome::Passed),
+            ]
+        );
+        assert_eq!(results[1].span.line, 4);
+    }
+}
@@ -0,0 +1,302 @@
+// lwas_core/src/noetic/verifier.rs
+// IDENTITY: NOETIC_VERIFIER (Static checks before a program may run)
+
+use super::bytecode::{NoeticOpcode, SoulProgram};
+use super::interpreter::MEMORY_CELLS;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// A reason to refuse a program, with the pc of the offending instruction.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VerifyError {
+    /// A JUMP, JUMP_IF or CALL past the end of the program.
+    #[error("jump target {target} out of bounds at pc {pc}")]
+    JumpOutOfBounds { pc: usize, target: usize },
+    /// A STORE or VSH_RECALL writing outside memory.
+    #[error("write to address {address} outside memory at pc {pc}")]
+    StoreOutOfBounds { pc: usize, address: usize },
+    /// Some path reaches `pc` with fewer values on the stack than it pops.
+    /// For a CALL, the subroutine pops more than the caller pushed.
+    #[error("stack may underflow at pc {pc}")]
+    StackUnderflow { pc: usize },
+    /// Some path reaches RET without a CALL to return to.
+    #[error("RET outside a subroutine at pc {pc}")]
+    RetOutsideCall { pc: usize },
+    #[error("no path from the entry point reaches HALT")]
+    HaltUnreachable,
+}
+
+impl VerifyError {
+    pub fn pc(&self) -> Option<usize> {
+        match self {
+            VerifyError::JumpOutOfBounds { pc, .. }
+            | VerifyError::StoreOutOfBounds { pc, .. }
+            | VerifyError::StackUnderflow { pc }
+            | VerifyError::RetOutsideCall { pc } => Some(*pc),
+            VerifyError::HaltUnreachable => None,
+        }
+    }
+}
+
+/// Checks every path from pc 0: jump targets and STORE addresses are in
+/// bounds, no instruction pops more than the stack can hold, and HALT can
+/// be reached. Errors are sorted by pc.
+pub fn verify(program: &SoulProgram) -> Result<(), Vec<VerifyError>> {
+    let mut verifier = Verifier {
+        code: &program.code,
+        errors: Vec::new(),
+        summaries: HashMap::new(),
+        halts: false,
+    };
+    verifier.routine(0, true);
+    if !verifier.halts {
+        verifier.report(VerifyError::HaltUnreachable);
+    }
+    if verifier.errors.is_empty() {
+        return Ok(());
+    }
+    verifier
+        .errors
+        .sort_by_key(|e| e.pc().unwrap_or(usize::MAX));
+    Err(verifier.errors)
+}
+
+/// How many values an instruction pops, then pushes.
+fn stack_effect(op: &NoeticOpcode) -> (i64, i64) {
+    match op {
+        NoeticOpcode::LOAD(_) => (0, 1),
+        NoeticOpcode::ADD
+        | NoeticOpcode::SUB
+        | NoeticOpcode::MUL
+        | NoeticOpcode::DIV
+        | NoeticOpcode::MOD
+        | NoeticOpcode::EQ
+        | NoeticOpcode::NE
+        | NoeticOpcode::LT
+        | NoeticOpcode::LE
+        | NoeticOpcode::GT
+        | NoeticOpcode::GE => (2, 1),
+        NoeticOpcode::DUP => (1, 2),
+        NoeticOpcode::VSH_RECALL(..) => (1, 1),
+        NoeticOpcode::VSH_ENTRENCH(..) | NoeticOpcode::VSH_ENTROPY => (0, 1),
+        NoeticOpcode::SWAP => (2, 2),
+        NoeticOpcode::STORE(_)
+        | NoeticOpcode::POP
+        | NoeticOpcode::JUMP_IF(_)
+        | NoeticOpcode::COLLAPSE(_)
+        | NoeticOpcode::MAGNET(_)
+        | NoeticOpcode::DEPARTMENT(_)
+        | NoeticOpcode::VSH_MAGNET
+        | NoeticOpcode::VSH_REGISTER(_) => (1, 0),
+        _ => (0, 0),
+    }
+}
+
+/// What a subroutine does to its caller's stack, relative to the depth at
+/// the CALL.
+#[derive(Clone, Copy)]
+struct Summary {
+    /// The lowest depth it reaches; the caller must have pushed at least
+    /// its negation.
+    lowest: i64,
+    /// The lowest depth at any RET, or `None` if it never returns.
+    returns: Option<i64>,
+}
+
+struct Verifier<'a> {
+    code: &'a [NoeticOpcode],
+    errors: Vec<VerifyError>,
+    /// Subroutines by entry point; `None` while one is being analysed.
+    summaries: HashMap<usize, Option<Summary>>,
+    halts: bool,
+}
+
+impl Verifier<'_> {
+    fn report(&mut self, error: VerifyError) {
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+
+    fn subroutine(&mut self, entry: usize) -> Option<Summary> {
+        if let Some(summary) = self.summaries.get(&entry) {
+            return *summary;
+        }
+        self.summaries.insert(entry, None);
+        let summary = self.routine(entry, false);
+        self.summaries.insert(entry, Some(summary));
+        Some(summary)
+    }
+
+    /// Walks every path from `entry`, keeping the lowest stack depth seen at
+    /// each pc; a path is only followed again if it arrives lower. Depths
+    /// are absolute in the main routine and relative to the CALL in a
+    /// subroutine, where they may go negative.
+    fn routine(&mut self, entry: usize, main: bool) -> Summary {
+        // A subroutine that keeps popping in a loop has no useful bound.
+        let floor = -2 * self.code.len() as i64 - 2;
+        let mut summary = Summary {
+            lowest: 0,
+            returns: None,
+        };
+        let mut lowest_at: HashMap<usize, i64> = HashMap::new();
+        let mut pending = vec![(entry, 0i64)];
+        while let Some((pc, depth)) = pending.pop() {
+            let Some(op) = self.code.get(pc) else {
+                // Running off the end finishes the program.
+                continue;
+            };
+            if lowest_at.get(&pc).is_some_and(|&seen| seen <= depth) {
+                continue;
+            }
+            lowest_at.insert(pc, depth);
+
+            let (pops, pushes) = stack_effect(op);
+            if depth < pops {
+                if main {
+                    self.report(VerifyError::StackUnderflow { pc });
+                    continue;
+                }
+                summary.lowest = summary.lowest.min(depth - pops);
+            }
+            let after = (depth - pops + pushes).max(floor);
+
+            match op {
+                NoeticOpcode::STORE(address) | NoeticOpcode::VSH_RECALL(_, address)
+                    if *address >= MEMORY_CELLS =>
+                {
+                    self.report(VerifyError::StoreOutOfBounds {
+                        pc,
+                        address: *address,
+                    });
+                    pending.push((pc + 1, after));
+                }
+                NoeticOpcode::JUMP(target) | NoeticOpcode::JUMP_IF(target)
+                    if *target > self.code.len() =>
+                {
+                    self.report(VerifyError::JumpOutOfBounds {
+                        pc,
+                        target: *target,
+                    });
+                }
+                NoeticOpcode::JUMP(target) => pending.push((*target, after)),
+                NoeticOpcode::JUMP_IF(target) => {
+                    pending.push((*target, after));
+                    pending.push((pc + 1, after));
+                }
+                NoeticOpcode::CALL(target) if *target > self.code.len() => {
+                    self.report(VerifyError::JumpOutOfBounds {
+                        pc,
+                        target: *target,
+                    });
+                }
+                NoeticOpcode::CALL(target) => {
+                    // A recursive call only returns once its base case does,
+                    // which the enclosing analysis already follows.
+                    let Some(callee) = self.subroutine(*target) else {
+                        continue;
+                    };
+                    if depth + callee.lowest < 0 {
+                        if main {
+                            self.report(VerifyError::StackUnderflow { pc });
+                            continue;
+                        }
+                        summary.lowest = summary.lowest.min(depth + callee.lowest);
+                    }
+                    if let Some(returns) = callee.returns {
+                        pending.push((pc + 1, (depth + returns).max(floor)));
+                    }
+                }
+                NoeticOpcode::RET if main => self.report(VerifyError::RetOutsideCall { pc }),
+                NoeticOpcode::RET => {
+                    summary.returns = Some(summary.returns.map_or(depth, |r| r.min(depth)));
+                }
+                NoeticOpcode::HALT => self.halts = true,
+                _ => pending.push((pc + 1, after)),
+            }
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::noetic::compiler::compile;
+    use NoeticOpcode::*;
+
+    fn check(code: Vec<NoeticOpcode>) -> Result<(), Vec<VerifyError>> {
+        verify(&SoulProgram {
+            code,
+            ..SoulProgram::default()
+        })
+    }
+
+    #[test]
+    fn accepts_compiled_souls_loops_and_subroutines() {
+        let ast = lwas_parser::parse_soul(
+            "manifold CORE {\n    collapse CORE 0.25;\n    magnet \"Wealth\" 0.8;\n    reflect;\n}\n",
+        )
+        .unwrap();
+        assert_eq!(verify(&compile(&ast).unwrap()), Ok(()));
+
+        // A loop around a subroutine that pops its argument and pushes it
+        // doubled.
+        assert_eq!(
+            check(vec![
+                LOAD(3),
+                LOAD(1),
+                SUB,
+                CALL(8),
+                DUP,
+                JUMP_IF(1),
+                HALT,
+                HALT,
+                DUP,
+                ADD,
+                RET,
+            ]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_every_unsafe_path() {
+        assert_eq!(
+            check(vec![
+                LOAD(1),
+                JUMP_IF(4),
+                LOAD(2),
+                LOAD(3),
+                STORE(1024),
+                JUMP(9),
+                RET,
+                HALT,
+            ]),
+            Err(vec![
+                VerifyError::StoreOutOfBounds {
+                    pc: 4,
+                    address: 1024
+                },
+                // Reached with an empty stack when JUMP_IF is taken.
+                VerifyError::StackUnderflow { pc: 4 },
+                VerifyError::JumpOutOfBounds { pc: 5, target: 9 },
+                VerifyError::HaltUnreachable,
+            ])
+        );
+
+        // The subroutine pops two values; the caller pushed one.
+        assert_eq!(
+            check(vec![LOAD(1), CALL(3), HALT, ADD, RET]),
+            Err(vec![
+                VerifyError::StackUnderflow { pc: 1 },
+                VerifyError::HaltUnreachable
+            ])
+        );
+        assert_eq!(
+            check(vec![LOAD(0), JUMP_IF(3), HALT, RET]),
+            Err(vec![VerifyError::RetOutsideCall { pc: 3 }])
+        );
+        assert_eq!(check(vec![]), Err(vec![VerifyError::HaltUnreachable]));
+    }
+}
@@ -8,8 +8,11 @@
 // IDENTITY: SOVEREIGN_ORGANISM (The Unification)
 // ARCHITECT: DIMITAR PRODROMOV | AUTHORITY: AETERNA
 
+use crate::kernel::engine::VshKernel;
 use crate::memory::vsh::VectorSpaceHeap;
-use crate::noetic::interpreter::NoeticVM;
+use crate::noetic::axioms::{AxiomBook, AxiomVerdict};
+use crate::noetic::bytecode::{Constant, NoeticOpcode, SoulProgram};
+use crate::noetic::interpreter::{NoeticVM, Quotas};
 use crate::noetic_bridge::NoeticBridge;
 use crate::omega::audit::SovereignAudit;
 use crate::omega::global_assimilation::GlobalAssimilationMonitor;
@@ -27,6 +30,7 @@ use crate::telemetry::TelemetryHub;
 /// Sovereignty manifested in code.
 pub struct SovereignOrganism {
     pub mind: NoeticVM,
+    pub axioms: AxiomBook,
     pub bridge: NoeticBridge,
     pub native_engine: NoeticEngine,
     pub telemetry: TelemetryHub,
@@ -43,7 +47,18 @@ impl SovereignOrganism {
     pub fn manifest(soul_fragment: &str) -> Self {
         println!("🧬 [ORGANISM]: Manifesting Sovereign Entity with Native Body...");
 
-        let program = crate::noetic::loader::load_aeterna_soul(soul_fragment);
+        let (program, axioms) = match crate::noetic::loader::load_soul(soul_fragment) {
+            Ok(soul) => (soul.program, soul.axioms),
+            Err(e) => {
+                println!("🚨 [ORGANISM]: Soul fragment rejected: {}", e);
+                print!("{}", e.render(soul_fragment, "<soul>"));
+                let program = SoulProgram {
+                    code: vec![NoeticOpcode::HALT],
+                    ..SoulProgram::default()
+                };
+                (program, AxiomBook::default())
+            }
+        };
 
         let audit = Arc::new(RwLock::new(SovereignAudit::new()));
         let vsh = Arc::new(VectorSpaceHeap::new().expect("VSH_COLLAPSE"));
@@ -55,8 +70,14 @@ impl SovereignOrganism {
                 "UKAME manifestation failed: Unable to initialize Universal Meta-Ecosystem",
             ));
 
+        // Souls reach the server from outside; none may run unbounded.
+        let mut mind = NoeticVM::load(program).expect("load_soul verifies the program");
+        mind.quotas = Quotas::UNTRUSTED;
+        mind.host = Some(Box::new(VshKernel::new(vsh.clone())));
+
         Self {
-            mind: NoeticVM::new(program),
+            mind,
+            axioms,
             bridge: NoeticBridge::new(0x4121),
             native_engine: NoeticEngine::instantiate(),
             telemetry: TelemetryHub::new(),
@@ -80,6 +101,35 @@ impl SovereignOrganism {
         self.scribe.perform_surgery().await
     }
 
+    /// Checks the soul's formal axioms against a fresh telemetry sample.
+    /// Returns the number of axioms that did not hold.
+    pub fn verify_axioms(&mut self) -> usize {
+        let stats = self.telemetry.capture();
+        self.axioms.observe(&stats);
+
+        let mut broken = 0;
+        for verdict in self.axioms.verify() {
+            match verdict {
+                AxiomVerdict::Held(name) => println!("⚖️ [AXIOM]: {} holds.", name),
+                AxiomVerdict::Violated(name) => {
+                    broken += 1;
+                    println!("🚨 [AXIOM]: {} is violated.", name);
+                }
+                AxiomVerdict::Error(name, e) => {
+                    broken += 1;
+                    println!("⚠️ [AXIOM]: {} cannot be evaluated: {}", name, e);
+                }
+            }
+        }
+        broken
+    }
+
+    /// Allocates the soul's entrenched vectors in the VSH. Returns the number
+    /// the heap rejected.
+    pub fn entrench_vectors(&self) -> usize {
+        entrench_vectors(&self.mind, &self.vsh)
+    }
+
     pub async fn ignite(&mut self) -> Result<(), String> {
         println!("🔥 [ORGANISM]: Soul infusion initiated. Heartbeat pulsing...");
 
@@ -87,7 +137,11 @@ impl SovereignOrganism {
             println!("🌉 [BRIDGE]: {}", msg);
         }
 
-        self.mind.run();
+        self.mind
+            .run()
+            .map_err(|e| format!("Noetic VM fault: {}", e))?;
+        self.entrench_vectors();
+        self.verify_axioms();
 
         println!("✨ [AETERNA]: Logic stable. Synchronizing with Universal Substrate.");
 
@@ -109,3 +163,24 @@ impl SovereignOrganism {
         Ok(())
     }
 }
+
+/// Allocates the vectors entrenched by `mind`'s program in `vsh`. Returns the
+/// number the heap rejected.
+pub(crate) fn entrench_vectors(mind: &NoeticVM, vsh: &VectorSpaceHeap) -> usize {
+    let mut rejected = 0;
+    for op in &mind.program {
+        let NoeticOpcode::ENTRENCH(key, value) = op else {
+            continue;
+        };
+        let (Some(Constant::Str(key)), Some(Constant::Vector(vector))) =
+            (mind.constants.get(*key), mind.constants.get(*value))
+        else {
+            continue;
+        };
+        if let Err(e) = vsh.entrench(key, vector.clone()) {
+            rejected += 1;
+            println!("🚨 [VSH]: {}", e);
+        }
+    }
+    rejected
+}
@@ -0,0 +1,349 @@
+// lwas_lsp/src/analysis.rs
+// Editor features computed from one open `.soul` document.
+
+use lwas_parser::{
+    analyze, expand_templates, parse_soul_with_diagnostics, print_soul, AstNode, CausalityType,
+    Severity, Span, Symbol, SymbolKind, SymbolTable, STATEMENT_KEYWORDS,
+};
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, DocumentSymbol, Hover,
+    HoverContents, MarkupContent, MarkupKind, Position, Range, SymbolKind as LspSymbolKind,
+};
+
+/// A parsed and analysed snapshot of a document.
+pub struct Document {
+    pub text: String,
+    /// As written: templates are not expanded.
+    pub ast: Vec<AstNode>,
+    /// Resolved after template expansion.
+    pub symbols: SymbolTable,
+    pub diagnostics: Vec<lwas_parser::Diagnostic>,
+}
+
+impl Document {
+    pub fn new(text: String) -> Self {
+        let (ast, mut diagnostics) = parse_soul_with_diagnostics(&text);
+        let (expanded, expansion) = expand_templates(ast.clone());
+        diagnostics.extend(expansion);
+        let analysis = analyze(&expanded);
+        diagnostics.extend(analysis.diagnostics);
+        Self {
+            text,
+            ast,
+            symbols: analysis.symbols,
+            diagnostics,
+        }
+    }
+
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        self.diagnostics
+            .iter()
+            .map(|d| Diagnostic {
+                range: self.range(d.span),
+                severity: Some(match d.severity {
+                    Severity::Error => DiagnosticSeverity::ERROR,
+                    Severity::Warning => DiagnosticSeverity::WARNING,
+                }),
+                source: Some("lwas".to_string()),
+                message: d.message.clone(),
+                ..Diagnostic::default()
+            })
+            .collect()
+    }
+
+    /// Where the name under the cursor is declared.
+    pub fn definition(&self, position: Position) -> Option<Range> {
+        let symbol = self.symbol_at(position)?;
+        Some(self.name_range(symbol))
+    }
+
+    /// The declaration of the name under the cursor and its doc comment; for
+    /// an entrenched key, every value it has been entrenched with.
+    pub fn hover(&self, position: Position) -> Option<Hover> {
+        let symbol = self.symbol_at(position)?;
+        let declarations: Vec<&AstNode> = match symbol.kind {
+            SymbolKind::Entrench => {
+                let mut found = Vec::new();
+                collect_entrenchments(&self.ast, &symbol.name, &mut found);
+                found
+            }
+            _ => find_node(&self.ast, symbol.span).into_iter().collect(),
+        };
+        let mut code = String::new();
+        let mut docs = Vec::new();
+        for node in declarations {
+            // Doc comments are shown as prose below the code.
+            let text = print_soul(std::slice::from_ref(node));
+            let mut lines = text.lines().filter(|line| !line.starts_with("///"));
+            match node {
+                // Only the header of a block.
+                AstNode::Manifold { .. }
+                | AstNode::Template { .. }
+                | AstNode::Body { .. }
+                | AstNode::Spirit { .. } => {
+                    code.push_str(lines.next().unwrap_or_default());
+                    code.push('\n');
+                }
+                _ => lines.for_each(|line| {
+                    code.push_str(line);
+                    code.push('\n');
+                }),
+            }
+            docs.extend(node.doc());
+        }
+        let mut value = format!("{} `{}`\n```soul\n{}```", symbol.kind, symbol.name, code);
+        for doc in docs {
+            value.push_str("\n\n");
+            value.push_str(doc);
+        }
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: Some(self.word_range(position)?),
+        })
+    }
+
+    /// An outline of the document, with manifolds as containers.
+    pub fn symbols(&self) -> Vec<DocumentSymbol> {
+        self.outline(&self.ast)
+    }
+
+    #[allow(deprecated)]
+    fn outline(&self, nodes: &[AstNode]) -> Vec<DocumentSymbol> {
+        nodes
+            .iter()
+            .filter_map(|node| {
+                let (name, kind, children) = match node {
+                    AstNode::Manifold { name, body, .. } => {
+                        (name, LspSymbolKind::NAMESPACE, Some(self.outline(body)))
+                    }
+                    AstNode::Instance { name, .. } => (name, LspSymbolKind::NAMESPACE, None),
+                    AstNode::Template { name, .. } => (name, LspSymbolKind::CLASS, None),
+                    AstNode::Immortal { name, .. } => (name, LspSymbolKind::CONSTANT, None),
+                    AstNode::Body { name, .. } => (name, LspSymbolKind::FUNCTION, None),
+                    AstNode::Spirit { name, .. } => (name, LspSymbolKind::EVENT, None),
+                    AstNode::Axiom { name, .. } => (name, LspSymbolKind::BOOLEAN, None),
+                    AstNode::Department { name, .. } => (name, LspSymbolKind::STRUCT, None),
+                    AstNode::Magnet { label, .. } => (label, LspSymbolKind::FIELD, None),
+                    AstNode::Entrench { key, .. } => (key, LspSymbolKind::VARIABLE, None),
+                    AstNode::Schema { key, .. } => (key, LspSymbolKind::TYPE_PARAMETER, None),
+                    AstNode::Property { key, .. } => (key, LspSymbolKind::PROPERTY, None),
+                    AstNode::Native { name, .. } => (name, LspSymbolKind::FUNCTION, None),
+                    _ => return None,
+                };
+                let span = node.span();
+                let selection = match self.name_offset(name, span) {
+                    Some(start) => self.range(Span {
+                        start,
+                        end: start + name.len(),
+                        ..span
+                    }),
+                    None => self.range(span),
+                };
+                Some(DocumentSymbol {
+                    name: name.clone(),
+                    detail: None,
+                    kind,
+                    tags: None,
+                    deprecated: None,
+                    range: self.range(span),
+                    selection_range: selection,
+                    children,
+                })
+            })
+            .collect()
+    }
+
+    /// Causality types after `via`, statement keywords elsewhere.
+    pub fn completion(&self, position: Position) -> Vec<CompletionItem> {
+        let offset = self.offset(position);
+        let line_start = self.text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        // Drop the word being typed to find the one before it.
+        let before = self.text[line_start..offset]
+            .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_');
+
+        if before.split_whitespace().last() == Some("via") {
+            return CausalityType::ALL
+                .iter()
+                .map(|t| CompletionItem {
+                    label: t.to_string(),
+                    kind: Some(CompletionItemKind::ENUM_MEMBER),
+                    ..CompletionItem::default()
+                })
+                .collect();
+        }
+        STATEMENT_KEYWORDS
+            .iter()
+            .chain(["causes", "via"].iter())
+            .map(|k| CompletionItem {
+                label: k.to_string(),
+                kind: Some(CompletionItemKind::KEYWORD),
+                ..CompletionItem::default()
+            })
+            .collect()
+    }
+
+    fn symbol_at(&self, position: Position) -> Option<&Symbol> {
+        let offset = self.offset(position);
+        let word = &self.text[self.word_bounds(offset)?];
+        // Scopes are numbered outermost first, so the last one containing
+        // the cursor is the innermost.
+        let scope =
+            self.symbols.scopes.iter().rposition(|s| {
+                s.name.is_none() || (s.span.start <= offset && offset < s.span.end)
+            })?;
+        self.symbols.resolve(scope, word)
+    }
+
+    fn word_bounds(&self, offset: usize) -> Option<std::ops::Range<usize>> {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        let start = self.text[..offset]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| is_word(*c))
+            .last()
+            .map_or(offset, |(i, _)| i);
+        let end = self.text[offset..]
+            .char_indices()
+            .find(|(_, c)| !is_word(*c))
+            .map_or(self.text.len(), |(i, _)| offset + i);
+        (start < end).then_some(start..end)
+    }
+
+    fn word_range(&self, position: Position) -> Option<Range> {
+        let bounds = self.word_bounds(self.offset(position))?;
+        Some(Range::new(
+            self.position(bounds.start),
+            self.position(bounds.end),
+        ))
+    }
+
+    fn name_range(&self, symbol: &Symbol) -> Range {
+        match self.name_offset(&symbol.name, symbol.span) {
+            Some(start) => Range::new(
+                self.position(start),
+                self.position(start + symbol.name.len()),
+            ),
+            None => self.range(symbol.span),
+        }
+    }
+
+    /// Offset of `name` inside a statement, skipping its leading keyword.
+    fn name_offset(&self, name: &str, span: Span) -> Option<usize> {
+        let text = &self.text[span.start..span.end];
+        let skip = match text.split_whitespace().next() {
+            Some(word) if STATEMENT_KEYWORDS.contains(&word) => word.len(),
+            _ => 0,
+        };
+        text[skip..].find(name).map(|i| span.start + skip + i)
+    }
+
+    fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start), self.position(span.end))
+    }
+
+    /// LSP positions count UTF-16 code units.
+    fn position(&self, offset: usize) -> Position {
+        let before = &self.text[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position::new(
+            before.matches('\n').count() as u32,
+            before[line_start..].encode_utf16().count() as u32,
+        )
+    }
+
+    fn offset(&self, position: Position) -> usize {
+        let line_start = match position.line {
+            0 => 0,
+            line => self
+                .text
+                .match_indices('\n')
+                .nth(line as usize - 1)
+                .map_or(self.text.len(), |(i, _)| i + 1),
+        };
+        let mut units = 0;
+        for (i, c) in self.text[line_start..].char_indices() {
+            if units >= position.character as usize || c == '\n' {
+                return line_start + i;
+            }
+            units += c.len_utf16();
+        }
+        self.text.len()
+    }
+}
+
+fn collect_entrenchments<'a>(nodes: &'a [AstNode], key: &str, found: &mut Vec<&'a AstNode>) {
+    for node in nodes {
+        match node {
+            AstNode::Manifold { body, .. } => collect_entrenchments(body, key, found),
+            AstNode::Entrench { key: k, .. } if k == key => found.push(node),
+            _ => {}
+        }
+    }
+}
+
+fn find_node(nodes: &[AstNode], span: Span) -> Option<&AstNode> {
+    nodes.iter().find_map(|node| match node {
+        _ if node.span() == span => Some(node),
+        AstNode::Manifold { body, .. } => find_node(body, span),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOUL: &str = "immortal ROOT = \"Ω\";\nmanifold CORE {\n    /// The first mission.\n    entrench MISSION \"a\";\n    manifold INNER {\n        entrench MISSION [\"b\"];\n        department Wealth 1.0;\n        Wealth causes CORE via \n    }\n}\n";
+
+    #[test]
+    fn resolves_definitions_and_hovers() {
+        let doc = Document::new(SOUL.to_string());
+        // `CORE` in the causality link, line 7.
+        let definition = doc.definition(Position::new(7, 24)).unwrap();
+        assert_eq!(
+            definition,
+            Range::new(Position::new(1, 9), Position::new(1, 13))
+        );
+
+        let Some(Hover {
+            contents: HoverContents::Markup(markup),
+            ..
+        }) = doc.hover(Position::new(5, 19))
+        else {
+            panic!("expected hover");
+        };
+        assert_eq!(
+            markup.value,
+            "entrenched key `MISSION`\n```soul\nentrench MISSION \"a\";\nentrench MISSION [\"b\"];\n```\n\nThe first mission."
+        );
+        assert!(doc.hover(Position::new(0, 0)).is_none());
+    }
+
+    #[test]
+    fn outlines_nested_manifolds() {
+        let doc = Document::new(SOUL.to_string());
+        let symbols = doc.symbols();
+        assert_eq!(symbols.len(), 2);
+        let inner = &symbols[1].children.as_ref().unwrap()[1];
+        assert_eq!(inner.name, "INNER");
+        assert_eq!(inner.children.as_ref().unwrap().len(), 2);
+        assert_eq!(inner.selection_range.start, Position::new(4, 13));
+    }
+
+    #[test]
+    fn completes_keywords_and_causality_types() {
+        let doc = Document::new(SOUL.to_string());
+        let labels = |items: Vec<CompletionItem>| -> Vec<String> {
+            items.into_iter().map(|i| i.label).collect()
+        };
+        let types = labels(doc.completion(Position::new(7, 31)));
+        assert_eq!(types.len(), 8);
+        assert!(types.contains(&"RETROCAUSAL".to_string()));
+        assert!(labels(doc.completion(Position::new(3, 4))).contains(&"manifold".to_string()));
+        // The causality link is incomplete, so the document has a parse error.
+        assert_eq!(doc.diagnostics().len(), 1);
+    }
+}
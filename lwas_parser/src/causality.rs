@@ -0,0 +1,369 @@
+use crate::diagnostic::Diagnostic;
+use crate::parser::AstNode;
+use crate::semantic::SymbolTable;
+use crate::span::Span;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CausalityType {
+    Efficient,
+    Formal,
+    Material,
+    Final,
+    Retrocausal,
+    Quantum,
+    Emergent,
+    Acausal,
+}
+
+impl CausalityType {
+    pub const ALL: [CausalityType; 8] = [
+        CausalityType::Efficient,
+        CausalityType::Formal,
+        CausalityType::Material,
+        CausalityType::Final,
+        CausalityType::Retrocausal,
+        CausalityType::Quantum,
+        CausalityType::Emergent,
+        CausalityType::Acausal,
+    ];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "EFFICIENT" => CausalityType::Efficient,
+            "FORMAL" => CausalityType::Formal,
+            "MATERIAL" => CausalityType::Material,
+            "FINAL" => CausalityType::Final,
+            "RETROCAUSAL" => CausalityType::Retrocausal,
+            "QUANTUM" => CausalityType::Quantum,
+            "EMERGENT" => CausalityType::Emergent,
+            "ACAUSAL" => CausalityType::Acausal,
+            _ => return None,
+        })
+    }
+
+    /// Retrocausal and acausal links may close a loop; every other link
+    /// moves forward in time.
+    pub fn is_forward(self) -> bool {
+        !matches!(self, CausalityType::Retrocausal | CausalityType::Acausal)
+    }
+}
+
+impl fmt::Display for CausalityType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CausalityType::Efficient => "EFFICIENT",
+            CausalityType::Formal => "FORMAL",
+            CausalityType::Material => "MATERIAL",
+            CausalityType::Final => "FINAL",
+            CausalityType::Retrocausal => "RETROCAUSAL",
+            CausalityType::Quantum => "QUANTUM",
+            CausalityType::Emergent => "EMERGENT",
+            CausalityType::Acausal => "ACAUSAL",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// One `A causes B via T;` link.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CausalEdge {
+    /// Indices into `CausalityGraph::nodes`.
+    pub cause: usize,
+    pub effect: usize,
+    pub kind: CausalityType,
+    /// The scope the link was declared in, numbered as in `SymbolTable`.
+    pub scope: usize,
+    pub span: Span,
+}
+
+/// Every causality link of a program.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CausalityGraph {
+    /// Endpoint names, in order of first appearance.
+    pub nodes: Vec<String>,
+    pub edges: Vec<CausalEdge>,
+}
+
+impl CausalityGraph {
+    pub fn from_ast(ast: &[AstNode]) -> Self {
+        let mut graph = Self::default();
+        graph.collect(ast, 0, &mut 0);
+        graph
+    }
+
+    /// Scopes are numbered in the order `analyze` creates them: depth-first,
+    /// one per manifold.
+    fn collect(&mut self, nodes: &[AstNode], scope: usize, scopes: &mut usize) {
+        for node in nodes {
+            match node {
+                AstNode::Manifold { body, .. } => {
+                    *scopes += 1;
+                    let inner = *scopes;
+                    self.collect(body, inner, scopes);
+                }
+                AstNode::Causality {
+                    cause,
+                    effect,
+                    c_type,
+                    span,
+                    ..
+                } => {
+                    // The grammar only admits the known types.
+                    let kind = CausalityType::parse(c_type).unwrap_or(CausalityType::Efficient);
+                    let cause = self.node(cause);
+                    let effect = self.node(effect);
+                    self.edges.push(CausalEdge {
+                        cause,
+                        effect,
+                        kind,
+                        scope,
+                        span: *span,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn node(&mut self, name: &str) -> usize {
+        match self.nodes.iter().position(|n| n == name) {
+            Some(index) => index,
+            None => {
+                self.nodes.push(name.to_string());
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Reports links to undeclared names and cycles made only of forward
+    /// links.
+    pub fn validate(&self, symbols: &SymbolTable) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        for edge in &self.edges {
+            for endpoint in [edge.cause, edge.effect] {
+                let name = &self.nodes[endpoint];
+                if symbols.resolve(edge.scope, name).is_none() {
+                    diagnostics.push(Diagnostic::error(
+                        format!("causality link refers to undeclared `{}`", name),
+                        edge.span,
+                    ));
+                }
+            }
+        }
+        for cycle in self.cycles() {
+            let mut names: Vec<&str> = cycle
+                .iter()
+                .map(|e| self.nodes[self.edges[*e].cause].as_str())
+                .collect();
+            names.push(names[0]);
+            diagnostics.push(Diagnostic::error(
+                format!(
+                    "causality cycle {} needs a RETROCAUSAL or ACAUSAL link",
+                    names.join(" -> ")
+                ),
+                self.edges[cycle[0]].span,
+            ));
+        }
+        diagnostics
+    }
+
+    /// Cycles made only of forward links, each as the edge indices walked.
+    pub fn cycles(&self) -> Vec<Vec<usize>> {
+        let mut cycles = Vec::new();
+        let mut state = vec![Visit::New; self.nodes.len()];
+        let mut path = Vec::new();
+        for node in 0..self.nodes.len() {
+            if state[node] == Visit::New {
+                self.find_cycles(node, &mut state, &mut path, &mut cycles);
+            }
+        }
+        cycles
+    }
+
+    fn find_cycles(
+        &self,
+        node: usize,
+        state: &mut [Visit],
+        path: &mut Vec<usize>,
+        cycles: &mut Vec<Vec<usize>>,
+    ) {
+        state[node] = Visit::Active;
+        for (index, edge) in self.edges.iter().enumerate() {
+            if edge.cause != node || !edge.kind.is_forward() {
+                continue;
+            }
+            path.push(index);
+            match state[edge.effect] {
+                Visit::New => self.find_cycles(edge.effect, state, path, cycles),
+                Visit::Active => {
+                    let start = path
+                        .iter()
+                        .position(|e| self.edges[*e].cause == edge.effect)
+                        .unwrap();
+                    cycles.push(path[start..].to_vec());
+                }
+                Visit::Done => {}
+            }
+            path.pop();
+        }
+        state[node] = Visit::Done;
+    }
+
+    /// Orders the nodes so every forward link's cause comes before its
+    /// effect; ties keep declaration order. `None` if forward links cycle.
+    pub fn topological_order(&self) -> Option<Vec<&str>> {
+        let mut incoming = vec![0usize; self.nodes.len()];
+        for edge in self.edges.iter().filter(|e| e.kind.is_forward()) {
+            incoming[edge.effect] += 1;
+        }
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut placed = vec![false; self.nodes.len()];
+        while order.len() < self.nodes.len() {
+            let next = (0..self.nodes.len()).find(|n| !placed[*n] && incoming[*n] == 0)?;
+            placed[next] = true;
+            order.push(self.nodes[next].as_str());
+            for edge in self.edges.iter().filter(|e| e.kind.is_forward()) {
+                if edge.cause == next {
+                    incoming[edge.effect] -= 1;
+                }
+            }
+        }
+        Some(order)
+    }
+
+    /// Graphviz DOT, with loop-closing links dashed.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph causality {\n    rankdir=LR;\n");
+        for node in &self.nodes {
+            out.push_str(&format!("    {:?};\n", node));
+        }
+        for edge in &self.edges {
+            let style = if edge.kind.is_forward() {
+                ""
+            } else {
+                ", style=dashed"
+            };
+            out.push_str(&format!(
+                "    {:?} -> {:?} [label={:?}{}];\n",
+                self.nodes[edge.cause],
+                self.nodes[edge.effect],
+                edge.kind.to_string(),
+                style
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// `{"nodes": [{"id"}], "edges": [{"source", "target", "type", "line"}],
+    /// "order": [...] | null}` for the helios UI.
+    pub fn to_json(&self) -> String {
+        let mut rank = HashMap::new();
+        let order = self.topological_order();
+        if let Some(order) = &order {
+            for (i, name) in order.iter().enumerate() {
+                rank.insert(*name, i);
+            }
+        }
+        let json = JsonGraph {
+            nodes: self
+                .nodes
+                .iter()
+                .map(|id| JsonNode {
+                    id,
+                    rank: rank.get(id.as_str()).copied(),
+                })
+                .collect(),
+            edges: self
+                .edges
+                .iter()
+                .map(|e| JsonEdge {
+                    source: &self.nodes[e.cause],
+                    target: &self.nodes[e.effect],
+                    kind: e.kind,
+                    line: e.span.line,
+                })
+                .collect(),
+            order,
+        };
+        serde_json::to_string_pretty(&json).unwrap()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    New,
+    Active,
+    Done,
+}
+
+#[derive(Serialize)]
+struct JsonGraph<'a> {
+    nodes: Vec<JsonNode<'a>>,
+    edges: Vec<JsonEdge<'a>>,
+    order: Option<Vec<&'a str>>,
+}
+
+#[derive(Serialize)]
+struct JsonNode<'a> {
+    id: &'a str,
+    rank: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct JsonEdge<'a> {
+    source: &'a str,
+    target: &'a str,
+    #[serde(rename = "type")]
+    kind: CausalityType,
+    line: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_soul;
+    use crate::semantic::analyze;
+
+    const SOUL: &str = "department A 1.0;\ndepartment B 1.0;\ndepartment C 1.0;\nB causes C via FINAL;\nA causes B via EFFICIENT;\nC causes A via RETROCAUSAL;\n";
+
+    #[test]
+    fn orders_forward_links_and_allows_retrocausal_loops() {
+        let ast = parse_soul(SOUL).unwrap();
+        let graph = CausalityGraph::from_ast(&ast);
+        assert_eq!(graph.nodes, ["B", "C", "A"]);
+        assert!(graph.validate(&analyze(&ast).symbols).is_empty());
+        assert_eq!(graph.topological_order().unwrap(), ["A", "B", "C"]);
+
+        let dot = graph.to_dot();
+        assert!(dot.contains("    \"A\" -> \"B\" [label=\"EFFICIENT\"];\n"));
+        assert!(dot.contains("    \"C\" -> \"A\" [label=\"RETROCAUSAL\", style=dashed];\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&graph.to_json()).unwrap();
+        assert_eq!(json["edges"][2]["type"], "RETROCAUSAL");
+        assert_eq!(json["nodes"][0]["rank"], 1);
+        assert_eq!(json["order"][0], "A");
+    }
+
+    #[test]
+    fn rejects_forward_cycles() {
+        let source = SOUL.replace("RETROCAUSAL", "QUANTUM");
+        let ast = parse_soul(&source).unwrap();
+        let graph = CausalityGraph::from_ast(&ast);
+        assert_eq!(graph.topological_order(), None);
+
+        let found: Vec<String> = graph
+            .validate(&analyze(&ast).symbols)
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(
+            found,
+            ["4:1: error: causality cycle B -> C -> A -> B needs a RETROCAUSAL or ACAUSAL link"]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::span::{LineIndex, Span};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A single problem found in a `.soul` source, anchored to a span.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            span,
+        }
+    }
+
+    pub fn warning(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+            span,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Renders the diagnostic with a code frame pointing at its span:
+    ///
+    /// ```text
+    /// error: expected `;`, found `collapse`
+    ///  --> sovereign.soul:4:5
+    ///   |
+    /// 4 |     collapse CORE;
+    ///   |     ^^^^^^^^
+    /// ```
+    pub fn render(&self, source: &str, origin: &str) -> String {
+        let index = LineIndex::new(source);
+        // A span outside the source points at its start or end, in the
+        // header as well as the frame.
+        let line = self.span.line.clamp(1, index.line_count());
+        let text = index.line_text(line);
+        let gutter = " ".repeat(line.to_string().len());
+
+        let length = text.chars().count();
+        let column = match self.span.line.cmp(&line) {
+            Ordering::Less => 1,
+            Ordering::Equal => self.span.column.clamp(1, length + 1),
+            Ordering::Greater => length + 1,
+        };
+        let remaining = length - (column - 1);
+        let width = source
+            .get(self.span.start..self.span.end)
+            .map(|s| s.lines().next().unwrap_or("").chars().count())
+            .unwrap_or(0)
+            .clamp(1, remaining.max(1));
+
+        format!(
+            "{}: {}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}\n",
+            self.severity,
+            self.message,
+            gutter,
+            origin,
+            line,
+            column,
+            gutter,
+            line,
+            text,
+            gutter,
+            " ".repeat(column - 1),
+            "^".repeat(width),
+        )
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {}: {}",
+            self.span.line, self.span.column, self.severity, self.message
+        )
+    }
+}
+
+/// Renders every diagnostic in order, separated by blank lines.
+pub fn render_all(diagnostics: &[Diagnostic], source: &str, origin: &str) -> String {
+    diagnostics
+        .iter()
+        .map(|d| d.render(source, origin))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Files merged into a soul by the module loader, so that diagnostics about
+/// their statements can be rendered against the right text.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    /// Origin and text of file `n` at index `n - 1`.
+    files: Vec<(String, String)>,
+}
+
+impl SourceMap {
+    /// Adds a file and returns the id its spans carry.
+    pub fn add(&mut self, origin: impl Into<String>, text: impl Into<String>) -> usize {
+        self.files.push((origin.into(), text.into()));
+        self.files.len()
+    }
+
+    /// Origin and text of `file`, or `None` for the soul itself.
+    pub fn get(&self, file: usize) -> Option<(&str, &str)> {
+        let (origin, text) = self.files.get(file.checked_sub(1)?)?;
+        Some((origin, text))
+    }
+
+    /// Where `span` points, as `origin:line:column`, with `origin` naming the
+    /// soul itself.
+    pub fn locate(&self, span: Span, origin: &str) -> String {
+        let origin = self.get(span.file).map_or(origin, |(origin, _)| origin);
+        format!("{}:{}:{}", origin, span.line, span.column)
+    }
+
+    /// Like [`render_all`], rendering each diagnostic against the file its
+    /// span points into; `source` and `origin` are the soul itself.
+    pub fn render_all(&self, diagnostics: &[Diagnostic], source: &str, origin: &str) -> String {
+        diagnostics
+            .iter()
+            .map(|d| {
+                let (origin, source) = self.get(d.span.file).unwrap_or((origin, source));
+                d.render(source, origin)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
@@ -0,0 +1,488 @@
+use crate::causality::{CausalityGraph, CausalityType};
+use crate::format::print_soul;
+use crate::parser::{detached_docs, AstNode};
+
+/// One documented declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocEntry {
+    pub name: String,
+    /// Enclosing manifolds, outermost first.
+    pub path: Vec<String>,
+    /// The first line of the declaration as `print_soul` writes it, without
+    /// its doc comment.
+    pub signature: String,
+    pub doc: Option<String>,
+    pub line: usize,
+}
+
+impl DocEntry {
+    fn new(name: &str, path: &[String], node: &AstNode) -> Self {
+        let printed = print_soul(std::slice::from_ref(node));
+        Self {
+            name: name.to_string(),
+            path: path.to_vec(),
+            signature: printed
+                .lines()
+                .find(|line| !line.starts_with("///"))
+                .unwrap_or_default()
+                .to_string(),
+            doc: node.doc().map(clean_doc),
+            line: node.span().line,
+        }
+    }
+
+    /// `CORE::INNER::NAME`
+    pub fn qualified_name(&self) -> String {
+        let mut parts = self.path.clone();
+        parts.push(self.name.clone());
+        parts.join("::")
+    }
+
+    fn anchor(&self, section: &str) -> String {
+        let name: String = self
+            .qualified_name()
+            .chars()
+            .map(|c| if c.is_alphanumeric() { c } else { '-' })
+            .collect();
+        format!("{}-{}", section, name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifoldDoc {
+    pub entry: DocEntry,
+    /// Declarations other than axioms, departments and links, which have
+    /// sections of their own.
+    pub members: Vec<DocEntry>,
+    pub manifolds: Vec<ManifoldDoc>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinkDoc {
+    pub cause: String,
+    pub effect: String,
+    pub kind: CausalityType,
+    pub doc: Option<String>,
+    pub line: usize,
+}
+
+/// The documentation of a soul program: its manifolds, axioms, departments
+/// and causality links, each with the `///` comment written above it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SoulDocs {
+    /// Top-level declarations outside any manifold.
+    pub members: Vec<DocEntry>,
+    pub manifolds: Vec<ManifoldDoc>,
+    pub axioms: Vec<DocEntry>,
+    pub departments: Vec<DocEntry>,
+    pub links: Vec<LinkDoc>,
+    /// Causal order of the link endpoints, if the forward links allow one.
+    pub order: Option<Vec<String>>,
+    /// Doc comments that document no declaration, such as a closing banner.
+    pub notes: Vec<String>,
+}
+
+impl SoulDocs {
+    pub fn from_ast(ast: &[AstNode]) -> Self {
+        let mut docs = Self::default();
+        (docs.members, docs.manifolds) = docs.collect(ast, &[]);
+        docs.order = CausalityGraph::from_ast(ast)
+            .topological_order()
+            .map(|order| order.into_iter().map(String::from).collect());
+        docs
+    }
+
+    /// Adds the doc comments of `source` that document no statement as notes.
+    pub fn add_notes(&mut self, source: &str) {
+        let notes = detached_docs(source).into_iter();
+        self.notes.extend(notes.map(|(_, text)| clean_doc(&text)));
+    }
+
+    /// Files the axioms, departments and links of one block and returns its
+    /// members and manifolds.
+    fn collect(&mut self, nodes: &[AstNode], path: &[String]) -> (Vec<DocEntry>, Vec<ManifoldDoc>) {
+        let mut members = Vec::new();
+        let mut manifolds = Vec::new();
+        for node in nodes {
+            match node {
+                AstNode::Manifold { name, body, .. } => {
+                    let entry = DocEntry::new(name, path, node);
+                    let inner_path = [path, std::slice::from_ref(name)].concat();
+                    let (inner_members, inner) = self.collect(body, &inner_path);
+                    manifolds.push(ManifoldDoc {
+                        entry,
+                        members: inner_members,
+                        manifolds: inner,
+                    });
+                }
+                AstNode::Axiom { name, .. } => self.axioms.push(DocEntry::new(name, path, node)),
+                AstNode::Department { name, .. } => {
+                    self.departments.push(DocEntry::new(name, path, node))
+                }
+                AstNode::Causality {
+                    cause,
+                    effect,
+                    c_type,
+                    ..
+                } => self.links.push(LinkDoc {
+                    cause: cause.clone(),
+                    effect: effect.clone(),
+                    kind: CausalityType::parse(c_type).unwrap_or(CausalityType::Efficient),
+                    doc: node.doc().map(clean_doc),
+                    line: node.span().line,
+                }),
+                AstNode::Immortal { name, .. }
+                | AstNode::Template { name, .. }
+                | AstNode::Instance { name, .. }
+                | AstNode::Body { name, .. }
+                | AstNode::Spirit { name, .. }
+                | AstNode::Native { name, .. }
+                | AstNode::Resonate { target: name, .. }
+                | AstNode::Collapse { target: name, .. }
+                | AstNode::Entrench { key: name, .. }
+                | AstNode::Schema { key: name, .. }
+                | AstNode::Magnet { label: name, .. }
+                | AstNode::Property { key: name, .. }
+                | AstNode::Import { path: name, .. } => {
+                    members.push(DocEntry::new(name, path, node))
+                }
+                AstNode::Reflect { .. }
+                | AstNode::Expect { .. }
+                | AstNode::Marker { .. }
+                | AstNode::Section { .. }
+                | AstNode::Proclamation { .. } => {}
+            }
+        }
+        (members, manifolds)
+    }
+
+    pub fn to_markdown(&self, title: &str) -> String {
+        let mut out = format!("# {}\n", title);
+        if !self.members.is_empty() {
+            out.push_str("\n## Declarations\n\n");
+            markdown_members(&mut out, &self.members);
+        }
+        if !self.manifolds.is_empty() {
+            out.push_str("\n## Manifolds\n");
+            for manifold in &self.manifolds {
+                markdown_manifold(&mut out, manifold, 3);
+            }
+        }
+        for (heading, entries) in [("Axioms", &self.axioms), ("Departments", &self.departments)] {
+            if entries.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n## {}\n", heading));
+            for entry in entries {
+                out.push_str(&format!(
+                    "\n### `{}`\n\n```soul\n{}\n```\n",
+                    entry.qualified_name(),
+                    entry.signature
+                ));
+                if let Some(doc) = &entry.doc {
+                    out.push_str(&format!("\n{}\n", doc));
+                }
+            }
+        }
+        if !self.links.is_empty() {
+            out.push_str("\n## Causality\n\n");
+            out.push_str("| Cause | Effect | Type | Line | Description |\n|---|---|---|---|---|\n");
+            for link in &self.links {
+                let doc = link.doc.as_deref().unwrap_or_default().replace('\n', " ");
+                out.push_str(&format!(
+                    "| `{}` | `{}` | {} | {} | {} |\n",
+                    link.cause,
+                    link.effect,
+                    link.kind,
+                    link.line,
+                    doc.replace('|', "\\|")
+                ));
+            }
+            if let Some(order) = &self.order {
+                out.push_str(&format!("\nCausal order: {}\n", order.join(" → ")));
+            }
+        }
+        if !self.notes.is_empty() {
+            out.push_str("\n## Notes\n");
+            for note in &self.notes {
+                out.push_str(&format!("\n{}\n", note));
+            }
+        }
+        out
+    }
+
+    /// A standalone page with a table of contents; every declaration has an
+    /// anchor.
+    pub fn to_html(&self, title: &str) -> String {
+        let mut body = String::new();
+        let mut toc = String::new();
+
+        if !self.members.is_empty() {
+            toc.push_str("<li><a href=\"#declarations\">Declarations</a></li>\n");
+            body.push_str("<section id=\"declarations\">\n<h2>Declarations</h2>\n");
+            html_members(&mut body, &self.members);
+            body.push_str("</section>\n");
+        }
+        if !self.manifolds.is_empty() {
+            toc.push_str("<li><a href=\"#manifolds\">Manifolds</a>\n<ul>\n");
+            body.push_str("<section id=\"manifolds\">\n<h2>Manifolds</h2>\n");
+            for manifold in &self.manifolds {
+                html_manifold(&mut body, &mut toc, manifold, 3);
+            }
+            toc.push_str("</ul>\n</li>\n");
+            body.push_str("</section>\n");
+        }
+        for (heading, section, entries) in [
+            ("Axioms", "axiom", &self.axioms),
+            ("Departments", "department", &self.departments),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+            let id = heading.to_lowercase();
+            toc.push_str(&format!("<li><a href=\"#{}\">{}</a></li>\n", id, heading));
+            body.push_str(&format!("<section id=\"{}\">\n<h2>{}</h2>\n", id, heading));
+            for entry in entries {
+                body.push_str(&format!(
+                    "<h3 id=\"{}\"><code>{}</code></h3>\n<pre><code>{}</code></pre>\n",
+                    escape(&entry.anchor(section)),
+                    escape(&entry.qualified_name()),
+                    escape(&entry.signature)
+                ));
+                html_doc(&mut body, entry.doc.as_deref());
+            }
+            body.push_str("</section>\n");
+        }
+        if !self.links.is_empty() {
+            toc.push_str("<li><a href=\"#causality\">Causality</a></li>\n");
+            body.push_str("<section id=\"causality\">\n<h2>Causality</h2>\n<table>\n");
+            body.push_str(concat!(
+                "<tr><th>Cause</th><th>Effect</th><th>Type</th>",
+                "<th>Line</th><th>Description</th></tr>\n"
+            ));
+            for link in &self.links {
+                body.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                    self.html_endpoint(&link.cause),
+                    self.html_endpoint(&link.effect),
+                    link.kind,
+                    link.line,
+                    escape(link.doc.as_deref().unwrap_or_default())
+                ));
+            }
+            body.push_str("</table>\n");
+            if let Some(order) = &self.order {
+                let order: Vec<String> = order.iter().map(|n| escape(n)).collect();
+                body.push_str(&format!(
+                    "<p>Causal order: {}</p>\n",
+                    order.join(" &rarr; ")
+                ));
+            }
+            body.push_str("</section>\n");
+        }
+        if !self.notes.is_empty() {
+            toc.push_str("<li><a href=\"#notes\">Notes</a></li>\n");
+            body.push_str("<section id=\"notes\">\n<h2>Notes</h2>\n");
+            for note in &self.notes {
+                html_doc(&mut body, Some(note));
+            }
+            body.push_str("</section>\n");
+        }
+
+        format!(
+            "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>
+{STYLE}</style>
+</head>
+<body>
+<nav>
+<ul>
+{toc}</ul>
+</nav>
+<main>
+<h1>{title}</h1>
+{body}</main>
+</body>
+</html>
+",
+            title = escape(title),
+        )
+    }
+
+    /// A link endpoint, linked to its department when there is one.
+    fn html_endpoint(&self, name: &str) -> String {
+        match self.departments.iter().find(|d| d.name == name) {
+            Some(department) => format!(
+                "<a href=\"#{}\"><code>{}</code></a>",
+                escape(&department.anchor("department")),
+                escape(name)
+            ),
+            None => format!("<code>{}</code>", escape(name)),
+        }
+    }
+}
+
+const STYLE: &str = "body { display: flex; font-family: sans-serif; margin: 0; }
+nav { min-width: 14em; padding: 1em; background: #f4f4f4; }
+main { padding: 1em 2em; max-width: 60em; }
+pre { background: #f4f4f4; padding: 0.5em; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #ccc; padding: 0.25em 0.5em; text-align: left; }
+";
+
+fn markdown_manifold(out: &mut String, manifold: &ManifoldDoc, level: usize) {
+    out.push_str(&format!(
+        "\n{} `{}`\n",
+        "#".repeat(level.min(6)),
+        manifold.entry.qualified_name()
+    ));
+    if let Some(doc) = &manifold.entry.doc {
+        out.push_str(&format!("\n{}\n", doc));
+    }
+    if !manifold.members.is_empty() {
+        out.push('\n');
+        markdown_members(out, &manifold.members);
+    }
+    for inner in &manifold.manifolds {
+        markdown_manifold(out, inner, level + 1);
+    }
+}
+
+fn markdown_members(out: &mut String, members: &[DocEntry]) {
+    for member in members {
+        match &member.doc {
+            Some(doc) => out.push_str(&format!(
+                "- `{}` — {}\n",
+                member.signature,
+                doc.replace('\n', "\n  ")
+            )),
+            None => out.push_str(&format!("- `{}`\n", member.signature)),
+        }
+    }
+}
+
+fn html_manifold(body: &mut String, toc: &mut String, manifold: &ManifoldDoc, level: usize) {
+    let anchor = escape(&manifold.entry.anchor("manifold"));
+    let name = escape(&manifold.entry.qualified_name());
+    toc.push_str(&format!("<li><a href=\"#{}\">{}</a></li>\n", anchor, name));
+    body.push_str(&format!(
+        "<h{level} id=\"{}\"><code>{}</code></h{level}>\n",
+        anchor,
+        name,
+        level = level.min(6)
+    ));
+    html_doc(body, manifold.entry.doc.as_deref());
+    if !manifold.members.is_empty() {
+        html_members(body, &manifold.members);
+    }
+    for inner in &manifold.manifolds {
+        html_manifold(body, toc, inner, level + 1);
+    }
+}
+
+fn html_members(body: &mut String, members: &[DocEntry]) {
+    body.push_str("<ul>\n");
+    for member in members {
+        body.push_str(&format!("<li><code>{}</code>", escape(&member.signature)));
+        if let Some(doc) = &member.doc {
+            body.push_str(&format!(" &mdash; {}", escape(doc)));
+        }
+        body.push_str("</li>\n");
+    }
+    body.push_str("</ul>\n");
+}
+
+/// Paragraphs are separated by blank doc lines.
+fn html_doc(body: &mut String, doc: Option<&str>) {
+    for paragraph in doc.unwrap_or_default().split("\n\n") {
+        if !paragraph.trim().is_empty() {
+            body.push_str(&format!("<p>{}</p>\n", escape(paragraph.trim())));
+        }
+    }
+}
+
+/// Drops the decorative `///` that some souls close their comment lines with.
+fn clean_doc(doc: &str) -> String {
+    let lines: Vec<&str> = doc
+        .lines()
+        .map(|line| line.strip_suffix("///").unwrap_or(line).trim_end())
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_soul;
+
+    const SOUL: &str = "/// The heart of the system.\n///\n/// Holds the mission.\nmanifold CORE {\n    /// What we are for. ///\n    entrench MISSION \"a\";\n    manifold INNER {\n        /// Keeps <entropy> low.\n        axiom STABLE: \"entropy < 0.5\";\n        department Wealth 1.0;\n    }\n    department Security 0.9;\n    /// Money buys safety.\n    Wealth causes Security via EFFICIENT;\n}\n";
+
+    #[test]
+    fn collects_documented_declarations() {
+        let ast = parse_soul(SOUL).unwrap();
+        assert_eq!(
+            ast[0].doc(),
+            Some("The heart of the system.\n\nHolds the mission.")
+        );
+
+        let docs = SoulDocs::from_ast(&ast);
+        let core = &docs.manifolds[0];
+        assert_eq!(core.members[0].doc.as_deref(), Some("What we are for."));
+        assert_eq!(core.manifolds[0].entry.qualified_name(), "CORE::INNER");
+        assert_eq!(docs.axioms[0].qualified_name(), "CORE::INNER::STABLE");
+        assert_eq!(docs.departments.len(), 2);
+        assert_eq!(docs.links[0].doc.as_deref(), Some("Money buys safety."));
+        assert_eq!(docs.order.as_ref().unwrap(), &["Wealth", "Security"]);
+    }
+
+    #[test]
+    fn renders_markdown_and_html() {
+        let docs = SoulDocs::from_ast(&parse_soul(SOUL).unwrap());
+        let markdown = docs.to_markdown("core.soul");
+        assert!(markdown.starts_with("# core.soul\n\n## Manifolds\n\n### `CORE`\n\nThe heart of the system.\n\nHolds the mission.\n\n- `entrench MISSION \"a\";` — What we are for.\n\n#### `CORE::INNER`\n"));
+        assert!(
+            markdown.contains("| `Wealth` | `Security` | EFFICIENT | 14 | Money buys safety. |\n")
+        );
+
+        let html = docs.to_html("core.soul");
+        assert!(html.contains("<h3 id=\"axiom-CORE--INNER--STABLE\"><code>CORE::INNER::STABLE</code></h3>\n<pre><code>axiom STABLE: &quot;entropy &lt; 0.5&quot;;</code></pre>\n<p>Keeps &lt;entropy&gt; low.</p>\n"));
+        assert!(html.contains(
+            "<td><a href=\"#department-CORE--INNER--Wealth\"><code>Wealth</code></a></td>"
+        ));
+        assert!(html.contains("<li><a href=\"#manifold-CORE--INNER\">CORE::INNER</a></li>"));
+    }
+
+    #[test]
+    fn keeps_banners() {
+        let src = "/// [SEED] ///\n\nmanifold CORE {}\n\n/// [COMPLETE] ///\n";
+        let mut docs = SoulDocs::from_ast(&parse_soul(src).unwrap());
+        docs.add_notes(src);
+        assert_eq!(docs.manifolds[0].entry.doc.as_deref(), Some("[SEED]"));
+        assert_eq!(docs.notes, ["[COMPLETE]"]);
+        assert!(docs
+            .to_markdown("seed.soul")
+            .ends_with("\n## Notes\n\n[COMPLETE]\n"));
+        assert!(docs
+            .to_html("seed.soul")
+            .contains("<section id=\"notes\">\n<h2>Notes</h2>\n<p>[COMPLETE]</p>\n"));
+    }
+}
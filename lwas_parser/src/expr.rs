@@ -0,0 +1,640 @@
+use crate::parser::{AstNode, EntrenchValue};
+use pest::error::LineColLocation;
+use pest::iterators::Pairs;
+use pest::pratt_parser::{Assoc, Op, PrattParser};
+use pest::Parser;
+use pest_derive::Parser;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use thiserror::Error;
+
+#[derive(Parser)]
+#[grammar = "axiom.pest"]
+struct AxiomParser;
+
+/// Entity kinds a quantifier may range over with `∀x in <kind>: ...`.
+pub const ENTITY_KINDS: &[&str] = &[
+    "manifold",
+    "department",
+    "magnet",
+    "entrench",
+    "immortal",
+    "axiom",
+    "body",
+    "spirit",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnaryOp {
+    Not,
+    Neg,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BinaryOp {
+    Implies,
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Quantifier {
+    ForAll,
+    Exists,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Expr {
+    Number(f64),
+    Bool(bool),
+    Str(String),
+    /// A telemetry value, a declared entity or a quantified variable.
+    Name(String),
+    /// `d.priority`
+    Member(Box<Expr>, String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Quantified {
+        quantifier: Quantifier,
+        var: String,
+        /// `in <kind>`; `None` ranges over every entity.
+        kind: Option<String>,
+        body: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Type {
+    Number,
+    Bool,
+    Str,
+    Entity,
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Type::Number => "number",
+            Type::Bool => "bool",
+            Type::Str => "string",
+            Type::Entity => "entity",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Bool(bool),
+    Str(String),
+    /// Index into `Environment::entities`.
+    Entity(usize),
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ExprError {
+    #[error("invalid expression at column {column}: {message}")]
+    Syntax { message: String, column: usize },
+    #[error("unknown name `{0}`")]
+    UnknownName(String),
+    #[error("unknown entity kind `{0}`")]
+    UnknownKind(String),
+    #[error("`{entity}` has no attribute `{attribute}`")]
+    UnknownAttribute { entity: String, attribute: String },
+    #[error("`{op}` cannot be applied to {found}")]
+    Type { op: &'static str, found: String },
+    #[error("division by zero")]
+    DivisionByZero,
+}
+
+/// Something an axiom can talk about: a manifold, department, magnet...
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entity {
+    pub name: String,
+    pub kind: String,
+    /// Numeric facts about the entity, e.g. `priority` or `curvature`.
+    pub attributes: HashMap<String, f64>,
+}
+
+/// What axioms are checked against: live telemetry and the soul's entities.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Environment {
+    /// `entropy`, `cpu`, ... as captured from the organism.
+    pub telemetry: HashMap<String, f64>,
+    pub entities: Vec<Entity>,
+}
+
+impl Environment {
+    /// Collects the entities declared in a soul.
+    pub fn from_ast(ast: &[AstNode]) -> Self {
+        let mut env = Self::default();
+        env.collect(ast);
+        env
+    }
+
+    pub fn set_telemetry(&mut self, name: &str, value: f64) {
+        self.telemetry.insert(name.to_string(), value);
+    }
+
+    fn collect(&mut self, nodes: &[AstNode]) {
+        for node in nodes {
+            let (name, kind, attributes) = match node {
+                AstNode::Manifold { name, body, .. } => {
+                    self.collect(body);
+                    let attributes = body
+                        .iter()
+                        .filter_map(|n| match n {
+                            AstNode::Property {
+                                key,
+                                value: EntrenchValue::Number(v),
+                                ..
+                            } => Some((key.clone(), *v as f64)),
+                            _ => None,
+                        })
+                        .collect();
+                    (name, "manifold", attributes)
+                }
+                AstNode::Department { name, priority, .. } => (
+                    name,
+                    "department",
+                    HashMap::from([("priority".to_string(), *priority)]),
+                ),
+                AstNode::Magnet { label, power, .. } => (
+                    label,
+                    "magnet",
+                    HashMap::from([("power".to_string(), *power)]),
+                ),
+                AstNode::Entrench { key, value, .. } => {
+                    let attributes = match value {
+                        Some(EntrenchValue::Number(v)) => {
+                            HashMap::from([("value".to_string(), *v as f64)])
+                        }
+                        _ => HashMap::new(),
+                    };
+                    (key, "entrench", attributes)
+                }
+                AstNode::Immortal { name, .. } => (name, "immortal", HashMap::new()),
+                AstNode::Axiom { name, .. } => (name, "axiom", HashMap::new()),
+                AstNode::Body { name, .. } => (name, "body", HashMap::new()),
+                AstNode::Spirit { name, .. } => (name, "spirit", HashMap::new()),
+                _ => continue,
+            };
+            // Repeated entrenchments of a key are one entity.
+            if self
+                .entities
+                .iter()
+                .any(|e| e.name == *name && e.kind == kind)
+            {
+                continue;
+            }
+            self.entities.push(Entity {
+                name: name.clone(),
+                kind: kind.to_string(),
+                attributes,
+            });
+        }
+    }
+
+    fn entity(&self, name: &str) -> Option<usize> {
+        self.entities.iter().position(|e| e.name == name)
+    }
+
+    fn domain<'a>(&'a self, kind: &'a Option<String>) -> impl Iterator<Item = usize> + 'a {
+        self.entities
+            .iter()
+            .enumerate()
+            .filter(move |(_, e)| kind.as_ref().is_none_or(|k| e.kind == *k))
+            .map(|(i, _)| i)
+    }
+}
+
+/// Parses an axiom expression such as `∀d in department: d.priority ≥ 0`.
+pub fn parse_expression(source: &str) -> Result<Expr, ExprError> {
+    let mut pairs = AxiomParser::parse(Rule::expression, source).map_err(|e| {
+        let column = match e.line_col {
+            LineColLocation::Pos((_, column)) | LineColLocation::Span((_, column), _) => column,
+        };
+        ExprError::Syntax {
+            message: e.variant.message().to_string(),
+            column,
+        }
+    })?;
+    let expr = pairs.next().unwrap().into_inner().next().unwrap();
+    Ok(build(expr.into_inner(), &pratt()))
+}
+
+fn pratt() -> PrattParser<Rule> {
+    PrattParser::new()
+        .op(Op::infix(Rule::implies, Assoc::Right))
+        .op(Op::infix(Rule::or, Assoc::Left))
+        .op(Op::infix(Rule::and, Assoc::Left))
+        .op(Op::infix(Rule::eq, Assoc::Left)
+            | Op::infix(Rule::ne, Assoc::Left)
+            | Op::infix(Rule::lt, Assoc::Left)
+            | Op::infix(Rule::le, Assoc::Left)
+            | Op::infix(Rule::gt, Assoc::Left)
+            | Op::infix(Rule::ge, Assoc::Left))
+        .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
+        .op(Op::infix(Rule::mul, Assoc::Left)
+            | Op::infix(Rule::div, Assoc::Left)
+            | Op::infix(Rule::modulo, Assoc::Left))
+        .op(Op::prefix(Rule::not) | Op::prefix(Rule::neg))
+        .op(Op::postfix(Rule::member))
+}
+
+fn build(pairs: Pairs<Rule>, pratt: &PrattParser<Rule>) -> Expr {
+    pratt
+        .map_primary(|primary| match primary.as_rule() {
+            Rule::number => Expr::Number(primary.as_str().parse().unwrap()),
+            Rule::boolean => Expr::Bool(primary.as_str() == "true"),
+            Rule::string => Expr::Str(primary.as_str().trim_matches('"').to_string()),
+            Rule::identifier => Expr::Name(primary.as_str().to_string()),
+            Rule::expr => build(primary.into_inner(), pratt),
+            Rule::quantifier => {
+                let mut inner = primary.into_inner();
+                let quantifier = match inner.next().unwrap().as_rule() {
+                    Rule::forall => Quantifier::ForAll,
+                    _ => Quantifier::Exists,
+                };
+                let var = inner.next().unwrap().as_str().to_string();
+                let mut next = inner.next().unwrap();
+                let kind = if next.as_rule() == Rule::identifier {
+                    let kind = next.as_str().to_string();
+                    next = inner.next().unwrap();
+                    Some(kind)
+                } else {
+                    None
+                };
+                Expr::Quantified {
+                    quantifier,
+                    var,
+                    kind,
+                    body: Box::new(build(next.into_inner(), pratt)),
+                }
+            }
+            rule => unreachable!("primary cannot be {:?}", rule),
+        })
+        .map_prefix(|op, rhs| {
+            let op = match op.as_rule() {
+                Rule::not => UnaryOp::Not,
+                _ => UnaryOp::Neg,
+            };
+            Expr::Unary(op, Box::new(rhs))
+        })
+        .map_postfix(|lhs, op| {
+            let attribute = op.into_inner().next().unwrap().as_str().to_string();
+            Expr::Member(Box::new(lhs), attribute)
+        })
+        .map_infix(|lhs, op, rhs| {
+            let op = match op.as_rule() {
+                Rule::implies => BinaryOp::Implies,
+                Rule::or => BinaryOp::Or,
+                Rule::and => BinaryOp::And,
+                Rule::eq => BinaryOp::Eq,
+                Rule::ne => BinaryOp::Ne,
+                Rule::lt => BinaryOp::Lt,
+                Rule::le => BinaryOp::Le,
+                Rule::gt => BinaryOp::Gt,
+                Rule::ge => BinaryOp::Ge,
+                Rule::add => BinaryOp::Add,
+                Rule::sub => BinaryOp::Sub,
+                Rule::mul => BinaryOp::Mul,
+                Rule::div => BinaryOp::Div,
+                rule => {
+                    debug_assert_eq!(rule, Rule::modulo);
+                    BinaryOp::Mod
+                }
+            };
+            Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+        })
+        .parse(pairs)
+}
+
+impl BinaryOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Implies => "→",
+            BinaryOp::Or => "∨",
+            BinaryOp::And => "∧",
+            BinaryOp::Eq => "=",
+            BinaryOp::Ne => "≠",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "≤",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => "≥",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Mod => "%",
+        }
+    }
+}
+
+impl Expr {
+    /// Infers the expression's type against the names `env` declares.
+    pub fn check(&self, env: &Environment) -> Result<Type, ExprError> {
+        self.check_in(env, &mut Vec::new())
+    }
+
+    fn check_in(&self, env: &Environment, bound: &mut Vec<String>) -> Result<Type, ExprError> {
+        match self {
+            Expr::Number(_) => Ok(Type::Number),
+            Expr::Bool(_) => Ok(Type::Bool),
+            Expr::Str(_) => Ok(Type::Str),
+            Expr::Name(name) => {
+                if bound.contains(name) || env.entity(name).is_some() {
+                    Ok(Type::Entity)
+                } else if env.telemetry.contains_key(name) {
+                    Ok(Type::Number)
+                } else {
+                    Err(ExprError::UnknownName(name.clone()))
+                }
+            }
+            Expr::Member(target, _) => match target.check_in(env, bound)? {
+                Type::Entity => Ok(Type::Number),
+                found => Err(type_error(".", found)),
+            },
+            Expr::Unary(op, operand) => {
+                let (symbol, expected) = match op {
+                    UnaryOp::Not => ("¬", Type::Bool),
+                    UnaryOp::Neg => ("-", Type::Number),
+                };
+                match operand.check_in(env, bound)? {
+                    found if found == expected => Ok(expected),
+                    found => Err(type_error(symbol, found)),
+                }
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = lhs.check_in(env, bound)?;
+                let rhs = rhs.check_in(env, bound)?;
+                let operands = |expected: Type| {
+                    if lhs == expected && rhs == expected {
+                        Ok(())
+                    } else {
+                        Err(ExprError::Type {
+                            op: op.symbol(),
+                            found: format!("{} and {}", lhs, rhs),
+                        })
+                    }
+                };
+                match op {
+                    BinaryOp::Implies | BinaryOp::Or | BinaryOp::And => {
+                        operands(Type::Bool).map(|_| Type::Bool)
+                    }
+                    BinaryOp::Eq | BinaryOp::Ne => operands(lhs).map(|_| Type::Bool),
+                    BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+                        operands(Type::Number).map(|_| Type::Bool)
+                    }
+                    _ => operands(Type::Number).map(|_| Type::Number),
+                }
+            }
+            Expr::Quantified {
+                var, kind, body, ..
+            } => {
+                if let Some(kind) = kind {
+                    if !ENTITY_KINDS.contains(&kind.as_str()) {
+                        return Err(ExprError::UnknownKind(kind.clone()));
+                    }
+                }
+                bound.push(var.clone());
+                let body = body.check_in(env, bound);
+                bound.pop();
+                match body? {
+                    Type::Bool => Ok(Type::Bool),
+                    found => Err(type_error("∀/∃", found)),
+                }
+            }
+        }
+    }
+
+    /// Evaluates the expression. Call [`Expr::check`] first to rule out
+    /// type errors; evaluation still reports them if it meets one.
+    pub fn eval(&self, env: &Environment) -> Result<Value, ExprError> {
+        self.eval_in(env, &mut Vec::new())
+    }
+
+    fn eval_in(
+        &self,
+        env: &Environment,
+        bound: &mut Vec<(String, usize)>,
+    ) -> Result<Value, ExprError> {
+        match self {
+            Expr::Number(n) => Ok(Value::Number(*n)),
+            Expr::Bool(b) => Ok(Value::Bool(*b)),
+            Expr::Str(s) => Ok(Value::Str(s.clone())),
+            Expr::Name(name) => {
+                if let Some((_, entity)) = bound.iter().rev().find(|(var, _)| var == name) {
+                    Ok(Value::Entity(*entity))
+                } else if let Some(entity) = env.entity(name) {
+                    Ok(Value::Entity(entity))
+                } else if let Some(value) = env.telemetry.get(name) {
+                    Ok(Value::Number(*value))
+                } else {
+                    Err(ExprError::UnknownName(name.clone()))
+                }
+            }
+            Expr::Member(target, attribute) => match target.eval_in(env, bound)? {
+                Value::Entity(index) => {
+                    let entity = &env.entities[index];
+                    entity
+                        .attributes
+                        .get(attribute)
+                        .map(|v| Value::Number(*v))
+                        .ok_or_else(|| ExprError::UnknownAttribute {
+                            entity: entity.name.clone(),
+                            attribute: attribute.clone(),
+                        })
+                }
+                other => Err(type_error(".", other.type_of())),
+            },
+            Expr::Unary(UnaryOp::Not, operand) => Ok(Value::Bool(!operand.bool_in(env, bound)?)),
+            Expr::Unary(UnaryOp::Neg, operand) => {
+                Ok(Value::Number(-operand.number_in(env, bound, "-")?))
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let symbol = op.symbol();
+                let value = match op {
+                    BinaryOp::And => {
+                        Value::Bool(lhs.bool_in(env, bound)? && rhs.bool_in(env, bound)?)
+                    }
+                    BinaryOp::Or => {
+                        Value::Bool(lhs.bool_in(env, bound)? || rhs.bool_in(env, bound)?)
+                    }
+                    BinaryOp::Implies => {
+                        Value::Bool(!lhs.bool_in(env, bound)? || rhs.bool_in(env, bound)?)
+                    }
+                    BinaryOp::Eq | BinaryOp::Ne => {
+                        let lhs = lhs.eval_in(env, bound)?;
+                        let rhs = rhs.eval_in(env, bound)?;
+                        if lhs.type_of() != rhs.type_of() {
+                            return Err(ExprError::Type {
+                                op: symbol,
+                                found: format!("{} and {}", lhs.type_of(), rhs.type_of()),
+                            });
+                        }
+                        Value::Bool((lhs == rhs) == (*op == BinaryOp::Eq))
+                    }
+                    _ => {
+                        let a = lhs.number_in(env, bound, symbol)?;
+                        let b = rhs.number_in(env, bound, symbol)?;
+                        match op {
+                            BinaryOp::Lt => Value::Bool(a < b),
+                            BinaryOp::Le => Value::Bool(a <= b),
+                            BinaryOp::Gt => Value::Bool(a > b),
+                            BinaryOp::Ge => Value::Bool(a >= b),
+                            BinaryOp::Add => Value::Number(a + b),
+                            BinaryOp::Sub => Value::Number(a - b),
+                            BinaryOp::Mul => Value::Number(a * b),
+                            BinaryOp::Div | BinaryOp::Mod if b == 0.0 => {
+                                return Err(ExprError::DivisionByZero)
+                            }
+                            BinaryOp::Div => Value::Number(a / b),
+                            _ => Value::Number(a % b),
+                        }
+                    }
+                };
+                Ok(value)
+            }
+            Expr::Quantified {
+                quantifier,
+                var,
+                kind,
+                body,
+            } => {
+                let want = *quantifier == Quantifier::Exists;
+                for entity in env.domain(kind) {
+                    bound.push((var.clone(), entity));
+                    let holds = body.bool_in(env, bound);
+                    bound.pop();
+                    if holds? == want {
+                        return Ok(Value::Bool(want));
+                    }
+                }
+                Ok(Value::Bool(!want))
+            }
+        }
+    }
+
+    fn bool_in(
+        &self,
+        env: &Environment,
+        bound: &mut Vec<(String, usize)>,
+    ) -> Result<bool, ExprError> {
+        match self.eval_in(env, bound)? {
+            Value::Bool(b) => Ok(b),
+            other => Err(type_error("∧/∨/¬", other.type_of())),
+        }
+    }
+
+    fn number_in(
+        &self,
+        env: &Environment,
+        bound: &mut Vec<(String, usize)>,
+        op: &'static str,
+    ) -> Result<f64, ExprError> {
+        match self.eval_in(env, bound)? {
+            Value::Number(n) => Ok(n),
+            other => Err(type_error(op, other.type_of())),
+        }
+    }
+}
+
+impl Value {
+    pub fn type_of(&self) -> Type {
+        match self {
+            Value::Number(_) => Type::Number,
+            Value::Bool(_) => Type::Bool,
+            Value::Str(_) => Type::Str,
+            Value::Entity(_) => Type::Entity,
+        }
+    }
+}
+
+fn type_error(op: &'static str, found: Type) -> ExprError {
+    ExprError::Type {
+        op,
+        found: found.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_soul;
+
+    fn env() -> Environment {
+        let ast = parse_soul(
+            "manifold CORE {\n    curvature: 0.618\n    department Security 0.99;\n    department Wealth 1.0;\n}\n",
+        )
+        .unwrap();
+        let mut env = Environment::from_ast(&ast);
+        env.set_telemetry("entropy", 0.25);
+        env.set_telemetry("cpu", 42.0);
+        env
+    }
+
+    fn holds(source: &str) -> bool {
+        let env = env();
+        let expr = parse_expression(source).unwrap();
+        assert_eq!(expr.check(&env), Ok(Type::Bool), "{}", source);
+        expr.eval(&env) == Ok(Value::Bool(true))
+    }
+
+    #[test]
+    fn precedence_and_connectives() {
+        let expr = parse_expression("1 + 2 * 3 = 7 ∧ ¬false").unwrap();
+        assert!(matches!(expr, Expr::Binary(BinaryOp::And, _, _)));
+        assert!(holds("1 + 2 * 3 = 7 ∧ ¬false"));
+        assert!(holds("entropy < 0.5 and cpu <= 90"));
+        assert!(holds("cpu > 90 -> entropy > 1"));
+        assert!(!holds("entropy >= 1 || -cpu > 0"));
+    }
+
+    #[test]
+    fn quantifies_over_declared_entities() {
+        assert!(holds("∃x: x = x"));
+        assert!(holds("∀d in department: d.priority ≥ 0.9"));
+        assert!(holds("exists m in manifold: m.curvature < 1 and m = CORE"));
+        assert!(!holds("forall d in department: d.priority = 1.0"));
+    }
+
+    #[test]
+    fn reports_type_and_name_errors() {
+        let env = env();
+        let check = |source: &str| parse_expression(source).unwrap().check(&env);
+        assert_eq!(
+            check("entropy + true"),
+            Err(ExprError::Type {
+                op: "+",
+                found: "number and bool".into()
+            })
+        );
+        assert_eq!(check("ram > 1"), Err(ExprError::UnknownName("ram".into())));
+        assert_eq!(
+            check("∀x in planet: true"),
+            Err(ExprError::UnknownKind("planet".into()))
+        );
+        assert_eq!(
+            parse_expression("1 / 0").unwrap().eval(&env),
+            Err(ExprError::DivisionByZero)
+        );
+        assert!(matches!(
+            parse_expression("entropy <"),
+            Err(ExprError::Syntax { column: 10, .. })
+        ));
+    }
+}
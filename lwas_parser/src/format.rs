@@ -0,0 +1,655 @@
+use crate::parser::{
+    doc_comment, parse_soul, AstNode, EntrenchValue, Frequency, ParseError, TemplateArg,
+};
+
+const INDENT: &str = "    ";
+
+/// Prints an AST as canonical `.soul` text.
+///
+/// Parsing the output yields the same AST (up to spans). Only `///` doc
+/// comments are part of the AST; use [`format_soul`] to keep the others.
+pub fn print_soul(ast: &[AstNode]) -> String {
+    let mut printer = Printer::new(None);
+    printer.block(ast, 0, 0);
+    printer.out
+}
+
+/// Reformats a `.soul` source canonically, keeping its comments and
+/// collapsing runs of blank lines to one.
+pub fn format_soul(source: &str) -> Result<String, ParseError> {
+    let ast = parse_soul(source)?;
+    let mut printer = Printer::new(Some(source));
+    printer.block(&ast, 0, source.len());
+    Ok(printer.out)
+}
+
+struct Printer<'a> {
+    source: Option<&'a str>,
+    out: String,
+    depth: usize,
+    /// Nothing has been printed yet in the current block.
+    fresh: bool,
+    blank: bool,
+    /// A comment may follow the last line without changing its meaning.
+    trailable: bool,
+}
+
+impl<'a> Printer<'a> {
+    fn new(source: Option<&'a str>) -> Self {
+        Self {
+            source,
+            out: String::new(),
+            depth: 0,
+            fresh: true,
+            blank: false,
+            trailable: false,
+        }
+    }
+
+    /// Prints `nodes`, which sit between the byte offsets `start` and `end` of
+    /// the source; the gaps between them hold only whitespace and comments.
+    fn block(&mut self, nodes: &[AstNode], start: usize, end: usize) {
+        let mut cursor = start;
+        for (i, node) in nodes.iter().enumerate() {
+            let span = node.span();
+            let standalone = self.depth == 0 && is_block(node);
+            if standalone && i > 0 {
+                self.blank = true;
+            }
+            // The doc comment is printed from the AST, above the node.
+            let start = self
+                .source
+                .and_then(|source| doc_comment(source, span.start))
+                .map_or(span.start, |(first, _)| first);
+            self.trivia(cursor, start, true);
+            self.inner_comments(node);
+            if let Some(doc) = node.doc() {
+                for line in doc.split('\n') {
+                    self.line(&format!("/// {}", line));
+                }
+                // A blank line between the comment and the node is kept.
+                let lines = self
+                    .source
+                    .map_or(0, |s| s[start..span.start].matches('\n').count());
+                self.blank = lines > doc.split('\n').count();
+            }
+            self.node(node);
+            self.blank = matches!(node, AstNode::Section { .. }) || standalone;
+            cursor = span.end;
+        }
+        self.blank = false;
+        self.trivia(cursor, end, false);
+    }
+
+    fn trivia(&mut self, start: usize, end: usize, before_node: bool) {
+        let Some(source) = self.source else {
+            return;
+        };
+        let gap = &source[start.min(end)..end];
+        let mut lines = gap.split('\n').peekable();
+
+        // Whatever shares a line with the previous node or `{` trails it.
+        if let Some(comment) = lines.next().and_then(find_comment) {
+            if self.trailable && start > 0 {
+                self.out.pop();
+                self.out.push(' ');
+                self.out.push_str(comment);
+                self.out.push('\n');
+            } else {
+                self.line(comment);
+            }
+        }
+
+        while let Some(line) = lines.next() {
+            let text = line.trim();
+            if text.is_empty() {
+                // The last piece is the indentation of the next node.
+                if lines.peek().is_some() || !before_node {
+                    self.blank = true;
+                }
+                continue;
+            }
+            self.line(text);
+        }
+        if !before_node {
+            self.blank = false;
+        }
+    }
+
+    /// Comments inside a single-line construct, such as a `spirit` block
+    /// spread over several lines, are moved above it.
+    fn inner_comments(&mut self, node: &AstNode) {
+        let Some(source) = self.source else {
+            return;
+        };
+        let span = node.span();
+        let text = match node {
+            AstNode::Body { .. }
+            | AstNode::Template { .. }
+            | AstNode::Native { .. }
+            | AstNode::Section { .. }
+            | AstNode::Property { .. }
+            | AstNode::Marker { .. }
+            | AstNode::Proclamation { .. } => return,
+            AstNode::Manifold { .. } => &source[span.start..manifold_open(source, node)],
+            _ => &source[span.start..span.end],
+        };
+        for comment in scan_comments(text) {
+            self.line(comment);
+        }
+    }
+
+    fn node(&mut self, node: &AstNode) {
+        match node {
+            AstNode::Immortal { name, value, .. } => {
+                self.line(&format!("immortal {} = {};", name, quote(value)))
+            }
+            AstNode::Body {
+                name,
+                content,
+                fence: Some(language),
+                ..
+            } => {
+                let fence = "`".repeat(longest_backtick_run(content).max(2) + 1);
+                self.line(&format!(
+                    "body {} {}{}",
+                    ident_or_quote(name),
+                    fence,
+                    language
+                ));
+                // Fenced text is raw: it keeps its own indentation.
+                if !content.is_empty() {
+                    for line in content.split('\n') {
+                        self.out.push_str(line);
+                        self.out.push('\n');
+                    }
+                }
+                self.line(&fence);
+            }
+            AstNode::Body { name, content, .. } => {
+                if content.is_empty() {
+                    self.line(&format!("body {} {{}}", ident_or_quote(name)));
+                } else {
+                    self.line(&format!("body {} {{", ident_or_quote(name)));
+                    self.nested(|p| p.line(content));
+                    self.line("}");
+                }
+            }
+            AstNode::Spirit {
+                name,
+                goal,
+                context,
+                fields,
+                ..
+            } => {
+                self.line(&format!("spirit {} {{", ident_or_quote(name)));
+                self.nested(|p| {
+                    p.line(&format!("goal: {}", quote(goal)));
+                    if let Some(context) = context {
+                        p.line(&format!("context: {}", quote(context)));
+                    }
+                    for (key, value) in fields {
+                        p.line(&format!("{}: {}", key, literal(value)));
+                    }
+                });
+                self.line("}");
+            }
+            AstNode::Manifold { name, body, .. } => {
+                self.line(&format!("manifold {} {{", ident_or_quote(name)));
+                let (start, end) = match self.source {
+                    Some(source) => (manifold_open(source, node), node.span().end - 1),
+                    None => (0, 0),
+                };
+                let mark = self.out.len();
+                self.nested(|p| p.block(body, start, end));
+                if self.out.len() == mark {
+                    self.out.truncate(mark - 1);
+                    self.out.push_str("}\n");
+                } else {
+                    self.line("}");
+                }
+            }
+            AstNode::Template {
+                name, params, body, ..
+            } => {
+                let params: Vec<String> = params
+                    .iter()
+                    .map(|p| format!("{}: {}", p.name, p.ty))
+                    .collect();
+                let head = format!("template {}({})", name, params.join(", "));
+                if body.is_empty() {
+                    self.line(&format!("{} {{}}", head));
+                } else {
+                    self.line(&format!("{} {{", head));
+                    self.nested(|p| p.line(body));
+                    self.line("}");
+                }
+            }
+            AstNode::Instance {
+                name,
+                template,
+                args,
+                ..
+            } => {
+                let args: Vec<String> = args.iter().map(template_arg).collect();
+                self.line(&format!(
+                    "manifold {} = {}({});",
+                    ident_or_quote(name),
+                    template,
+                    args.join(", ")
+                ));
+            }
+            AstNode::Resonate {
+                target,
+                frequency,
+                partner,
+                ..
+            } => {
+                let mut text = format!("resonate {}", ident_or_quote(target));
+                let explicit = *frequency != Frequency::Hz(1.0);
+                match partner {
+                    Some(partner) => {
+                        text.push_str(&format!(" with {}", ident_or_quote(partner)));
+                        if explicit {
+                            text.push_str(&format!(" at {}", frequency_text(frequency)));
+                            if matches!(frequency, Frequency::Hz(_)) {
+                                text.push_str("Hz");
+                            }
+                        }
+                    }
+                    None if explicit => text.push_str(&format!(" {}", frequency_text(frequency))),
+                    None => {}
+                }
+                text.push(';');
+                self.line(&text);
+            }
+            AstNode::Collapse {
+                target,
+                entropy_threshold,
+                condition,
+                ..
+            } => {
+                let mut text = format!("collapse {}", ident_or_quote(target));
+                if let Some(condition) = condition {
+                    text.push_str(&format!(" where {}", condition));
+                } else if *entropy_threshold != 0.5 {
+                    text.push_str(&format!(" {}", number(*entropy_threshold)));
+                }
+                text.push(';');
+                self.line(&text);
+            }
+            AstNode::Entrench { key, value, .. } => {
+                let mut text = format!("entrench {}", ident_or_quote(key));
+                if let Some(value) = value {
+                    text.push(' ');
+                    text.push_str(&literal(value));
+                }
+                text.push(';');
+                self.line(&text);
+            }
+            AstNode::Schema { key, ty, .. } => {
+                self.line(&format!("schema {}: {};", ident_or_quote(key), ty))
+            }
+            AstNode::Expect {
+                condition, action, ..
+            } => match action {
+                Some(action) => {
+                    let mut printer = Printer::new(None);
+                    printer.node(action);
+                    let action = printer.out.trim_end();
+                    self.line(&format!("expect {} after {}", condition, action));
+                }
+                None => self.line(&format!("expect {};", condition)),
+            },
+            AstNode::Magnet { label, power, .. } => {
+                self.line(&format!("magnet {} {};", quote(label), number(*power)))
+            }
+            AstNode::Department { name, priority, .. } => {
+                self.line(&format!("department {} {};", name, number(*priority)))
+            }
+            AstNode::Reflect { .. } => self.line("reflect;"),
+            AstNode::Axiom {
+                name, expression, ..
+            } => self.line(&format!("axiom {}: {};", name, quote(expression))),
+            AstNode::Causality {
+                cause,
+                effect,
+                c_type,
+                ..
+            } => self.line(&format!("{} causes {} via {};", cause, effect, c_type)),
+            AstNode::Property { key, value, .. } => {
+                match value {
+                    EntrenchValue::String(text) if is_free_text(text) => {
+                        self.line(&format!("{}: {}", key, text));
+                        // Free text runs to the end of the line.
+                        self.trailable = false;
+                    }
+                    value => self.line(&format!("{}: {}", key, literal(value))),
+                }
+            }
+            AstNode::Marker { name, value, .. } => match value {
+                Some(value) if value.is_empty() => self.line(&format!("[{}:]", name)),
+                Some(value) => self.line(&format!("[{}: {}]", name, value)),
+                None => self.line(&format!("[{}]", name)),
+            },
+            AstNode::Section { name, lines, .. } => {
+                self.line(&format!("[{}]:", name));
+                for line in lines {
+                    self.line(line);
+                }
+            }
+            AstNode::Proclamation { text, .. } => self.line(&format!("{};", quote(text))),
+            AstNode::Import { path, alias, .. } => match alias {
+                Some(alias) => self.line(&format!("import {} as {};", quote(path), alias)),
+                None => self.line(&format!("include {};", quote(path))),
+            },
+            AstNode::Native { source, .. } => self.line(source),
+        }
+    }
+
+    fn nested(&mut self, f: impl FnOnce(&mut Self)) {
+        self.depth += 1;
+        self.fresh = true;
+        f(self);
+        self.depth -= 1;
+        self.fresh = false;
+        self.blank = false;
+    }
+
+    /// Writes one logical line; continuation lines of multi-line text are
+    /// kept verbatim.
+    fn line(&mut self, text: &str) {
+        if self.blank && !self.fresh {
+            self.out.push('\n');
+        }
+        self.blank = false;
+        self.fresh = false;
+        self.trailable = true;
+        self.out.push_str(&INDENT.repeat(self.depth));
+        self.out.push_str(text.trim_end());
+        self.out.push('\n');
+    }
+}
+
+fn is_block(node: &AstNode) -> bool {
+    matches!(
+        node,
+        AstNode::Manifold { .. }
+            | AstNode::Template { .. }
+            | AstNode::Body { .. }
+            | AstNode::Spirit { .. }
+            | AstNode::Native { .. }
+    )
+}
+
+/// Offset just past the `{` that opens a manifold.
+fn manifold_open(source: &str, node: &AstNode) -> usize {
+    let span = node.span();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in source[span.start..span.end].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' if !in_string => return span.start + i + 1,
+            _ => {}
+        }
+    }
+    span.end
+}
+
+/// The `//` comment in a run of trivia, if any.
+fn find_comment(trivia: &str) -> Option<&str> {
+    trivia.find("//").map(|at| trivia[at..].trim_end())
+}
+
+/// Every `//` comment in a piece of code, skipping string literals.
+fn scan_comments(code: &str) -> Vec<&str> {
+    let mut comments = Vec::new();
+    let mut in_string = false;
+    let mut chars = code.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            '/' if !in_string && code[i..].starts_with("//") => {
+                let end = code[i..].find('\n').map_or(code.len(), |n| i + n);
+                comments.push(code[i..end].trim_end());
+                while chars.peek().is_some_and(|(j, _)| *j < end) {
+                    chars.next();
+                }
+            }
+            _ => {}
+        }
+    }
+    comments
+}
+
+fn quote(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\0' => out.push_str("\\0"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn longest_backtick_run(text: &str) -> usize {
+    text.split(|c| c != '`').map(str::len).max().unwrap_or(0)
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn ident_or_quote(text: &str) -> String {
+    if is_identifier(text) {
+        text.to_string()
+    } else {
+        quote(text)
+    }
+}
+
+/// Whether a property value can be written without quotes and still read
+/// back as the same text rather than as a literal.
+fn is_free_text(text: &str) -> bool {
+    !text.is_empty()
+        && text == text.trim()
+        && !text.contains('\n')
+        && !text.ends_with(';')
+        && (text.contains('"')
+            || !text.starts_with(|c: char| c == '[' || c == '-' || c.is_ascii_digit()))
+}
+
+/// Shortest decimal that reads back as the same value, always with a
+/// fractional part: `528.0`, `1.618`.
+fn number(value: f64) -> String {
+    let text = value.to_string();
+    if text.contains('.') {
+        text
+    } else {
+        format!("{}.0", text)
+    }
+}
+
+fn number_f32(value: f32) -> String {
+    let text = value.to_string();
+    if text.contains('.') {
+        text
+    } else {
+        format!("{}.0", text)
+    }
+}
+
+fn frequency_text(frequency: &Frequency) -> String {
+    match frequency {
+        Frequency::Hz(hz) => number(*hz),
+        Frequency::Signature(bytes) => {
+            let digits: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+            // Long signatures are grouped by byte, as in `AETERNA_ANIMA.soul`.
+            let separator = if bytes.len() > 8 { "_" } else { "" };
+            format!("0x{}", digits.join(separator))
+        }
+    }
+}
+
+/// An argument as written in an instantiation, and as substituted for its
+/// placeholder when the template is expanded.
+pub(crate) fn template_arg(arg: &TemplateArg) -> String {
+    match arg {
+        TemplateArg::Number(value) => number(*value),
+        TemplateArg::String(text) => quote(text),
+        TemplateArg::Ident(name) => name.clone(),
+        TemplateArg::Vector(values) => literal(&EntrenchValue::Vector(values.clone())),
+    }
+}
+
+fn literal(value: &EntrenchValue) -> String {
+    match value {
+        EntrenchValue::Vector(values) => {
+            let items: Vec<String> = values.iter().map(|v| number_f32(*v)).collect();
+            format!("[{}]", items.join(", "))
+        }
+        EntrenchValue::StringList(items) => {
+            let items: Vec<String> = items.iter().map(|s| quote(s)).collect();
+            format!("[{}]", items.join(", "))
+        }
+        EntrenchValue::String(text) => quote(text),
+        EntrenchValue::Number(value) => number_f32(*value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::span::Span;
+
+    fn strip_spans(nodes: &mut [AstNode]) {
+        for node in nodes {
+            match node {
+                AstNode::Manifold { body, span, .. } => {
+                    *span = Span::default();
+                    strip_spans(body);
+                }
+                AstNode::Expect { action, span, .. } => {
+                    *span = Span::default();
+                    if let Some(action) = action {
+                        strip_spans(std::slice::from_mut(&mut **action));
+                    }
+                }
+                AstNode::Immortal { span, .. }
+                | AstNode::Body { span, .. }
+                | AstNode::Spirit { span, .. }
+                | AstNode::Template { span, .. }
+                | AstNode::Instance { span, .. }
+                | AstNode::Resonate { span, .. }
+                | AstNode::Collapse { span, .. }
+                | AstNode::Entrench { span, .. }
+                | AstNode::Schema { span, .. }
+                | AstNode::Magnet { span, .. }
+                | AstNode::Department { span, .. }
+                | AstNode::Reflect { span, .. }
+                | AstNode::Axiom { span, .. }
+                | AstNode::Causality { span, .. }
+                | AstNode::Property { span, .. }
+                | AstNode::Marker { span, .. }
+                | AstNode::Section { span, .. }
+                | AstNode::Proclamation { span, .. }
+                | AstNode::Import { span, .. }
+                | AstNode::Native { span, .. } => *span = Span::default(),
+            }
+        }
+    }
+
+    fn parse_bare(source: &str) -> Vec<AstNode> {
+        let mut ast = parse_soul(source).unwrap();
+        strip_spans(&mut ast);
+        ast
+    }
+
+    #[test]
+    fn canonical_layout() {
+        let source = "// seed\nmanifold CORE{resonate RESONANCE(0x4121);entrench MISSION(\"Global Ingestion\") // why\n\n\n\n  collapse ENTROPY(0.0000);\n   manifold \"Inner Core\" { }}\n[LOGOS: MANIFESTED]\n";
+        let expected = "// seed\nmanifold CORE {\n    resonate RESONANCE 0x4121;\n    entrench MISSION \"Global Ingestion\"; // why\n\n    collapse ENTROPY 0.0;\n    manifold \"Inner Core\" {}\n}\n\n[LOGOS: MANIFESTED]\n";
+        assert_eq!(format_soul(source).unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_fenced_bodies_and_escapes() {
+        let source = "manifold M {\n    body Raw ```js\n  if (a) { b(\"```\") }\n\n    ```\n    spirit S { goal \"tab\\there\" context \"c\" weight: 0.5 }\n}\n";
+        let expected = "manifold M {\n    body Raw ````js\n  if (a) { b(\"```\") }\n\n    ````\n    spirit S {\n        goal: \"tab\\there\"\n        context: \"c\"\n        weight: 0.5\n    }\n}\n";
+        let formatted = format_soul(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(parse_bare(&formatted), parse_bare(source));
+    }
+
+    #[test]
+    fn keeps_templates() {
+        let source = "template T(n: number,s: string){magnet $s $n;}\nmanifold A=T(1, \"x\")\n";
+        let expected = "template T(n: number, s: string) {\n    magnet $s $n;\n}\n\nmanifold A = T(1.0, \"x\");\n";
+        let formatted = format_soul(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(parse_bare(&formatted), parse_bare(source));
+    }
+
+    #[test]
+    fn keeps_schemas() {
+        let source = "schema EMBED:vector [ 128 ]\nschema \"MISSION\" : string;\n";
+        let expected = "schema EMBED: vector[128];\nschema MISSION: string;\n";
+        let formatted = format_soul(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(parse_bare(&formatted), parse_bare(source));
+    }
+
+    #[test]
+    fn keeps_expectations() {
+        let source = "manifold CORE {\nexpect entropy<=0.5   after collapse CORE 0.2\n expect resonance == 1;}\n";
+        let expected = "manifold CORE {\n    expect entropy<=0.5 after collapse CORE 0.2;\n    expect resonance == 1;\n}\n";
+        let formatted = format_soul(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(parse_bare(&formatted), parse_bare(source));
+    }
+
+    #[test]
+    fn round_trips_every_soul_in_the_repo() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        for dir in ["", "LwaS", "AETERNA_VAULT/SOULS", "OMEGA_VAULT"] {
+            let Ok(entries) = std::fs::read_dir(root.join(dir)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.extension().is_none_or(|e| e != "soul") {
+                    continue;
+                }
+                let source = std::fs::read_to_string(&path).unwrap();
+                let ast = parse_bare(&source);
+
+                assert_eq!(parse_bare(&print_soul(&ast)), ast, "{}", path.display());
+
+                let formatted = format_soul(&source).unwrap();
+                assert_eq!(parse_bare(&formatted), ast, "{}", path.display());
+                assert_eq!(
+                    format_soul(&formatted).unwrap(),
+                    formatted,
+                    "{}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
@@ -1,2 +1,25 @@
+pub mod causality;
+pub mod diagnostic;
+pub mod doc;
+pub mod expr;
+pub mod format;
+pub mod module;
 pub mod parser;
-pub use parser::{parse_soul, AstNode, EntrenchValue};
+pub mod schema;
+pub mod semantic;
+pub mod span;
+pub mod template;
+pub use causality::{CausalEdge, CausalityGraph, CausalityType};
+pub use diagnostic::{Diagnostic, Severity, SourceMap};
+pub use doc::{DocEntry, LinkDoc, ManifoldDoc, SoulDocs};
+pub use expr::{parse_expression, Entity, Environment, Expr, ExprError, Type, Value};
+pub use format::{format_soul, print_soul};
+pub use module::{load_program, resolve_imports, ModuleError, ResolvedProgram};
+pub use parser::{
+    detached_docs, parse_soul, parse_soul_with_diagnostics, AstNode, EntrenchValue, Frequency,
+    ParamType, ParseError, SchemaType, TemplateArg, TemplateParam, STATEMENT_KEYWORDS,
+};
+pub use schema::SchemaSet;
+pub use semantic::{analyze, Analysis, Symbol, SymbolKind, SymbolTable};
+pub use span::Span;
+pub use template::expand_templates;
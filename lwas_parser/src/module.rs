@@ -0,0 +1,362 @@
+use crate::diagnostic::SourceMap;
+use crate::parser::{parse_soul, AstNode, ParseError};
+use crate::span::Span;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ModuleError {
+    #[error("cannot read `{}`: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("`{}` does not parse: {error}", path.display())]
+    Parse {
+        path: PathBuf,
+        /// Text of the module, for rendering `error`'s diagnostics.
+        text: String,
+        error: Box<ParseError>,
+    },
+    #[error("import cycle: {}", .0.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(" -> "))]
+    Cycle(Vec<PathBuf>),
+    #[error("`{path}` must be imported at the top level of a soul")]
+    NestedImport {
+        path: String,
+        span: Span,
+        /// Files imported so far, for rendering `span` if it is in one.
+        sources: SourceMap,
+    },
+}
+
+/// A soul with every `import`/`include` replaced by the statements it names.
+///
+/// Spans of merged statements point into the file they came from, which
+/// `sources` holds.
+#[derive(Debug, Clone)]
+pub struct ResolvedProgram {
+    pub ast: Vec<AstNode>,
+    /// Every file merged into the program, in load order.
+    pub files: Vec<PathBuf>,
+    pub sources: SourceMap,
+}
+
+/// Loads a soul file and everything it imports.
+pub fn load_program(entry: &Path) -> Result<ResolvedProgram, ModuleError> {
+    let mut loader = Loader::default();
+    let ast = loader.file(entry, true)?;
+    Ok(ResolvedProgram {
+        ast,
+        files: loader.files,
+        sources: loader.sources,
+    })
+}
+
+/// Resolves the imports of an already parsed soul, relative to `base`.
+pub fn resolve_imports(ast: Vec<AstNode>, base: &Path) -> Result<ResolvedProgram, ModuleError> {
+    let mut loader = Loader::default();
+    let ast = loader.splice(ast, base)?;
+    Ok(ResolvedProgram {
+        ast,
+        files: loader.files,
+        sources: loader.sources,
+    })
+}
+
+#[derive(Default)]
+struct Loader {
+    /// Files being loaded, innermost last.
+    stack: Vec<PathBuf>,
+    files: Vec<PathBuf>,
+    /// Aliases of the imports being loaded, outermost first.
+    namespace: Vec<String>,
+    /// A file is merged once per namespace it is imported into.
+    merged: HashSet<(PathBuf, String)>,
+    sources: SourceMap,
+    /// Id in `sources` of every imported file.
+    ids: HashMap<PathBuf, usize>,
+}
+
+impl Loader {
+    /// Loads a file, tagging its spans with a `sources` id unless it is the
+    /// `root` of the program.
+    fn file(&mut self, path: &Path, root: bool) -> Result<Vec<AstNode>, ModuleError> {
+        let canonical = canonicalize(path)?;
+        if let Some(start) = self.stack.iter().position(|p| *p == canonical) {
+            let mut chain = self.stack[start..].to_vec();
+            chain.push(canonical);
+            return Err(ModuleError::Cycle(chain));
+        }
+
+        let text = fs::read_to_string(&canonical).map_err(|source| ModuleError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let mut ast = match parse_soul(&text) {
+            Ok(ast) => ast,
+            Err(error) => {
+                return Err(ModuleError::Parse {
+                    path: path.to_path_buf(),
+                    text,
+                    error: Box::new(error),
+                })
+            }
+        };
+        if !root {
+            let id = match self.ids.get(&canonical) {
+                Some(id) => *id,
+                None => {
+                    let id = self.sources.add(path.display().to_string(), text);
+                    self.ids.insert(canonical.clone(), id);
+                    id
+                }
+            };
+            relabel(&mut ast, id);
+        }
+
+        let base = canonical.parent().unwrap_or(Path::new(".")).to_path_buf();
+        self.stack.push(canonical.clone());
+        if !self.files.contains(&canonical) {
+            self.files.push(canonical);
+        }
+        let resolved = self.splice(ast, &base);
+        self.stack.pop();
+        resolved
+    }
+
+    fn splice(&mut self, ast: Vec<AstNode>, base: &Path) -> Result<Vec<AstNode>, ModuleError> {
+        let mut program = Vec::new();
+        for node in ast {
+            match node {
+                AstNode::Import { path, alias, .. } => {
+                    let target = base.join(&path);
+                    let mut scope = self.namespace.clone();
+                    scope.extend(alias.clone());
+                    let key = (canonicalize(&target)?, scope.join("::"));
+                    if !self.merged.insert(key) {
+                        continue;
+                    }
+
+                    let outer = std::mem::replace(&mut self.namespace, scope);
+                    let module = self.file(&target, false);
+                    self.namespace = outer;
+                    match alias {
+                        Some(alias) => program.extend(namespace(module?, &alias)),
+                        None => program.extend(module?),
+                    }
+                }
+                AstNode::Manifold { ref body, .. } => {
+                    if let Some((path, span)) = find_import(body) {
+                        return Err(ModuleError::NestedImport {
+                            path,
+                            span,
+                            sources: self.sources.clone(),
+                        });
+                    }
+                    program.push(node);
+                }
+                node => program.push(node),
+            }
+        }
+        Ok(program)
+    }
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, ModuleError> {
+    fs::canonicalize(path).map_err(|source| ModuleError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Points the spans of a module's statements at its `sources` id.
+fn relabel(nodes: &mut [AstNode], file: usize) {
+    for node in nodes {
+        node.span_mut().file = file;
+        match node {
+            AstNode::Manifold { body, .. } => relabel(body, file),
+            AstNode::Expect {
+                action: Some(action),
+                ..
+            } => relabel(std::slice::from_mut(&mut **action), file),
+            _ => {}
+        }
+    }
+}
+
+fn find_import(nodes: &[AstNode]) -> Option<(String, Span)> {
+    nodes.iter().find_map(|node| match node {
+        AstNode::Import { path, span, .. } => Some((path.clone(), *span)),
+        AstNode::Manifold { body, .. } => find_import(body),
+        _ => None,
+    })
+}
+
+/// Prefixes the module's top-level manifolds and template instances with
+/// `alias::`, along with every reference to them inside the module.
+fn namespace(mut module: Vec<AstNode>, alias: &str) -> Vec<AstNode> {
+    let manifolds: HashSet<String> = module
+        .iter()
+        .filter_map(|node| match node {
+            AstNode::Manifold { name, .. } | AstNode::Instance { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    let rename = |name: &mut String| {
+        if manifolds.contains(name) {
+            *name = format!("{}::{}", alias, name);
+        }
+    };
+
+    for node in &mut module {
+        if let AstNode::Manifold { name, .. } | AstNode::Instance { name, .. } = node {
+            rename(name);
+        }
+        rename_references(node, &rename);
+    }
+    module
+}
+
+fn rename_references(node: &mut AstNode, rename: &impl Fn(&mut String)) {
+    match node {
+        AstNode::Manifold { body, .. } => {
+            for child in body {
+                rename_references(child, rename);
+            }
+        }
+        AstNode::Resonate {
+            target, partner, ..
+        } => {
+            rename(target);
+            if let Some(partner) = partner {
+                rename(partner);
+            }
+        }
+        AstNode::Collapse { target, .. } => rename(target),
+        AstNode::Entrench { key, .. } => rename(key),
+        AstNode::Expect {
+            action: Some(action),
+            ..
+        } => rename_references(action, rename),
+        AstNode::Causality { cause, effect, .. } => {
+            rename(cause);
+            rename(effect);
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::Diagnostic;
+
+    /// A scratch directory of soul files, removed on drop.
+    struct Souls(PathBuf);
+
+    impl Souls {
+        fn new(name: &str, files: &[(&str, &str)]) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("lwas_modules_{}_{}", name, std::process::id()));
+            for (path, text) in files {
+                let path = dir.join(path);
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(path, text).unwrap();
+            }
+            Self(dir)
+        }
+    }
+
+    impl Drop for Souls {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn merges_and_namespaces_imports() {
+        let souls = Souls::new(
+            "merge",
+            &[
+                ("main.soul", "import \"vault/laws.soul\" as laws;\ninclude \"common.soul\";\ninclude \"common.soul\";\nresonate CORE with \"laws::UniversalLaws\";\n"),
+                ("vault/laws.soul", "include \"../common.soul\";\nmanifold UniversalLaws { curvature: 0.5 }\ncollapse UniversalLaws;\n"),
+                ("common.soul", "manifold CORE {}\n"),
+            ],
+        );
+        let program = load_program(&souls.0.join("main.soul")).unwrap();
+
+        assert_eq!(program.files.len(), 3);
+        let names: Vec<String> = program
+            .ast
+            .iter()
+            .map(|node| match node {
+                AstNode::Manifold { name, .. } => format!("manifold {}", name),
+                AstNode::Collapse { target, .. } => format!("collapse {}", target),
+                AstNode::Resonate { target, .. } => format!("resonate {}", target),
+                other => format!("{:?}", other),
+            })
+            .collect();
+        // `common.soul` is merged once into `laws` and once into the root.
+        assert_eq!(
+            names,
+            [
+                "manifold laws::CORE",
+                "manifold laws::UniversalLaws",
+                "collapse laws::UniversalLaws",
+                "manifold CORE",
+                "resonate CORE",
+            ]
+        );
+    }
+
+    #[test]
+    fn spans_point_into_their_own_file() {
+        let souls = Souls::new(
+            "spans",
+            &[
+                ("main.soul", "include \"laws.soul\";\nmanifold CORE {}\n"),
+                (
+                    "laws.soul",
+                    "// laws\nmanifold Laws {\n    curvature: 0.5\n}\n",
+                ),
+            ],
+        );
+        let program = load_program(&souls.0.join("main.soul")).unwrap();
+        let AstNode::Manifold { body, span, .. } = &program.ast[0] else {
+            panic!("expected the imported manifold");
+        };
+        assert_eq!((span.file, span.line), (1, 2));
+        assert_eq!(body[0].span().file, 1);
+        assert_eq!(program.ast[1].span().file, 0);
+
+        let (origin, _) = program.sources.get(1).unwrap();
+        assert!(origin.ends_with("laws.soul"));
+        let diagnostic = Diagnostic::error("too curved", body[0].span());
+        let rendered = program.sources.render_all(&[diagnostic], "", "main.soul");
+        assert!(
+            rendered.contains(&format!(" --> {}:3:5", origin)),
+            "{}",
+            rendered
+        );
+        assert!(rendered.contains("3 |     curvature: 0.5"), "{}", rendered);
+    }
+
+    #[test]
+    fn detects_import_cycles() {
+        let souls = Souls::new(
+            "cycle",
+            &[
+                ("a.soul", "import \"b.soul\" as b;\n"),
+                ("b.soul", "include \"a.soul\";\n"),
+            ],
+        );
+        let err = load_program(&souls.0.join("a.soul")).unwrap_err();
+        let ModuleError::Cycle(chain) = err else {
+            panic!("expected a cycle, got {}", err);
+        };
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[0], chain[2]);
+    }
+}
@@ -1,219 +1,1435 @@
+use crate::diagnostic::Diagnostic;
+use crate::span::{LineIndex, Span};
+use pest::error::{ErrorVariant, InputLocation};
+use pest::iterators::Pair;
 use pest::Parser;
 use pest_derive::Parser;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Parser)]
 #[grammar = "lwas.pest"]
 pub struct LwasParser;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A statement. Every variant carries `doc`, the `///` comment lines right
+/// above it with the markers stripped, joined by `\n`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum AstNode {
     Immortal {
         name: String,
         value: String,
+        doc: Option<String>,
+        span: Span,
     },
     Body {
         name: String,
         content: String,
+        /// `Some` for a fenced body, holding its language tag (possibly empty).
+        fence: Option<String>,
+        doc: Option<String>,
+        span: Span,
     },
     Spirit {
         name: String,
         goal: String,
+        context: Option<String>,
+        /// Every other `key: value` field, in declaration order.
+        fields: Vec<(String, EntrenchValue)>,
+        doc: Option<String>,
+        span: Span,
     },
     Manifold {
         name: String,
         body: Vec<AstNode>,
+        doc: Option<String>,
+        span: Span,
+    },
+    /// `template Region(weight: number) { ... }`
+    Template {
+        name: String,
+        params: Vec<TemplateParam>,
+        /// Text between the braces, with `$weight` placeholders.
+        body: String,
+        doc: Option<String>,
+        span: Span,
+    },
+    /// `manifold EU = Region(0.8);`, replaced by a `Manifold` when templates
+    /// are expanded.
+    Instance {
+        name: String,
+        template: String,
+        args: Vec<TemplateArg>,
+        doc: Option<String>,
+        span: Span,
     },
     Resonate {
         target: String,
-        frequency: f64,
+        frequency: Frequency,
+        /// `resonate A with B`
+        partner: Option<String>,
+        doc: Option<String>,
+        span: Span,
     },
     Collapse {
         target: String,
         entropy_threshold: f64,
+        /// Raw text of a `where` clause.
+        condition: Option<String>,
+        doc: Option<String>,
+        span: Span,
     },
     Entrench {
         key: String,
-        value: EntrenchValue,
+        /// `None` for a bare `entrench KEY`.
+        value: Option<EntrenchValue>,
+        doc: Option<String>,
+        span: Span,
+    },
+    /// `schema EMBED: vector[128];`
+    Schema {
+        key: String,
+        ty: SchemaType,
+        doc: Option<String>,
+        span: Span,
+    },
+    /// `expect entropy <= 0.5 after collapse CORE;`
+    Expect {
+        /// An axiom expression over the organism's state.
+        condition: String,
+        /// The statement run right before the check.
+        action: Option<Box<AstNode>>,
+        doc: Option<String>,
+        span: Span,
     },
     Magnet {
         label: String,
         power: f64,
+        doc: Option<String>,
+        span: Span,
     },
     Department {
         name: String,
         priority: f64,
+        doc: Option<String>,
+        span: Span,
+    },
+    Reflect {
+        doc: Option<String>,
+        span: Span,
     },
-    Reflect,
     Axiom {
         name: String,
         expression: String,
+        doc: Option<String>,
+        span: Span,
     },
     Causality {
         cause: String,
         effect: String,
         c_type: String,
+        doc: Option<String>,
+        span: Span,
+    },
+    /// `curvature: 0.618` or a header field such as `STATUS: ACTIVE`.
+    Property {
+        key: String,
+        value: EntrenchValue,
+        doc: Option<String>,
+        span: Span,
+    },
+    /// `[LOGOS: MANIFESTED]`
+    Marker {
+        name: String,
+        value: Option<String>,
+        doc: Option<String>,
+        span: Span,
+    },
+    /// `[DECREE]:` followed by free-form lines.
+    Section {
+        name: String,
+        lines: Vec<String>,
+        doc: Option<String>,
+        span: Span,
+    },
+    /// A bare string statement.
+    Proclamation {
+        text: String,
+        doc: Option<String>,
+        span: Span,
+    },
+    /// `import "path.soul" as alias;`, or `include "path.soul";` with no alias.
+    Import {
+        path: String,
+        alias: Option<String>,
+        doc: Option<String>,
+        span: Span,
+    },
+    /// A Rust function embedded in the soul, kept verbatim.
+    Native {
+        name: String,
+        source: String,
+        doc: Option<String>,
+        span: Span,
     },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl AstNode {
+    pub fn span(&self) -> Span {
+        match self {
+            AstNode::Immortal { span, .. }
+            | AstNode::Body { span, .. }
+            | AstNode::Spirit { span, .. }
+            | AstNode::Manifold { span, .. }
+            | AstNode::Template { span, .. }
+            | AstNode::Instance { span, .. }
+            | AstNode::Resonate { span, .. }
+            | AstNode::Collapse { span, .. }
+            | AstNode::Entrench { span, .. }
+            | AstNode::Schema { span, .. }
+            | AstNode::Expect { span, .. }
+            | AstNode::Magnet { span, .. }
+            | AstNode::Department { span, .. }
+            | AstNode::Reflect { span, .. }
+            | AstNode::Axiom { span, .. }
+            | AstNode::Causality { span, .. }
+            | AstNode::Property { span, .. }
+            | AstNode::Marker { span, .. }
+            | AstNode::Section { span, .. }
+            | AstNode::Proclamation { span, .. }
+            | AstNode::Import { span, .. }
+            | AstNode::Native { span, .. } => *span,
+        }
+    }
+
+    pub fn span_mut(&mut self) -> &mut Span {
+        match self {
+            AstNode::Immortal { span, .. }
+            | AstNode::Body { span, .. }
+            | AstNode::Spirit { span, .. }
+            | AstNode::Manifold { span, .. }
+            | AstNode::Template { span, .. }
+            | AstNode::Instance { span, .. }
+            | AstNode::Resonate { span, .. }
+            | AstNode::Collapse { span, .. }
+            | AstNode::Entrench { span, .. }
+            | AstNode::Schema { span, .. }
+            | AstNode::Expect { span, .. }
+            | AstNode::Magnet { span, .. }
+            | AstNode::Department { span, .. }
+            | AstNode::Reflect { span, .. }
+            | AstNode::Axiom { span, .. }
+            | AstNode::Causality { span, .. }
+            | AstNode::Property { span, .. }
+            | AstNode::Marker { span, .. }
+            | AstNode::Section { span, .. }
+            | AstNode::Proclamation { span, .. }
+            | AstNode::Import { span, .. }
+            | AstNode::Native { span, .. } => span,
+        }
+    }
+
+    pub fn doc(&self) -> Option<&str> {
+        match self {
+            AstNode::Immortal { doc, .. }
+            | AstNode::Body { doc, .. }
+            | AstNode::Spirit { doc, .. }
+            | AstNode::Manifold { doc, .. }
+            | AstNode::Template { doc, .. }
+            | AstNode::Instance { doc, .. }
+            | AstNode::Resonate { doc, .. }
+            | AstNode::Collapse { doc, .. }
+            | AstNode::Entrench { doc, .. }
+            | AstNode::Schema { doc, .. }
+            | AstNode::Expect { doc, .. }
+            | AstNode::Magnet { doc, .. }
+            | AstNode::Department { doc, .. }
+            | AstNode::Reflect { doc, .. }
+            | AstNode::Axiom { doc, .. }
+            | AstNode::Causality { doc, .. }
+            | AstNode::Property { doc, .. }
+            | AstNode::Marker { doc, .. }
+            | AstNode::Section { doc, .. }
+            | AstNode::Proclamation { doc, .. }
+            | AstNode::Import { doc, .. }
+            | AstNode::Native { doc, .. } => doc.as_deref(),
+        }
+    }
+}
+
+/// The type of a template parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ParamType {
+    Number,
+    String,
+    Ident,
+    Vector,
+}
+
+impl fmt::Display for ParamType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ParamType::Number => "number",
+            ParamType::String => "string",
+            ParamType::Ident => "ident",
+            ParamType::Vector => "vector",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateParam {
+    pub name: String,
+    pub ty: ParamType,
+}
+
+/// An argument of a template instantiation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TemplateArg {
+    Number(f64),
+    String(String),
+    Ident(String),
+    Vector(Vec<f32>),
+}
+
+impl TemplateArg {
+    pub fn ty(&self) -> ParamType {
+        match self {
+            TemplateArg::Number(_) => ParamType::Number,
+            TemplateArg::String(_) => ParamType::String,
+            TemplateArg::Ident(_) => ParamType::Ident,
+            TemplateArg::Vector(_) => ParamType::Vector,
+        }
+    }
+}
+
+/// The shape a `schema` requires of a key's entrenched values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SchemaType {
+    String,
+    Number,
+    /// A list of strings.
+    List,
+    /// A vector, of exactly this many dimensions if given.
+    Vector(Option<usize>),
+}
+
+impl SchemaType {
+    /// Describes `value` if it does not fit the schema.
+    pub fn check(&self, value: &EntrenchValue) -> Result<(), String> {
+        let fits = match (self, value) {
+            (SchemaType::String, EntrenchValue::String(_))
+            | (SchemaType::Number, EntrenchValue::Number(_))
+            | (SchemaType::List, EntrenchValue::StringList(_))
+            | (SchemaType::Vector(None), EntrenchValue::Vector(_)) => true,
+            (SchemaType::Vector(Some(dims)), EntrenchValue::Vector(v)) => v.len() == *dims,
+            _ => false,
+        };
+        if fits {
+            return Ok(());
+        }
+        Err(match value {
+            EntrenchValue::String(_) => "a string".to_string(),
+            EntrenchValue::Number(_) => "a number".to_string(),
+            EntrenchValue::StringList(l) => format!("a list of {} string(s)", l.len()),
+            EntrenchValue::Vector(v) => format!("a vector of {} dimension(s)", v.len()),
+        })
+    }
+}
+
+impl fmt::Display for SchemaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaType::String => write!(f, "string"),
+            SchemaType::Number => write!(f, "number"),
+            SchemaType::List => write!(f, "list"),
+            SchemaType::Vector(None) => write!(f, "vector"),
+            SchemaType::Vector(Some(dims)) => write!(f, "vector[{}]", dims),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EntrenchValue {
     Vector(Vec<f32>),
+    StringList(Vec<String>),
     String(String),
     Number(f32),
 }
 
+/// Operand of a `resonate` statement. Hex literals such as `0x4121` or the
+/// AUTHORITY signature in `AETERNA_ANIMA.soul` are kept byte-exact.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Frequency {
+    Hz(f64),
+    Signature(Vec<u8>),
+}
+
 #[derive(Error, Debug)]
 pub enum ParseError {
-    #[error("Parsing error: {0}")]
-    Pest(#[from] pest::error::Error<Rule>),
+    #[error("Parsing failed with {} error(s){}", .0.len(), first_at(.0))]
+    Diagnostics(Vec<Diagnostic>),
+}
+
+impl ParseError {
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        match self {
+            ParseError::Diagnostics(diagnostics) => diagnostics.clone(),
+        }
+    }
+}
+
+fn first_at(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .first()
+        .map_or(String::new(), |d| format!(", first at {}", d))
 }
 
+/// Keywords that open a statement; used to resynchronise after an error.
+pub const STATEMENT_KEYWORDS: &[&str] = &[
+    "immortal",
+    "body",
+    "spirit",
+    "manifold",
+    "resonate",
+    "collapse",
+    "entrench",
+    "magnet",
+    "department",
+    "reflect",
+    "axiom",
+    "import",
+    "include",
+    "pub",
+    "fn",
+    "template",
+    "schema",
+    "expect",
+];
+
 pub fn parse_soul(input: &str) -> Result<Vec<AstNode>, ParseError> {
-    let mut pairs = LwasParser::parse(Rule::program, input)?;
-    let program_pair = pairs.next().unwrap();
-    Ok(parse_statements(program_pair.into_inner()))
+    let (ast, diagnostics) = parse_soul_with_diagnostics(input);
+    if diagnostics.iter().any(Diagnostic::is_error) {
+        return Err(ParseError::Diagnostics(diagnostics));
+    }
+    Ok(ast)
 }
 
-fn parse_statements(pairs: pest::iterators::Pairs<Rule>) -> Vec<AstNode> {
-    let mut ast = Vec::new();
-    for pair in pairs {
-        match pair.as_rule() {
-            Rule::statement => {
-                let inner = pair.into_inner().next().unwrap();
-                match inner.as_rule() {
-                    Rule::immortal_decl => {
-                        let mut inner_rules = inner.into_inner();
-                        let name = inner_rules.next().unwrap().as_str().to_string();
-                        let value = inner_rules
-                            .next()
-                            .unwrap()
-                            .as_str()
-                            .trim_matches('"')
-                            .to_string();
-                        ast.push(AstNode::Immortal { name, value });
-                    }
-                    Rule::body_block => {
-                        let mut inner_rules = inner.into_inner();
-                        let name = inner_rules.next().unwrap().as_str().to_string();
-                        let content = inner_rules.next().unwrap().as_str().trim().to_string();
-                        ast.push(AstNode::Body { name, content });
-                    }
-                    Rule::spirit_block => {
-                        let mut inner_rules = inner.into_inner();
-                        let name = inner_rules.next().unwrap().as_str().to_string();
-                        let mut goal = String::new();
-                        for field in inner_rules {
-                            if field.as_rule() == Rule::string_literal {
-                                goal = field.as_str().trim_matches('"').to_string();
-                                break;
-                            }
-                        }
-                        ast.push(AstNode::Spirit { name, goal });
-                    }
-                    Rule::manifold_block => {
-                        let mut inner_rules = inner.into_inner();
-                        let name = inner_rules.next().unwrap().as_str().to_string();
-                        let body = parse_statements(inner_rules);
-                        ast.push(AstNode::Manifold { name, body });
-                    }
-                    Rule::resonate_stmt => {
-                        let mut inner_rules = inner.into_inner();
-                        let target = inner_rules.next().unwrap().as_str().to_string();
-                        let frequency = inner_rules
-                            .next()
-                            .map(|n| n.as_str().parse::<f64>().unwrap_or(1.0))
-                            .unwrap_or(1.0);
-                        ast.push(AstNode::Resonate { target, frequency });
-                    }
-                    Rule::collapse_stmt => {
-                        let mut inner_rules = inner.into_inner();
-                        let target = inner_rules.next().unwrap().as_str().to_string();
-                        let entropy_threshold = inner_rules
-                            .next()
-                            .map(|n| n.as_str().parse::<f64>().unwrap_or(0.5))
-                            .unwrap_or(0.5);
-                        ast.push(AstNode::Collapse {
-                            target,
-                            entropy_threshold,
+/// Parses as much of `input` as possible, recovering at statement
+/// boundaries so that every broken statement is reported in one pass.
+/// The returned AST contains all statements that parsed successfully.
+pub fn parse_soul_with_diagnostics(input: &str) -> (Vec<AstNode>, Vec<Diagnostic>) {
+    let index = LineIndex::new(input);
+    if let Ok(mut pairs) = LwasParser::parse(Rule::program, input) {
+        let ctx = Ctx {
+            index: &index,
+            base: 0,
+        };
+        let program_pair = pairs.next().unwrap();
+        return (
+            parse_statements(program_pair.into_inner(), &ctx),
+            Vec::new(),
+        );
+    }
+
+    // Diagnostics name the literal tokens (`;`, `{`, keywords) a statement
+    // expected, which pest only tracks with error detail on. The switch is
+    // process-wide; a source that parses never reaches it.
+    pest::set_error_detail(true);
+    let mut recovery = Recovery {
+        index: &index,
+        diagnostics: Vec::new(),
+    };
+    let (ast, _) = recovery.block(0, None);
+    (ast, recovery.diagnostics)
+}
+
+/// Converts pest spans, which are relative to the parsed slice, into
+/// absolute spans of the whole source.
+struct Ctx<'a> {
+    index: &'a LineIndex<'a>,
+    base: usize,
+}
+
+impl Ctx<'_> {
+    /// Span of a statement, without the whitespace and comments that implicit
+    /// skipping attaches when it ends in an optional `;`.
+    fn statement_span(&self, pair: &Pair<Rule>) -> Span {
+        let span = pair.as_span();
+        let text = span.as_str();
+        // After the last inner pair only punctuation and trivia remain.
+        let from = pair
+            .clone()
+            .into_inner()
+            .last()
+            .map_or(0, |p| p.as_span().end() - span.start());
+        let mut end = from;
+        let mut chars = text[from..].char_indices().map(|(i, c)| (from + i, c));
+        while let Some((i, c)) = chars.next() {
+            if text[i..].starts_with("//") {
+                chars.by_ref().find(|(_, c)| *c == '\n');
+            } else if !c.is_whitespace() {
+                end = i + c.len_utf8();
+            }
+        }
+        self.index
+            .span(self.base + span.start(), self.base + span.start() + end)
+    }
+}
+
+struct Recovery<'a> {
+    index: &'a LineIndex<'a>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Recovery<'_> {
+    /// Parses statements from `pos` until end of input or, when `open` holds
+    /// the offset of a `{`, until the matching `}`. Returns the statements and
+    /// the offset just past the block.
+    fn block(&mut self, mut pos: usize, open: Option<usize>) -> (Vec<AstNode>, usize) {
+        let source = self.index.source();
+        let mut ast = Vec::new();
+
+        loop {
+            pos = skip_trivia(source, pos);
+            if pos >= source.len() {
+                if let Some(open) = open {
+                    let (line, column) = self.index.line_col(open);
+                    self.diagnostics.push(Diagnostic::error(
+                        format!(
+                            "expected `}}` to close the block opened at {}:{}, found end of input",
+                            line, column
+                        ),
+                        self.index.span(source.len(), source.len()),
+                    ));
+                }
+                return (ast, source.len());
+            }
+
+            if source[pos..].starts_with('}') {
+                if open.is_some() {
+                    return (ast, pos + 1);
+                }
+                self.diagnostics.push(Diagnostic::error(
+                    "expected statement, found `}`",
+                    self.index.span(pos, pos + 1),
+                ));
+                pos += 1;
+                continue;
+            }
+
+            let rest = &source[pos..];
+            match LwasParser::parse(Rule::statement_entry, rest) {
+                Ok(mut pairs) => {
+                    let entry = pairs.next().unwrap();
+                    let end = pos + entry.as_span().end();
+                    let ctx = Ctx {
+                        index: self.index,
+                        base: pos,
+                    };
+                    let statement = entry.into_inner().next().unwrap();
+                    ast.push(parse_statement(
+                        statement.into_inner().next().unwrap(),
+                        &ctx,
+                    ));
+                    pos = end;
+                }
+                Err(err) => {
+                    if let Ok(mut head) = LwasParser::parse(Rule::manifold_head, rest) {
+                        let head = head.next().unwrap();
+                        let head_end = pos + head.as_span().end();
+                        let name = head.into_inner().next().unwrap().as_str().to_string();
+                        let (body, end) = self.block(head_end, Some(head_end - 1));
+                        ast.push(AstNode::Manifold {
+                            name,
+                            body,
+                            doc: doc_comment(source, pos).map(|(_, text)| text),
+                            span: self.index.span(pos, end),
                         });
+                        pos = end;
+                        continue;
                     }
-                    Rule::entrench_stmt => {
-                        let mut inner_rules = inner.into_inner();
-                        let key = inner_rules.next().unwrap().as_str().to_string();
-                        let val_pair = inner_rules.next().unwrap();
-                        let value = match val_pair.as_rule() {
-                            Rule::vector => {
-                                let vec = val_pair
-                                    .into_inner()
-                                    .map(|n| n.as_str().parse::<f32>().unwrap_or(0.0))
-                                    .collect();
-                                EntrenchValue::Vector(vec)
-                            }
-                            Rule::string_literal => EntrenchValue::String(
-                                val_pair.as_str().trim_matches('"').to_string(),
-                            ),
-                            Rule::number => EntrenchValue::Number(
-                                val_pair.as_str().parse::<f32>().unwrap_or(0.0),
-                            ),
-                            _ => EntrenchValue::String("".into()),
-                        };
-                        ast.push(AstNode::Entrench { key, value });
-                    }
-                    Rule::magnet_stmt => {
-                        let mut inner_rules = inner.into_inner();
-                        let label = inner_rules
-                            .next()
-                            .unwrap()
-                            .as_str()
-                            .trim_matches('"')
-                            .to_string();
-                        let power = inner_rules
-                            .next()
-                            .map(|n| n.as_str().parse::<f64>().unwrap_or(1.0))
-                            .unwrap_or(1.0);
-                        ast.push(AstNode::Magnet { label, power });
+
+                    let diagnostic = self.describe(&err, pos);
+                    let error_pos = diagnostic.span.start;
+                    self.diagnostics.push(diagnostic);
+                    pos = if error_pos > pos && starts_statement(source, error_pos) {
+                        error_pos
+                    } else {
+                        sync_point(source, pos)
+                    };
+                }
+            }
+        }
+    }
+
+    /// Turns a pest error for a statement starting at `base` into an
+    /// "expected X, found Y" diagnostic at the furthest point pest reached.
+    fn describe(&self, err: &pest::error::Error<Rule>, base: usize) -> Diagnostic {
+        let source = self.index.source();
+        let mut error_pos = base
+            + match err.location {
+                InputLocation::Pos(p) => p,
+                InputLocation::Span((p, _)) => p,
+            };
+
+        let mut expected: Vec<String> = Vec::new();
+        if let ErrorVariant::ParsingError { positives, .. } = &err.variant {
+            expected.extend(
+                positives
+                    .iter()
+                    .map(|rule| describe_rule(*rule).to_string()),
+            );
+        }
+        // Literal tokens (`;`, `{`, keywords) are only tracked by the detailed
+        // attempts, which may also have got further than the rule positives.
+        if let Some(attempts) = err.parse_attempts() {
+            let furthest = base + attempts.max_position;
+            let mut attempted = Vec::new();
+            for stack in attempts.call_stacks() {
+                if let Some(rule) = stack.deepest.get_rule() {
+                    attempted.push(describe_rule(*rule).to_string());
+                }
+            }
+            // Character ranges and built-ins are covered by the rule names;
+            // whitespace and comments are always allowed.
+            for token in attempts.expected_tokens() {
+                let token = token.to_string();
+                if token != "BUILTIN_RULE"
+                    && !token.contains("..")
+                    && !token.trim().is_empty()
+                    && token != "//"
+                {
+                    attempted.push(format!("`{}`", token));
+                }
+            }
+            // Attempts inside atomic rules, such as the character after a
+            // `\` in a string, are not tracked; keep the rule positives then.
+            if furthest > error_pos && !attempted.is_empty() {
+                error_pos = furthest;
+                expected = attempted;
+            } else if furthest == error_pos {
+                expected.extend(attempted);
+            }
+        }
+        // Only `escape` can expect `u{`: pest stopped right after a `\`.
+        if expected.iter().any(|e| e == "`u{`") && source[..error_pos].ends_with('\\') {
+            let width = source[error_pos..].chars().next().map_or(0, char::len_utf8);
+            return Diagnostic::error(
+                format!(
+                    "unknown escape sequence `{}`",
+                    &source[error_pos - 1..error_pos + width]
+                ),
+                self.index.span(error_pos - 1, error_pos + width),
+            );
+        }
+        expected.sort();
+        expected.dedup();
+        expected.retain(|e| e != "statement");
+
+        let expected = match expected.len() {
+            1..=4 => expected.join(" or "),
+            _ => "statement".to_string(),
+        };
+        let (found, width) = match found_token(source, error_pos) {
+            Some(token) => (format!("`{}`", token), token.len()),
+            None => ("end of input".to_string(), 0),
+        };
+        Diagnostic::error(
+            format!("expected {}, found {}", expected, found),
+            self.index.span(error_pos, error_pos + width),
+        )
+    }
+}
+
+fn describe_rule(rule: Rule) -> &'static str {
+    match rule {
+        Rule::identifier => "identifier",
+        Rule::string_literal => "string literal",
+        Rule::number => "number",
+        Rule::vector | Rule::string_list => "vector",
+        Rule::causality_type => "causality type",
+        Rule::param_type => "parameter type",
+        Rule::text_content | Rule::fence_text => "body text",
+        _ => "statement",
+    }
+}
+
+/// Skips whitespace and `//` comments.
+fn skip_trivia(source: &str, mut pos: usize) -> usize {
+    loop {
+        let rest = &source[pos..];
+        let trimmed = rest.trim_start();
+        pos += rest.len() - trimmed.len();
+        if trimmed.starts_with("//") {
+            pos += trimmed.find('\n').unwrap_or(trimmed.len());
+        } else {
+            return pos;
+        }
+    }
+}
+
+/// The `///` lines above the statement at `start`, if the statement opens
+/// its line; blank lines may separate them from it. Returns where the first
+/// of them begins and their text, with `///` and one following space
+/// removed. `////` is a plain comment.
+pub(crate) fn doc_comment(source: &str, start: usize) -> Option<(usize, String)> {
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    if !source[line_start..start].trim().is_empty() {
+        return None;
+    }
+    let mut first = line_start;
+    let mut lines = Vec::new();
+    while first > 0 {
+        let above = source[..first - 1].rfind('\n').map_or(0, |i| i + 1);
+        let text = source[above..first - 1].trim();
+        match doc_line(text) {
+            Some(line) => lines.push(line),
+            None if text.is_empty() && lines.is_empty() => {}
+            None => break,
+        }
+        first = above;
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    lines.reverse();
+    let indent = source[first..].len() - source[first..].trim_start().len();
+    Some((first + indent, lines.join("\n")))
+}
+
+/// The text of a trimmed `///` line.
+fn doc_line(text: &str) -> Option<String> {
+    let rest = text.strip_prefix("///")?;
+    if rest.starts_with('/') {
+        return None;
+    }
+    let rest = rest.strip_prefix(' ').unwrap_or(rest);
+    Some(rest.trim_end().to_string())
+}
+
+/// The `///` blocks of `source` that document no statement, such as a
+/// closing banner, with their spans and text. Bodies, templates, native
+/// functions and sections are verbatim, so `///` inside them does not count.
+pub fn detached_docs(source: &str) -> Vec<(Span, String)> {
+    fn visit(
+        nodes: &[AstNode],
+        source: &str,
+        attached: &mut HashSet<usize>,
+        verbatim: &mut Vec<Span>,
+    ) {
+        for node in nodes {
+            if let Some((first, _)) = doc_comment(source, node.span().start) {
+                attached.insert(first);
+            }
+            match node {
+                AstNode::Manifold { body, .. } => visit(body, source, attached, verbatim),
+                AstNode::Body { span, .. }
+                | AstNode::Template { span, .. }
+                | AstNode::Native { span, .. }
+                | AstNode::Section { span, .. } => verbatim.push(*span),
+                _ => {}
+            }
+        }
+    }
+
+    let (ast, _) = parse_soul_with_diagnostics(source);
+    let mut attached = HashSet::new();
+    let mut verbatim = Vec::new();
+    visit(&ast, source, &mut attached, &mut verbatim);
+
+    let index = LineIndex::new(source);
+    let mut docs = Vec::new();
+    let mut block: Option<(usize, usize, Vec<String>)> = None;
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let start = offset + line.len() - line.trim_start().len();
+        offset += line.len();
+        let inside = verbatim.iter().any(|s| s.start <= start && start < s.end);
+        match doc_line(line.trim()).filter(|_| !inside) {
+            Some(text) => {
+                let end = start + line.trim().len();
+                let (_, last, lines) = block.get_or_insert((start, end, Vec::new()));
+                *last = end;
+                lines.push(text);
+            }
+            None => docs.extend(block.take()),
+        }
+    }
+    docs.extend(block);
+    docs.into_iter()
+        .filter(|(first, _, _)| !attached.contains(first))
+        .map(|(first, end, lines)| (index.span(first, end), lines.join("\n")))
+        .collect()
+}
+
+fn starts_statement(source: &str, pos: usize) -> bool {
+    match found_token(source, pos) {
+        Some(word) => STATEMENT_KEYWORDS.contains(&word),
+        None => false,
+    }
+}
+
+/// The word (or single character) at `pos`, or `None` at end of input.
+fn found_token(source: &str, pos: usize) -> Option<&str> {
+    let rest = &source[pos..];
+    let first = rest.chars().next()?;
+    if first.is_alphanumeric() || first == '_' {
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(rest.len());
+        Some(&rest[..len])
+    } else {
+        Some(&rest[..first.len_utf8()])
+    }
+}
+
+/// Finds where parsing should resume after a broken statement starting at
+/// `pos`: just past the next `;` or balanced `{ ... }` block, or right before
+/// a `}` that closes the enclosing block.
+fn sync_point(source: &str, pos: usize) -> usize {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut chars = source[pos..].char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let at = pos + i;
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '/' if source[at..].starts_with("//") => {
+                while let Some((_, c)) = chars.peek() {
+                    if *c == '\n' {
+                        break;
                     }
-                    Rule::department_stmt => {
-                        let mut inner_rules = inner.into_inner();
-                        let name = inner_rules.next().unwrap().as_str().to_string();
-                        let priority = inner_rules
-                            .next()
-                            .map(|n| n.as_str().parse::<f64>().unwrap_or(1.0))
-                            .unwrap_or(1.0);
-                        ast.push(AstNode::Department { name, priority });
+                    chars.next();
+                }
+            }
+            ';' if depth == 0 => return at + 1,
+            '{' => depth += 1,
+            '}' if depth == 0 => return at,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return at + 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    source.len()
+}
+
+fn parse_statements(pairs: pest::iterators::Pairs<Rule>, ctx: &Ctx) -> Vec<AstNode> {
+    let mut ast = Vec::new();
+    for pair in pairs {
+        if pair.as_rule() == Rule::statement {
+            ast.push(parse_statement(pair.into_inner().next().unwrap(), ctx));
+        }
+    }
+    ast
+}
+
+fn parse_statement(inner: Pair<Rule>, ctx: &Ctx) -> AstNode {
+    let span = ctx.statement_span(&inner);
+    let doc = doc_comment(ctx.index.source(), span.start).map(|(_, text)| text);
+    match inner.as_rule() {
+        Rule::immortal_decl => {
+            let mut inner_rules = inner.into_inner();
+            let name = inner_rules.next().unwrap().as_str().to_string();
+            let value = unquote(inner_rules.next().unwrap().as_str());
+            AstNode::Immortal {
+                name,
+                value,
+                doc,
+                span,
+            }
+        }
+        Rule::body_block => {
+            let mut inner_rules = inner.into_inner();
+            let name = unquote(inner_rules.next().unwrap().as_str());
+            let body = inner_rules.next().unwrap();
+            let (content, fence) = match body.as_rule() {
+                Rule::fenced_body => {
+                    let mut fence = body.into_inner();
+                    let language = fence.next().unwrap().as_str().to_string();
+                    let text = fence.next().unwrap().as_str();
+                    // The text starts with the newline that ends the fence line.
+                    let text = text
+                        .strip_prefix('\n')
+                        .or_else(|| text.strip_prefix("\r\n"));
+                    (text.unwrap_or("").to_string(), Some(language))
+                }
+                _ => (body.as_str().trim().to_string(), None),
+            };
+            AstNode::Body {
+                name,
+                content,
+                fence,
+                doc,
+                span,
+            }
+        }
+        Rule::spirit_block => {
+            let mut inner_rules = inner.into_inner();
+            let name = unquote(inner_rules.next().unwrap().as_str());
+            let goal = unquote(inner_rules.next().unwrap().as_str());
+            let mut context = None;
+            let mut fields = Vec::new();
+            for field in inner_rules {
+                let mut parts = field.into_inner();
+                let key = parts.next().unwrap().as_str().to_string();
+                match parse_literal(parts.next().unwrap()) {
+                    EntrenchValue::String(text) if key == "context" && context.is_none() => {
+                        context = Some(text)
                     }
-                    Rule::reflection_stmt => {
-                        ast.push(AstNode::Reflect);
+                    value => fields.push((key, value)),
+                }
+            }
+            AstNode::Spirit {
+                name,
+                goal,
+                context,
+                fields,
+                doc,
+                span,
+            }
+        }
+        Rule::manifold_block => {
+            let mut inner_rules = inner.into_inner();
+            let name = unquote(inner_rules.next().unwrap().as_str());
+            let body = parse_statements(inner_rules, ctx);
+            AstNode::Manifold {
+                name,
+                body,
+                doc,
+                span,
+            }
+        }
+        Rule::template_decl => {
+            let mut inner_rules = inner.into_inner();
+            let name = inner_rules.next().unwrap().as_str().to_string();
+            let mut params = Vec::new();
+            let mut body = String::new();
+            for part in inner_rules {
+                match part.as_rule() {
+                    Rule::template_param => {
+                        let mut param = part.into_inner();
+                        let name = param.next().unwrap().as_str().to_string();
+                        let ty = match param.next().unwrap().as_str() {
+                            "number" => ParamType::Number,
+                            "string" => ParamType::String,
+                            "ident" => ParamType::Ident,
+                            _ => ParamType::Vector,
+                        };
+                        params.push(TemplateParam { name, ty });
                     }
-                    Rule::axiom_stmt => {
-                        let mut inner_rules = inner.into_inner();
-                        let name = inner_rules.next().unwrap().as_str().to_string();
-                        let expression = inner_rules
-                            .next()
-                            .unwrap()
-                            .as_str()
-                            .trim_matches('"')
-                            .to_string();
-                        ast.push(AstNode::Axiom { name, expression });
+                    _ => body = part.as_str().trim().to_string(),
+                }
+            }
+            AstNode::Template {
+                name,
+                params,
+                body,
+                doc,
+                span,
+            }
+        }
+        Rule::manifold_instance => {
+            let mut inner_rules = inner.into_inner();
+            let name = unquote(inner_rules.next().unwrap().as_str());
+            let template = inner_rules.next().unwrap().as_str().to_string();
+            let args = inner_rules
+                .map(|arg| match arg.as_rule() {
+                    Rule::number => TemplateArg::Number(parse_number(arg.as_str())),
+                    Rule::string_literal => TemplateArg::String(unquote(arg.as_str())),
+                    Rule::vector => TemplateArg::Vector(
+                        arg.into_inner()
+                            .map(|n| parse_number(n.as_str()) as f32)
+                            .collect(),
+                    ),
+                    _ => TemplateArg::Ident(arg.as_str().to_string()),
+                })
+                .collect();
+            AstNode::Instance {
+                name,
+                template,
+                args,
+                doc,
+                span,
+            }
+        }
+        Rule::resonate_stmt => {
+            let mut inner_rules = inner.into_inner();
+            let target = unquote(inner_rules.next().unwrap().as_str());
+            let mut frequency = Frequency::Hz(1.0);
+            let mut partner = None;
+            for arg in inner_rules {
+                match arg.as_rule() {
+                    Rule::number => frequency = parse_frequency(arg.as_str()),
+                    Rule::partner => {
+                        partner = Some(unquote(arg.into_inner().next().unwrap().as_str()))
                     }
-                    Rule::causality_stmt => {
-                        let mut inner_rules = inner.into_inner();
-                        let cause = inner_rules.next().unwrap().as_str().to_string();
-                        let effect = inner_rules.next().unwrap().as_str().to_string();
-                        let c_type = inner_rules.next().unwrap().as_str().to_string();
-                        ast.push(AstNode::Causality {
-                            cause,
-                            effect,
-                            c_type,
-                        });
+                    _ => {}
+                }
+            }
+            AstNode::Resonate {
+                target,
+                frequency,
+                partner,
+                doc,
+                span,
+            }
+        }
+        Rule::collapse_stmt => {
+            let mut inner_rules = inner.into_inner();
+            let target = unquote(inner_rules.next().unwrap().as_str());
+            let mut entropy_threshold = 0.5;
+            let mut condition = None;
+            for arg in inner_rules {
+                match arg.as_rule() {
+                    Rule::number => entropy_threshold = parse_number(arg.as_str()),
+                    Rule::where_clause => {
+                        let text = arg.into_inner().next().unwrap().as_str();
+                        condition = Some(text.trim().to_string());
                     }
                     _ => {}
                 }
             }
-            _ => {}
+            AstNode::Collapse {
+                target,
+                entropy_threshold,
+                condition,
+                doc,
+                span,
+            }
+        }
+        Rule::entrench_stmt => {
+            let mut inner_rules = inner.into_inner();
+            let key = unquote(inner_rules.next().unwrap().as_str());
+            let value = inner_rules.next().map(parse_literal);
+            AstNode::Entrench {
+                key,
+                value,
+                doc,
+                span,
+            }
+        }
+        Rule::schema_decl => {
+            let mut inner_rules = inner.into_inner();
+            let key = unquote(inner_rules.next().unwrap().as_str());
+            let ty = inner_rules.next().unwrap();
+            let ty = match ty.as_str() {
+                "string" => SchemaType::String,
+                "number" => SchemaType::Number,
+                "list" => SchemaType::List,
+                _ => {
+                    let vector = ty.into_inner().next().unwrap();
+                    let dims = vector.into_inner().next();
+                    // A dimension too large for `usize` can never match.
+                    SchemaType::Vector(dims.map(|d| d.as_str().parse().unwrap_or(usize::MAX)))
+                }
+            };
+            AstNode::Schema { key, ty, doc, span }
+        }
+        Rule::expect_stmt => {
+            let mut inner_rules = inner.into_inner();
+            let condition = inner_rules.next().unwrap().as_str().trim().to_string();
+            let action = inner_rules
+                .next()
+                .map(|action| Box::new(parse_statement(action, ctx)));
+            AstNode::Expect {
+                condition,
+                action,
+                doc,
+                span,
+            }
+        }
+        Rule::magnet_stmt => {
+            let mut inner_rules = inner.into_inner();
+            let label = unquote(inner_rules.next().unwrap().as_str());
+            let power = parse_number(inner_rules.next().unwrap().as_str());
+            AstNode::Magnet {
+                label,
+                power,
+                doc,
+                span,
+            }
         }
+        Rule::department_stmt => {
+            let mut inner_rules = inner.into_inner();
+            let name = inner_rules.next().unwrap().as_str().to_string();
+            let priority = parse_number(inner_rules.next().unwrap().as_str());
+            AstNode::Department {
+                name,
+                priority,
+                doc,
+                span,
+            }
+        }
+        Rule::reflection_stmt => AstNode::Reflect { doc, span },
+        Rule::axiom_stmt => {
+            let mut inner_rules = inner.into_inner();
+            let name = inner_rules.next().unwrap().as_str().to_string();
+            let expression = unquote(inner_rules.next().unwrap().as_str());
+            AstNode::Axiom {
+                name,
+                expression,
+                doc,
+                span,
+            }
+        }
+        Rule::causality_stmt => {
+            let mut inner_rules = inner.into_inner();
+            let cause = inner_rules.next().unwrap().as_str().to_string();
+            let effect = inner_rules.next().unwrap().as_str().to_string();
+            let c_type = inner_rules.next().unwrap().as_str().to_string();
+            AstNode::Causality {
+                cause,
+                effect,
+                c_type,
+                doc,
+                span,
+            }
+        }
+        Rule::import_stmt => {
+            let mut inner_rules = inner.into_inner();
+            let path = unquote(inner_rules.next().unwrap().as_str());
+            let alias = inner_rules.next().map(|a| a.as_str().to_string());
+            AstNode::Import {
+                path,
+                alias,
+                doc,
+                span,
+            }
+        }
+        Rule::native_fn => {
+            let source = inner.as_str().trim().to_string();
+            let name = inner
+                .into_inner()
+                .find(|p| p.as_rule() == Rule::identifier)
+                .unwrap()
+                .as_str()
+                .to_string();
+            AstNode::Native {
+                name,
+                source,
+                doc,
+                span,
+            }
+        }
+        Rule::property => {
+            let mut inner_rules = inner.into_inner();
+            let key = inner_rules.next().unwrap().as_str().to_string();
+            let mut value = inner_rules.next().unwrap();
+            if value.as_rule() == Rule::property_field {
+                value = value.into_inner().next().unwrap();
+            }
+            let value = match value.as_rule() {
+                Rule::property_literal => parse_literal(value.into_inner().next().unwrap()),
+                _ => EntrenchValue::String(value.as_str().trim().trim_end_matches(';').to_string()),
+            };
+            AstNode::Property {
+                key,
+                value,
+                doc,
+                span,
+            }
+        }
+        Rule::section => {
+            let mut inner_rules = inner.into_inner();
+            let name = inner_rules.next().unwrap().as_str().to_string();
+            let lines = inner_rules.map(|l| l.as_str().trim().to_string()).collect();
+            AstNode::Section {
+                name,
+                lines,
+                doc,
+                span,
+            }
+        }
+        Rule::marker => {
+            let mut inner_rules = inner.into_inner();
+            let name = inner_rules.next().unwrap().as_str().to_string();
+            let value = inner_rules.next().map(|v| v.as_str().trim().to_string());
+            AstNode::Marker {
+                name,
+                value,
+                doc,
+                span,
+            }
+        }
+        Rule::proclamation => {
+            let text = unquote(inner.into_inner().next().unwrap().as_str());
+            AstNode::Proclamation { text, doc, span }
+        }
+        rule => unreachable!("statement cannot contain {:?}", rule),
+    }
+}
+
+fn parse_literal(pair: Pair<Rule>) -> EntrenchValue {
+    match pair.as_rule() {
+        Rule::vector => EntrenchValue::Vector(
+            pair.into_inner()
+                .map(|n| parse_number(n.as_str()) as f32)
+                .collect(),
+        ),
+        Rule::string_list => {
+            EntrenchValue::StringList(pair.into_inner().map(|s| unquote(s.as_str())).collect())
+        }
+        Rule::string_literal => EntrenchValue::String(unquote(pair.as_str())),
+        Rule::number => EntrenchValue::Number(parse_number(pair.as_str()) as f32),
+        rule => unreachable!("literal cannot be {:?}", rule),
+    }
+}
+
+fn parse_frequency(text: &str) -> Frequency {
+    let digits = text.replace('_', "");
+    match digits.strip_prefix("0x") {
+        Some(hex) => {
+            let padded = if hex.len() % 2 == 1 {
+                format!("0{}", hex)
+            } else {
+                hex.to_string()
+            };
+            let bytes = (0..padded.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&padded[i..i + 2], 16).unwrap_or(0))
+                .collect();
+            Frequency::Signature(bytes)
+        }
+        _ => Frequency::Hz(parse_number(text)),
+    }
+}
+
+/// Strips the quotes of a `string_literal` and resolves its escapes. Bare
+/// identifiers pass through unchanged.
+fn unquote(text: &str) -> String {
+    let Some(inner) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')) else {
+        return text.to_string();
+    };
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('0') => out.push('\0'),
+            Some('u') => {
+                let code: String = chars.by_ref().skip(1).take_while(|c| *c != '}').collect();
+                let c = u32::from_str_radix(&code, 16).ok().and_then(char::from_u32);
+                out.push(c.unwrap_or(char::REPLACEMENT_CHARACTER));
+            }
+            Some(c) => out.push(c),
+            None => {}
+        }
+    }
+    out
+}
+
+/// Parses a `number` token: optional sign, `_` digit separators and `0x`
+/// hex literals. Hex literals wider than 53 bits lose precision.
+pub fn parse_number(text: &str) -> f64 {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    let digits = digits.replace('_', "");
+    let value = match digits.strip_prefix("0x") {
+        Some(hex) => hex
+            .chars()
+            .filter_map(|c| c.to_digit(16))
+            .fold(0.0, |acc, d| acc * 16.0 + d as f64),
+        None => digits.parse::<f64>().unwrap_or(0.0),
+    };
+    if negative {
+        -value
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diagnostic::render_all;
+
+    #[test]
+    fn spans_point_at_statements() {
+        let src = "manifold CORE {\n    resonate CORE 528.0;\n}\n";
+        let ast = parse_soul(src).unwrap();
+        let AstNode::Manifold { body, span, .. } = &ast[0] else {
+            panic!("expected manifold");
+        };
+        assert_eq!((span.line, span.column), (1, 1));
+        let inner = body[0].span();
+        assert_eq!((inner.line, inner.column), (2, 5));
+        assert_eq!(&src[inner.start..inner.end], "resonate CORE 528.0;");
+    }
+
+    #[test]
+    fn reports_every_broken_statement() {
+        let src = "manifold CORE {\n    resonate CORE 1.0 2.0;\n    collapse CORE;\n    department 5;\n}\nmagnet \"x\" 2.0;\nmanifold OPEN {\n    reflect;\n";
+        let (ast, diagnostics) = parse_soul_with_diagnostics(src);
+
+        assert_eq!(diagnostics.len(), 3, "{:#?}", diagnostics);
+        assert_eq!(diagnostics[0].span.line, 2);
+        assert_eq!(diagnostics[0].message, "expected statement, found `2.0`");
+        assert_eq!(diagnostics[1].span.line, 4);
+        assert_eq!(diagnostics[1].message, "expected identifier, found `5`");
+        assert_eq!(
+            diagnostics[2].message,
+            "expected `}` to close the block opened at 7:15, found end of input"
+        );
+
+        // Statements around the errors are still recovered.
+        let AstNode::Manifold { body, .. } = &ast[0] else {
+            panic!("expected manifold");
+        };
+        assert_eq!(body.len(), 2);
+        assert!(matches!(ast[1], AstNode::Magnet { .. }));
+        assert!(matches!(&ast[2], AstNode::Manifold { body, .. } if body.len() == 1));
+    }
+
+    #[test]
+    fn renders_code_frame() {
+        let src = "resonate CORE 1.0;\ncollapse 12;\n";
+        let err = parse_soul(src).unwrap_err();
+        let rendered = err.diagnostics()[0].render(src, "test.soul");
+        assert!(rendered.contains(" --> test.soul:2:10"), "{}", rendered);
+        assert!(rendered.contains("2 | collapse 12;"), "{}", rendered);
+        assert!(rendered.contains("  |          ^^"), "{}", rendered);
+
+        // A span past the end points at the end, header and frame alike.
+        let past = Span {
+            line: 9,
+            column: 4,
+            ..Span::default()
+        };
+        let rendered = Diagnostic::error("late", past).render("collapse 12;", "test.soul");
+        assert_eq!(
+            rendered,
+            "error: late\n --> test.soul:1:13\n  |\n1 | collapse 12;\n  |             ^\n"
+        );
+    }
+
+    #[test]
+    fn parses_every_soul_in_the_repo() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let mut checked = 0;
+        for dir in ["", "LwaS", "AETERNA_VAULT/SOULS", "OMEGA_VAULT"] {
+            for entry in std::fs::read_dir(root.join(dir)).unwrap() {
+                let path = entry.unwrap().path();
+                if path.extension().is_some_and(|e| e == "soul") {
+                    let src = std::fs::read_to_string(&path).unwrap();
+                    if let Err(err) = parse_soul(&src) {
+                        let origin = path.display().to_string();
+                        panic!("{}", render_all(&err.diagnostics(), &src, &origin));
+                    }
+                    checked += 1;
+                }
+            }
+        }
+        assert!(checked >= 10);
+    }
+
+    #[test]
+    fn keeps_spirit_fields_and_raw_bodies() {
+        let src = "spirit Oracle {\n    goal: \"say \\\"hi\\\"\\n\",\n    context: \"\\u{3A9}\",\n    depth: 3,\n    goalpost: \"far\"\n}\nbody Nested { if x { y(\"}\"); } }\nbody Snippet ````rust\nlet s = \"}\";\n```\n````\n";
+        let ast = parse_soul(src).unwrap();
+        assert_eq!(
+            ast[0],
+            AstNode::Spirit {
+                name: "Oracle".into(),
+                goal: "say \"hi\"\n".into(),
+                context: Some("Ω".into()),
+                fields: vec![
+                    ("depth".into(), EntrenchValue::Number(3.0)),
+                    ("goalpost".into(), EntrenchValue::String("far".into())),
+                ],
+                doc: None,
+                span: ast[0].span(),
+            }
+        );
+        assert!(matches!(&ast[1], AstNode::Body { content, fence: None, .. }
+            if content == "if x { y(\"}\"); }"));
+        assert!(
+            matches!(&ast[2], AstNode::Body { content, fence: Some(lang), .. }
+            if lang == "rust" && content == "let s = \"}\";\n```")
+        );
+
+        let err = parse_soul("immortal X = \"C:\\Users\";").unwrap_err();
+        assert_eq!(
+            err.diagnostics()[0].to_string(),
+            "1:17: error: unknown escape sequence `\\U`"
+        );
+    }
+
+    #[test]
+    fn attaches_doc_comments() {
+        let src = "/// banner ///\n\n//// not a doc\n/// First line.\n///\n///   indented\n\nentrench A 1; /// trailing\nentrench B 2;\n\n/// closing ///\n";
+        let ast = parse_soul(src).unwrap();
+        assert_eq!(ast[0].doc(), Some("First line.\n\n  indented"));
+        assert_eq!(ast[1].doc(), None);
+        assert_eq!(
+            doc_comment(src, ast[0].span().start).unwrap().0,
+            src.find("/// First").unwrap()
+        );
+
+        // Blocks followed by a plain comment or the end of the soul are
+        // kept apart.
+        let detached = detached_docs(src);
+        assert_eq!(detached.len(), 2, "{:?}", detached);
+        assert_eq!(detached[0].1, "banner ///");
+        assert_eq!((detached[0].0.start, detached[0].0.end), (0, 14));
+        assert_eq!(detached[1].1, "closing ///");
+        assert_eq!(detached[1].0.line, 11);
+
+        let banner = parse_soul("/// Seed.\n\nmanifold CORE {}\n").unwrap();
+        assert_eq!(banner[0].doc(), Some("Seed."));
+        assert!(detached_docs("body B { /// raw\n }\n").is_empty());
+    }
+
+    #[test]
+    fn numeric_literals() {
+        for (text, value) in [("0x4121", 16673.0), ("9_001.5", 9001.5), ("-0xf_f", -255.0)] {
+            let literal = LwasParser::parse(Rule::number, text).unwrap().as_str();
+            assert_eq!(literal, text);
+            assert_eq!(parse_number(literal), value);
+        }
+        // Digit separators go between digits, so only `-0` is a number here.
+        let literal = LwasParser::parse(Rule::number, "-0x_ff").unwrap();
+        assert_eq!(literal.as_str(), "-0");
+        for bad in [
+            "collapse CORE 1_;",
+            "resonate CORE 1__0;",
+            "collapse CORE 0.5_;",
+            "resonate CORE 0xf_;",
+        ] {
+            assert!(parse_soul(bad).is_err(), "{}", bad);
+        }
+
+        let ast =
+            parse_soul("reflection causes ORDER via FINAL\nresonate X with Y at 1.618Hz").unwrap();
+        assert!(matches!(&ast[0], AstNode::Causality { cause, .. } if cause == "reflection"));
+        assert!(matches!(
+            &ast[1],
+            AstNode::Resonate { partner: Some(p), frequency, .. } if p == "Y" && *frequency == Frequency::Hz(1.618)
+        ));
     }
-    ast
 }
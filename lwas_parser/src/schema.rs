@@ -0,0 +1,113 @@
+use crate::diagnostic::Diagnostic;
+use crate::parser::{AstNode, SchemaType};
+use crate::span::Span;
+use std::collections::HashMap;
+
+/// The `schema` declarations of a program. A schema applies to its key in
+/// every manifold, wherever it is declared.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SchemaSet {
+    pub schemas: HashMap<String, (SchemaType, Span)>,
+    /// Keys declared more than once, with the span of the later declaration.
+    redeclared: Vec<(String, Span)>,
+}
+
+impl SchemaSet {
+    pub fn from_ast(ast: &[AstNode]) -> Self {
+        let mut set = Self::default();
+        set.collect(ast);
+        set
+    }
+
+    fn collect(&mut self, nodes: &[AstNode]) {
+        for node in nodes {
+            match node {
+                AstNode::Manifold { body, .. } => self.collect(body),
+                AstNode::Schema { key, ty, span, .. } => {
+                    if self.schemas.contains_key(key) {
+                        self.redeclared.push((key.clone(), *span));
+                    } else {
+                        self.schemas.insert(key.clone(), (*ty, *span));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<SchemaType> {
+        self.schemas.get(key).map(|(ty, _)| *ty)
+    }
+
+    /// Reports redeclared schemas and every `entrench` whose value does not
+    /// fit its key's schema. Bare `entrench KEY` carries no value to check.
+    pub fn validate(&self, ast: &[AstNode]) -> Vec<Diagnostic> {
+        let mut diagnostics: Vec<Diagnostic> = self
+            .redeclared
+            .iter()
+            .map(|(key, span)| {
+                let line = self.schemas[key].1.line;
+                Diagnostic::error(
+                    format!("schema for `{}` is already declared on line {}", key, line),
+                    *span,
+                )
+            })
+            .collect();
+        self.check(ast, &mut diagnostics);
+        diagnostics
+    }
+
+    fn check(&self, nodes: &[AstNode], diagnostics: &mut Vec<Diagnostic>) {
+        for node in nodes {
+            match node {
+                AstNode::Manifold { body, .. } => self.check(body, diagnostics),
+                AstNode::Entrench {
+                    key,
+                    value: Some(value),
+                    span,
+                    ..
+                } => {
+                    let Some((ty, declared)) = self.schemas.get(key) else {
+                        continue;
+                    };
+                    if let Err(found) = ty.check(value) {
+                        diagnostics.push(Diagnostic::error(
+                            format!(
+                                "`{}` must be a {} by its schema on line {}, found {}",
+                                key, ty, declared.line, found
+                            ),
+                            *span,
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_soul;
+
+    #[test]
+    fn checks_entrenched_values_against_schemas() {
+        let ast = parse_soul(
+            "schema MISSION: string;\nschema EMBED: vector[3];\nmanifold CORE {\n    entrench MISSION \"ok\";\n    entrench EMBED [1, 2, 3];\n    entrench EMBED [1, 2];\n    entrench MISSION 4;\n    entrench EMBED;\n    entrench FREE 1;\n}\nschema EMBED: vector;\n",
+        )
+        .unwrap();
+        let set = SchemaSet::from_ast(&ast);
+        assert_eq!(set.get("EMBED"), Some(SchemaType::Vector(Some(3))));
+
+        let found: Vec<String> = set.validate(&ast).iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            found,
+            [
+                "11:1: error: schema for `EMBED` is already declared on line 2",
+                "6:5: error: `EMBED` must be a vector[3] by its schema on line 2, found a vector of 2 dimension(s)",
+                "7:5: error: `MISSION` must be a string by its schema on line 1, found a number",
+            ]
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use crate::causality::CausalityGraph;
+use crate::diagnostic::Diagnostic;
+use crate::expr::parse_expression;
+use crate::parser::AstNode;
+use crate::schema::SchemaSet;
+use crate::span::Span;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SymbolKind {
+    Immortal,
+    Manifold,
+    Body,
+    Spirit,
+    Axiom,
+    Department,
+    Magnet,
+    Import,
+    Entrench,
+    Property,
+    Template,
+}
+
+impl SymbolKind {
+    /// Definitions may appear once per scope; entrenched keys and properties
+    /// may be set again.
+    fn is_definition(self) -> bool {
+        !matches!(self, SymbolKind::Entrench | SymbolKind::Property)
+    }
+}
+
+impl fmt::Display for SymbolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SymbolKind::Immortal => "immortal",
+            SymbolKind::Manifold => "manifold",
+            SymbolKind::Body => "body",
+            SymbolKind::Spirit => "spirit",
+            SymbolKind::Axiom => "axiom",
+            SymbolKind::Department => "department",
+            SymbolKind::Magnet => "magnet",
+            SymbolKind::Import => "import",
+            SymbolKind::Entrench => "entrenched key",
+            SymbolKind::Property => "property",
+            SymbolKind::Template => "template",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub span: Span,
+}
+
+/// The root of a soul, or one `manifold` block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Scope {
+    /// `None` for the root scope.
+    pub name: Option<String>,
+    pub parent: Option<usize>,
+    pub span: Span,
+    pub symbols: Vec<Symbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolTable {
+    /// Index 0 is the root scope.
+    pub scopes: Vec<Scope>,
+}
+
+impl SymbolTable {
+    /// Looks `name` up in `scope` and then in its enclosing scopes.
+    pub fn resolve(&self, scope: usize, name: &str) -> Option<&Symbol> {
+        let mut current = Some(scope);
+        while let Some(index) = current {
+            let scope = &self.scopes[index];
+            if let Some(symbol) = scope.symbols.iter().find(|s| s.name == name) {
+                return Some(symbol);
+            }
+            current = scope.parent;
+        }
+        None
+    }
+}
+
+pub struct Analysis {
+    pub symbols: SymbolTable,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl Analysis {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics.iter().any(Diagnostic::is_error)
+    }
+}
+
+/// Resolves names and checks a parsed soul.
+///
+/// Errors: redeclared definitions (including any rewrite of an `immortal`),
+/// causality links to undeclared names or closing a forward-only cycle,
+/// `collapse` thresholds outside [0, 1], negative `department` priorities
+/// `entrench` values that do not fit their `schema` and `expect`
+/// conditions that are not valid expressions. Warnings:
+/// definitions shadowing an enclosing manifold's and properties set twice.
+pub fn analyze(ast: &[AstNode]) -> Analysis {
+    let mut analyzer = Analyzer {
+        table: SymbolTable {
+            scopes: vec![Scope {
+                name: None,
+                parent: None,
+                span: Span::default(),
+                symbols: Vec::new(),
+            }],
+        },
+        diagnostics: Vec::new(),
+    };
+    analyzer.block(0, ast);
+    let causality = CausalityGraph::from_ast(ast).validate(&analyzer.table);
+    analyzer.diagnostics.extend(causality);
+    let schemas = SchemaSet::from_ast(ast).validate(ast);
+    analyzer.diagnostics.extend(schemas);
+    Analysis {
+        symbols: analyzer.table,
+        diagnostics: analyzer.diagnostics,
+    }
+}
+
+struct Analyzer {
+    table: SymbolTable,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Analyzer {
+    /// Declares everything in the block first, so links may point forward.
+    fn block(&mut self, scope: usize, nodes: &[AstNode]) {
+        for node in nodes {
+            self.declare(scope, node);
+        }
+        for node in nodes {
+            self.check(scope, node);
+        }
+    }
+
+    fn declare(&mut self, scope: usize, node: &AstNode) {
+        let (name, kind) = match node {
+            AstNode::Immortal { name, .. } => (name, SymbolKind::Immortal),
+            AstNode::Manifold { name, .. } | AstNode::Instance { name, .. } => {
+                (name, SymbolKind::Manifold)
+            }
+            AstNode::Template { name, .. } => (name, SymbolKind::Template),
+            AstNode::Body { name, .. } => (name, SymbolKind::Body),
+            AstNode::Spirit { name, .. } => (name, SymbolKind::Spirit),
+            AstNode::Axiom { name, .. } => (name, SymbolKind::Axiom),
+            AstNode::Department { name, .. } => (name, SymbolKind::Department),
+            AstNode::Magnet { label, .. } => (label, SymbolKind::Magnet),
+            AstNode::Import {
+                alias: Some(alias), ..
+            } => (alias, SymbolKind::Import),
+            AstNode::Entrench { key, .. } => (key, SymbolKind::Entrench),
+            AstNode::Property { key, .. } => (key, SymbolKind::Property),
+            _ => return,
+        };
+        let span = node.span();
+
+        if let Some(existing) = self.table.resolve(scope, name).cloned() {
+            let local = self.table.scopes[scope].symbols.contains(&existing);
+            if existing.kind == SymbolKind::Immortal {
+                self.error(
+                    format!(
+                        "`{}` is immortal (line {}) and cannot be redeclared",
+                        name, existing.span.line
+                    ),
+                    span,
+                );
+                return;
+            }
+            // Entrenching a known name reinforces it rather than declaring it.
+            if kind == SymbolKind::Entrench {
+                return;
+            }
+            if local && kind.is_definition() && existing.kind.is_definition() {
+                self.error(
+                    format!(
+                        "`{}` is already declared as a {} on line {}",
+                        name, existing.kind, existing.span.line
+                    ),
+                    span,
+                );
+                return;
+            }
+            if local && kind == SymbolKind::Property && existing.kind == SymbolKind::Property {
+                self.warning(
+                    format!(
+                        "property `{}` is already set on line {}; the last value wins",
+                        name, existing.span.line
+                    ),
+                    span,
+                );
+                return;
+            }
+            if !local && kind.is_definition() {
+                self.warning(
+                    format!(
+                        "{} `{}` shadows the {} declared on line {}",
+                        kind, name, existing.kind, existing.span.line
+                    ),
+                    span,
+                );
+            }
+            if local {
+                return;
+            }
+        }
+
+        self.table.scopes[scope].symbols.push(Symbol {
+            name: name.clone(),
+            kind,
+            span,
+        });
+    }
+
+    fn check(&mut self, scope: usize, node: &AstNode) {
+        match node {
+            AstNode::Manifold {
+                name, body, span, ..
+            } => {
+                self.table.scopes.push(Scope {
+                    name: Some(name.clone()),
+                    parent: Some(scope),
+                    span: *span,
+                    symbols: Vec::new(),
+                });
+                let inner = self.table.scopes.len() - 1;
+                self.block(inner, body);
+            }
+            AstNode::Collapse {
+                entropy_threshold,
+                span,
+                ..
+            } if !(0.0..=1.0).contains(entropy_threshold) => {
+                self.error(
+                    format!("collapse threshold {} is outside [0, 1]", entropy_threshold),
+                    *span,
+                );
+            }
+            AstNode::Expect {
+                condition,
+                action,
+                span,
+                ..
+            } => {
+                if let Err(e) = parse_expression(condition) {
+                    self.error(format!("invalid expectation: {}", e), *span);
+                }
+                if let Some(action) = action {
+                    self.check(scope, action);
+                }
+            }
+            AstNode::Department { priority, span, .. } if *priority < 0.0 => {
+                self.error(
+                    format!("department priority {} must not be negative", priority),
+                    *span,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    fn error(&mut self, message: String, span: Span) {
+        self.diagnostics.push(Diagnostic::error(message, span));
+    }
+
+    fn warning(&mut self, message: String, span: Span) {
+        self.diagnostics.push(Diagnostic::warning(message, span));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_soul;
+
+    fn messages(source: &str) -> Vec<String> {
+        let ast = parse_soul(source).unwrap();
+        analyze(&ast)
+            .diagnostics
+            .iter()
+            .map(|d| d.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn builds_a_scope_per_manifold() {
+        let ast = parse_soul(
+            "immortal ROOT = \"x\";\nmanifold CORE {\n    entrench MISSION \"a\";\n    entrench MISSION \"b\";\n    manifold INNER { curvature: 0.5 }\n}\n",
+        )
+        .unwrap();
+        let analysis = analyze(&ast);
+        assert!(analysis.diagnostics.is_empty());
+
+        let table = &analysis.symbols;
+        assert_eq!(table.scopes.len(), 3);
+        assert_eq!(table.scopes[1].name.as_deref(), Some("CORE"));
+        assert_eq!(table.scopes[1].symbols.len(), 2);
+        assert_eq!(table.resolve(2, "MISSION").unwrap().span.line, 3);
+        assert_eq!(table.resolve(2, "ROOT").unwrap().kind, SymbolKind::Immortal);
+        assert!(table.resolve(0, "curvature").is_none());
+    }
+
+    #[test]
+    fn reports_undefined_duplicate_shadowed_and_out_of_range() {
+        let found = messages(
+            "immortal KEY = \"a\";\nimmortal KEY = \"b\";\nmanifold M {\n    department M 0.5;\n    department D -1.0;\n    collapse M 1.5;\n    M causes GHOST via FINAL;\n}\n",
+        );
+        assert_eq!(
+            found,
+            [
+                "2:1: error: `KEY` is immortal (line 1) and cannot be redeclared",
+                "4:5: warning: department `M` shadows the manifold declared on line 3",
+                "5:5: error: department priority -1 must not be negative",
+                "6:5: error: collapse threshold 1.5 is outside [0, 1]",
+                "7:5: error: causality link refers to undeclared `GHOST`",
+            ]
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use crate::diagnostic::Diagnostic;
+use crate::format::template_arg;
+use crate::parser::{parse_soul_with_diagnostics, AstNode, TemplateArg, TemplateParam};
+use crate::span::Span;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Replaces every `manifold X = T(...)` with the `manifold X { ... }` that
+/// template `T` expands to, and drops the template declarations.
+///
+/// Templates are visible throughout the program, wherever they are declared.
+/// Each `$param` in a template body, outside string literals, is replaced by
+/// its argument and the result is parsed as the manifold's body. Statements
+/// produced by an expansion take the span of the instantiation.
+///
+/// Errors: unknown or twice-declared templates, placeholders naming no
+/// parameter, argument counts or types that do not match the parameters,
+/// expansions that do not parse and templates that instantiate themselves.
+/// Instances with errors are dropped.
+pub fn expand_templates(ast: Vec<AstNode>) -> (Vec<AstNode>, Vec<Diagnostic>) {
+    let mut expander = Expander::default();
+    expander.collect(&ast);
+    let ast = expander.block(ast);
+    (ast, expander.diagnostics)
+}
+
+struct Template {
+    params: Vec<TemplateParam>,
+    body: String,
+}
+
+#[derive(Default)]
+struct Expander {
+    templates: HashMap<String, Template>,
+    diagnostics: Vec<Diagnostic>,
+    /// Templates being expanded, outermost first.
+    stack: Vec<String>,
+}
+
+impl Expander {
+    fn collect(&mut self, nodes: &[AstNode]) {
+        for node in nodes {
+            match node {
+                AstNode::Manifold { body, .. } => self.collect(body),
+                AstNode::Template {
+                    name,
+                    params,
+                    body,
+                    span,
+                    ..
+                } => {
+                    if self.templates.contains_key(name) {
+                        self.error(format!("template `{}` is declared twice", name), *span);
+                        continue;
+                    }
+                    for (i, param) in params.iter().enumerate() {
+                        if params[..i].iter().any(|p| p.name == param.name) {
+                            self.error(
+                                format!(
+                                    "template `{}` declares parameter `{}` twice",
+                                    name, param.name
+                                ),
+                                *span,
+                            );
+                        }
+                    }
+                    for (_, placeholder) in placeholders(body) {
+                        if !params.iter().any(|p| p.name == placeholder) {
+                            self.error(
+                                format!(
+                                    "template `{}` has no parameter `{}` for `${}`",
+                                    name, placeholder, placeholder
+                                ),
+                                *span,
+                            );
+                        }
+                    }
+                    self.templates.insert(
+                        name.clone(),
+                        Template {
+                            params: params.clone(),
+                            body: body.clone(),
+                        },
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn block(&mut self, nodes: Vec<AstNode>) -> Vec<AstNode> {
+        let mut out = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            match node {
+                AstNode::Template { .. } => {}
+                AstNode::Manifold {
+                    name,
+                    body,
+                    doc,
+                    span,
+                } => out.push(AstNode::Manifold {
+                    name,
+                    body: self.block(body),
+                    doc,
+                    span,
+                }),
+                AstNode::Instance {
+                    name,
+                    template,
+                    args,
+                    doc,
+                    span,
+                } => {
+                    if let Some(body) = self.instantiate(&template, &args, span) {
+                        out.push(AstNode::Manifold {
+                            name,
+                            body,
+                            doc,
+                            span,
+                        });
+                    }
+                }
+                node => out.push(node),
+            }
+        }
+        out
+    }
+
+    /// The statements of one instantiation, or `None` after reporting why
+    /// it cannot be expanded.
+    fn instantiate(
+        &mut self,
+        name: &str,
+        args: &[TemplateArg],
+        span: Span,
+    ) -> Option<Vec<AstNode>> {
+        let Some(template) = self.templates.get(name) else {
+            self.error(format!("unknown template `{}`", name), span);
+            return None;
+        };
+        if self.stack.iter().any(|t| t == name) {
+            let mut chain = self.stack.clone();
+            chain.push(name.to_string());
+            self.error(
+                format!(
+                    "template `{}` instantiates itself: {}",
+                    name,
+                    chain.join(" -> ")
+                ),
+                span,
+            );
+            return None;
+        }
+        if args.len() != template.params.len() {
+            let message = format!(
+                "template `{}` takes {} argument(s), found {}",
+                name,
+                template.params.len(),
+                args.len()
+            );
+            self.error(message, span);
+            return None;
+        }
+        let mismatches: Vec<String> = template
+            .params
+            .iter()
+            .zip(args)
+            .filter(|(param, arg)| param.ty != arg.ty())
+            .map(|(param, arg)| {
+                format!(
+                    "argument `{}` of template `{}` must be a {}, found a {}",
+                    param.name,
+                    name,
+                    param.ty,
+                    arg.ty()
+                )
+            })
+            .collect();
+        if !mismatches.is_empty() {
+            for message in mismatches {
+                self.error(message, span);
+            }
+            return None;
+        }
+
+        let text = substitute(&template.body, &template.params, args);
+        let (mut body, diagnostics) = parse_soul_with_diagnostics(&text);
+        if diagnostics.iter().any(Diagnostic::is_error) {
+            for diagnostic in diagnostics {
+                self.error(
+                    format!("in expansion of template `{}`: {}", name, diagnostic),
+                    span,
+                );
+            }
+            return None;
+        }
+
+        // Relocated first, so errors in nested instances point here too.
+        relocate(&mut body, span);
+        self.stack.push(name.to_string());
+        let body = self.block(body);
+        self.stack.pop();
+        Some(body)
+    }
+
+    fn error(&mut self, message: String, span: Span) {
+        self.diagnostics.push(Diagnostic::error(message, span));
+    }
+}
+
+/// Every `$name` outside string literals, with its byte range.
+fn placeholders(body: &str) -> Vec<(Range<usize>, &str)> {
+    let mut found = Vec::new();
+    let mut in_string = false;
+    let mut chars = body.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            '$' if !in_string => {
+                let rest = &body[i + 1..];
+                if !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                    continue;
+                }
+                let len = rest
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len());
+                found.push((i..i + 1 + len, &rest[..len]));
+            }
+            _ => {}
+        }
+    }
+    found
+}
+
+fn substitute(body: &str, params: &[TemplateParam], args: &[TemplateArg]) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut last = 0;
+    for (range, name) in placeholders(body) {
+        let Some(index) = params.iter().position(|p| p.name == name) else {
+            continue;
+        };
+        out.push_str(&body[last..range.start]);
+        out.push_str(&template_arg(&args[index]));
+        last = range.end;
+    }
+    out.push_str(&body[last..]);
+    out
+}
+
+fn relocate(nodes: &mut [AstNode], span: Span) {
+    for node in nodes {
+        *node.span_mut() = span;
+        match node {
+            AstNode::Manifold { body, .. } => relocate(body, span),
+            AstNode::Expect {
+                action: Some(action),
+                ..
+            } => relocate(std::slice::from_mut(&mut **action), span),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_soul, EntrenchValue};
+
+    fn expand(source: &str) -> (Vec<AstNode>, Vec<String>) {
+        let (ast, diagnostics) = expand_templates(parse_soul(source).unwrap());
+        (ast, diagnostics.iter().map(|d| d.to_string()).collect())
+    }
+
+    #[test]
+    fn expands_instances_into_manifolds() {
+        let (ast, errors) = expand(
+            "template Region(head: ident, weight: number, motto: string) {\n    department $head $weight;\n    entrench MOTTO $motto;\n    entrench NOTE \"$motto\";\n    manifold Inner = Leaf([1, 2]);\n}\ntemplate Leaf(v: vector) { entrench V $v; }\nmanifold EU = Region(Wealth, 0.8, \"Unity\");\n",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(ast.len(), 1);
+        let AstNode::Manifold {
+            name, body, span, ..
+        } = &ast[0]
+        else {
+            panic!("expected a manifold, found {:?}", ast[0]);
+        };
+        assert_eq!(name, "EU");
+        assert_eq!(span.line, 8);
+        assert!(
+            matches!(&body[0], AstNode::Department { name, priority, .. }
+            if name == "Wealth" && *priority == 0.8)
+        );
+        assert!(
+            matches!(&body[1], AstNode::Entrench { value: Some(EntrenchValue::String(s)), .. }
+            if s == "Unity")
+        );
+        // Placeholders inside strings are left alone.
+        assert!(
+            matches!(&body[2], AstNode::Entrench { value: Some(EntrenchValue::String(s)), .. }
+            if s == "$motto")
+        );
+        let AstNode::Manifold { body: inner, .. } = &body[3] else {
+            panic!("expected the nested instance to expand");
+        };
+        assert_eq!(inner[0].span(), *span);
+    }
+
+    #[test]
+    fn reports_arity_and_type_mismatches() {
+        let (ast, errors) = expand(
+            "template T(n: number, s: string) { magnet $s $n; entrench X $missing; }\nmanifold A = T(1.0);\nmanifold B = T(\"x\", 2.0);\nmanifold C = Nope();\ntemplate Loop() { manifold Again = Loop(); }\nmanifold D = Loop();\n",
+        );
+        // Only `D` expands, without its recursive instance.
+        assert_eq!(ast.len(), 1);
+        assert_eq!(
+            errors,
+            [
+                "1:1: error: template `T` has no parameter `missing` for `$missing`",
+                "2:1: error: template `T` takes 2 argument(s), found 1",
+                "3:1: error: argument `n` of template `T` must be a number, found a string",
+                "3:1: error: argument `s` of template `T` must be a string, found a number",
+                "4:1: error: unknown template `Nope`",
+                "6:1: error: template `Loop` instantiates itself: Loop -> Loop",
+            ]
+        );
+    }
+}